@@ -5,7 +5,7 @@ use crate::events::{
 };
 pub use crate::ext::*;
 use crate::internal::extract_base_uri;
-use crate::views::LeaseView;
+use crate::views::{DisputeVotingView, LeaseView};
 use crate::{models::ContractError, ShedaContract, ShedaContractExt};
 #[allow(unused_imports)]
 use crate::{models::*, HasNew};
@@ -27,12 +27,13 @@ fn checked_sub_u128(left: u128, right: u128, label: &str) -> u128 {
 
 #[near_bindgen]
 impl ShedaContract {
-    fn resolve_dispute_payout(
+    pub(crate) fn resolve_dispute_payout(
         &mut self,
         lease_id: u64,
         winner: DisputeWinner,
         payout_amount: U128,
     ) -> Result<(), ContractError> {
+        crate::jury::assert_no_panel(self, &DisputeCase::Lease { lease_id });
         let mut lease = self
             .leases
             .get(&lease_id)
@@ -53,33 +54,63 @@ impl ShedaContract {
                 .clone(),
         };
 
-        let payout = payout_amount.0.min(lease.escrow_held);
+        let escrow_held = lease.escrow_held;
         let escrow_token = lease.escrow_token.clone();
+        let property_id = lease.property_id;
 
         lease.dispute_status = DisputeStatus::Resolved;
+        let owner_claim = self.lease_dispute_claims.remove(&lease_id);
+        self.dispute_votes.remove(&lease_id);
         if let Some(info) = lease.dispute.as_mut() {
             info.oracle_result = Some(winner.clone());
             info.resolved_by = Some(env::signer_account_id());
             info.resolved_at = Some(env::block_timestamp());
         }
         self.leases.insert(lease_id, lease);
+        crate::bond::release(self, &DisputeCase::Lease { lease_id }, Some(&recipient));
         log!(
             "Dispute for lease {} resolved by admin {}",
             lease_id,
             env::signer_account_id()
         );
 
-        let current_balance = *self.stable_coin_balances.get(&escrow_token).unwrap_or(&0);
-        self.stable_coin_balances.insert(
-            escrow_token.clone(),
-            checked_sub_u128(current_balance, payout, "resolve_dispute payout"),
-        );
+        // An owner's claim is paid out of the damage deposit, never the
+        // lease payment the owner already has; a tenant who beats it is owed
+        // nothing. A dispute over damage-deposit deductions is paid out of the
+        // deposit too, with whatever the winner isn't awarded going to the
+        // other side.
+        let payout = match owner_claim {
+            Some(claim) => match winner {
+                DisputeWinner::Owner => {
+                    crate::deposit::pay_owner_claim(self, lease_id, payout_amount.0.min(claim))
+                }
+                DisputeWinner::Tenant => 0,
+            },
+            None => match crate::deposit::settle_disputed(self, lease_id, &winner, payout_amount.0)
+            {
+                Some(payout) => payout,
+                None => {
+                    let payout = payout_amount.0.min(escrow_held);
+                    let current_balance =
+                        *self.stable_coin_balances.get(&escrow_token).unwrap_or(&0);
+                    self.stable_coin_balances.insert(
+                        escrow_token.clone(),
+                        checked_sub_u128(current_balance, payout, "resolve_dispute payout"),
+                    );
 
-        #[allow(unused_must_use)]
-        ft_contract::ext(escrow_token)
-            .with_attached_deposit(NearToken::from_yoctonear(1))
-            .with_static_gas(Gas::from_tgas(30))
-            .ft_transfer(recipient.clone(), U128(payout));
+                    let payouts = crate::payout::owner_payouts(
+                        self,
+                        property_id,
+                        &escrow_token,
+                        recipient.clone(),
+                        payout,
+                    );
+                    #[allow(unused_must_use)]
+                    crate::payout::send_payouts(&escrow_token, payouts);
+                    payout
+                }
+            },
+        };
 
         emit_event(
             "DisputeResolved",
@@ -165,12 +196,24 @@ impl ShedaContract {
         self.leases
             .values()
             .filter(|lease| lease.dispute_status == DisputeStatus::Raised)
-            .map(|lease| lease.into())
+            .map(|lease| self.lease_to_view(lease))
             .collect()
     }
 
+    /// Admin votes on a raised lease dispute for the side that should win
+    /// and what it should be paid. Each admin has one vote and can change it
+    /// until voting closes. Once the quorum has voted and one side has the
+    /// majority, the dispute resolves itself, for the smallest payout any
+    /// admin on that side voted for. Without a `payout_amount` the vote is
+    /// for the owner's claim, if they made one, and otherwise for what the
+    /// lease still holds in escrow.
     #[payable]
-    pub fn vote_lease_dispute(&mut self, lease_id: u64, vote_for_tenant: bool) {
+    pub fn vote_lease_dispute(
+        &mut self,
+        lease_id: u64,
+        vote_for_tenant: bool,
+        payout_amount: Option<U128>,
+    ) {
         self.assert_admin();
         let mut lease = self
             .leases
@@ -182,7 +225,35 @@ impl ShedaContract {
             lease.dispute_status == DisputeStatus::Raised,
             "Dispute not active"
         );
+        let payout = payout_amount.map(|amount| amount.0).unwrap_or_else(|| {
+            self.lease_dispute_claims
+                .get(&lease_id)
+                .copied()
+                .unwrap_or(lease.escrow_held)
+        });
+
+        let now = env::block_timestamp();
+        let voting_period_ns = self.dispute_voting_period_ns;
+        let voting = self
+            .dispute_votes
+            .entry(lease_id)
+            .or_insert_with(|| DisputeVoting {
+                opened_at: now,
+                closes_at: now.saturating_add(voting_period_ns),
+                votes: Vec::new(),
+            });
+        require!(now <= voting.closes_at, "Voting on this dispute has closed");
+        let admin_id = env::predecessor_account_id();
+        voting.votes.retain(|vote| vote.admin_id != admin_id);
+        voting.votes.push(DisputeVote {
+            admin_id,
+            for_tenant: vote_for_tenant,
+            payout,
+            voted_at: now,
+        });
 
+        let voting = voting.clone();
+        let (votes_for_tenant, votes_for_owner) = self.count_dispute_votes(&voting);
         let mut info = lease.dispute.unwrap_or(DisputeInfo {
             raised_by: lease.tenant_id.clone(),
             raised_at: env::block_timestamp(),
@@ -195,15 +266,158 @@ impl ShedaContract {
             resolved_by: None,
             resolved_at: None,
         });
+        info.votes_for_tenant = votes_for_tenant;
+        info.votes_for_owner = votes_for_owner;
+
+        lease.dispute = Some(info);
+        self.leases.insert(lease_id, lease);
+
+        if let Some((winner, payout)) = self.dispute_vote_outcome(&voting, false) {
+            self.resolve_dispute_payout(lease_id, winner, U128(payout))
+                .unwrap_or_else(|error| env::panic_str(error.as_ref()));
+        }
+    }
+
+    /// Anyone closes the vote on a lease dispute once its voting period is
+    /// over: if the quorum voted, the side with more votes wins. A tie, or a
+    /// vote short of the quorum, is left for an admin to resolve.
+    pub fn close_dispute_vote(&mut self, lease_id: u64) {
+        let voting = self
+            .dispute_votes
+            .get(&lease_id)
+            .cloned()
+            .expect("No vote on this dispute");
+        require!(
+            env::block_timestamp() > voting.closes_at,
+            "Voting on this dispute is still open"
+        );
+        let (winner, payout) = self.dispute_vote_outcome(&voting, true).unwrap_or_else(|| {
+            env::panic_str(
+                "The vote ended without a quorum or a majority; an admin has to resolve it",
+            )
+        });
+        self.resolve_dispute_payout(lease_id, winner, U128(payout))
+            .unwrap_or_else(|error| env::panic_str(error.as_ref()));
+    }
+
+    pub fn get_dispute_votes(&self, lease_id: u64) -> Option<DisputeVotingView> {
+        self.dispute_votes
+            .get(&lease_id)
+            .map(|voting| voting.into())
+    }
 
-        if vote_for_tenant {
-            info.votes_for_tenant = info.votes_for_tenant.saturating_add(1);
+    /// Votes for each side, counting only those who are still admins.
+    fn count_dispute_votes(&self, voting: &DisputeVoting) -> (u64, u64) {
+        voting
+            .votes
+            .iter()
+            .filter(|vote| self.admins.contains(&vote.admin_id))
+            .fold((0, 0), |(tenant, owner), vote| {
+                if vote.for_tenant {
+                    (tenant + 1, owner)
+                } else {
+                    (tenant, owner + 1)
+                }
+            })
+    }
+
+    /// Who the vote says has won, and the smallest payout any admin on that
+    /// side voted for. Nothing is decided without the quorum. While voting is
+    /// open a side also needs the majority; once it's `closed`, more votes
+    /// than the other side will do.
+    fn dispute_vote_outcome(
+        &self,
+        voting: &DisputeVoting,
+        closed: bool,
+    ) -> Option<(DisputeWinner, u128)> {
+        let (for_tenant, for_owner) = self.count_dispute_votes(voting);
+        let total = for_tenant + for_owner;
+        let leader = for_tenant.max(for_owner);
+        let decided = total >= u64::from(self.dispute_quorum)
+            && if closed {
+                for_tenant != for_owner
+            } else {
+                u128::from(leader) * 10_000
+                    >= u128::from(self.dispute_majority_bps) * u128::from(total)
+            };
+        if !decided {
+            return None;
+        }
+
+        let tenant_wins = for_tenant > for_owner;
+        let payout = voting
+            .votes
+            .iter()
+            .filter(|vote| self.admins.contains(&vote.admin_id) && vote.for_tenant == tenant_wins)
+            .map(|vote| vote.payout)
+            .min()?;
+        let winner = if tenant_wins {
+            DisputeWinner::Tenant
         } else {
-            info.votes_for_owner = info.votes_for_owner.saturating_add(1);
+            DisputeWinner::Owner
+        };
+        Some((winner, payout))
+    }
+
+    /// How many admin votes settle a lease dispute, the share of them a side
+    /// needs (over half), and how long voting stays open. Each argument is
+    /// optional; `None` leaves that rule unchanged.
+    #[payable]
+    pub fn set_dispute_voting_rules(
+        &mut self,
+        quorum: Option<u32>,
+        majority_bps: Option<u16>,
+        voting_period_ns: Option<u64>,
+    ) {
+        self.assert_owner();
+
+        if let Some(quorum) = quorum {
+            require!(quorum > 0, "quorum must be greater than zero");
+            self.dispute_quorum = quorum;
+        }
+        if let Some(bps) = majority_bps {
+            require!(
+                bps > 5_000 && bps <= 10_000,
+                "majority_bps must be between 5001 and 10000"
+            );
+            self.dispute_majority_bps = bps;
+        }
+        if let Some(period) = voting_period_ns {
+            require!(period > 0, "voting_period_ns must be greater than zero");
+            self.dispute_voting_period_ns = period;
         }
 
-        lease.dispute = Some(info);
-        self.leases.insert(lease_id, lease);
+        log!(
+            "Dispute voting rules updated by owner {}",
+            env::signer_account_id()
+        );
+    }
+
+    /// The bond a tenant or bidder posts to raise a dispute, and whether a
+    /// lost bond goes to the treasury instead of the other side. Each
+    /// argument is optional; `None` leaves it unchanged. A zero bond lets
+    /// disputes be raised without one. Bonds already posted keep the terms
+    /// they were posted on.
+    #[payable]
+    pub fn set_dispute_bond_terms(
+        &mut self,
+        bond_amount: Option<U128>,
+        forfeit_to_treasury: Option<bool>,
+    ) {
+        self.assert_owner();
+
+        if let Some(amount) = bond_amount {
+            self.dispute_bond = amount.0;
+        }
+        if let Some(to_treasury) = forfeit_to_treasury {
+            self.dispute_bond_to_treasury = to_treasury;
+        }
+
+        log!(
+            "Dispute bond set to {} by owner {}",
+            self.dispute_bond,
+            env::signer_account_id()
+        );
     }
 
     /// Tune the buyer-cancellation windows without a redeploy.
@@ -268,6 +482,95 @@ impl ShedaContract {
         );
     }
 
+    /// Tune earnest-money purchases: the minimum deposit as bps of the offer,
+    /// the share of the deposit forfeited to the seller on default, and how
+    /// long after confirming the documents the buyer has to pay the balance.
+    #[payable]
+    pub fn set_earnest_money_terms(
+        &mut self,
+        min_deposit_bps: Option<u16>,
+        forfeit_bps: Option<u16>,
+        balance_due_window_ns: Option<u64>,
+    ) {
+        self.assert_owner();
+
+        for bps in [min_deposit_bps, forfeit_bps].into_iter().flatten() {
+            require!(bps <= 10_000, "Basis points can't exceed 10000");
+        }
+        if let Some(window) = balance_due_window_ns {
+            require!(
+                window > 0,
+                "balance_due_window_ns must be greater than zero"
+            );
+            self.balance_due_window_ns = window;
+        }
+        if let Some(bps) = min_deposit_bps {
+            self.earnest_min_deposit_bps = bps;
+        }
+        if let Some(bps) = forfeit_bps {
+            self.earnest_forfeit_bps = bps;
+        }
+
+        log!(
+            "Earnest-money terms updated by owner {}",
+            env::signer_account_id()
+        );
+    }
+
+    /// How long past a missed instalment the seller must wait before they
+    /// can foreclose.
+    #[payable]
+    pub fn set_instalment_grace_period(&mut self, grace_period_ns: u64) {
+        self.assert_owner();
+        self.instalment_grace_period_ns = grace_period_ns;
+        log!(
+            "Instalment grace period set to {} ns by owner {}",
+            grace_period_ns,
+            env::signer_account_id()
+        );
+    }
+
+    /// How long the owner has to file deductions once a lease ends, and the
+    /// tenant then has to answer them. Zero refunds deposits as soon as the
+    /// lease expires.
+    #[payable]
+    pub fn set_deposit_claim_window(&mut self, window_ns: u64) {
+        self.assert_owner();
+        self.deposit_claim_window_ns = window_ns;
+        log!(
+            "Deposit claim window set to {} ns by owner {}",
+            window_ns,
+            env::signer_account_id()
+        );
+    }
+
+    /// How long the other side of a newly raised dispute has to answer it
+    /// with evidence. Disputes already open keep their deadline.
+    #[payable]
+    pub fn set_dispute_response_period(&mut self, period_ns: u64) {
+        self.assert_owner();
+        self.dispute_response_period_ns = period_ns;
+        log!(
+            "Dispute response period set to {} ns by owner {}",
+            period_ns,
+            env::signer_account_id()
+        );
+    }
+
+    /// What ending a lease early costs whoever gives notice, as basis points
+    /// of the lease payment.
+    #[payable]
+    pub fn set_early_termination_penalty(&mut self, penalty_bps: u16) {
+        self.assert_owner();
+        require!(penalty_bps <= 10_000, "Basis points can't exceed 10000");
+        self.early_termination_penalty_bps = penalty_bps;
+        log!(
+            "Early termination penalty set to {} bps by owner {}",
+            penalty_bps,
+            env::signer_account_id()
+        );
+    }
+
     #[payable]
     pub fn set_oracle_account(&mut self, oracle_account: AccountId) {
         self.assert_owner();
@@ -483,6 +786,8 @@ impl ShedaContract {
     #[payable]
     pub fn refund_bids(&mut self, property_id: u64) {
         self.assert_admin();
+        // Co-bidders get back what they put in; the rest is each bidder's.
+        crate::joint::refund_pending_co_bidders(self, property_id, None);
         if let Some(bids) = self.bids.get_mut(&property_id) {
             for bid in bids.iter_mut() {
                 if bid.status != BidStatus::Pending {
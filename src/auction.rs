@@ -0,0 +1,1015 @@
+use near_sdk::{
+    assert_one_yocto, env, json_types::U128, log, near_bindgen, require, AccountId, Gas, NearToken,
+    Promise,
+};
+
+use crate::{
+    events::{
+        emit_event, AuctionCancelledEvent, AuctionSettledEvent, AuctionStartedEvent,
+        BidRefundedEvent, SealedBidCommittedEvent, SealedBidRevealedEvent,
+        SealedCommitRefundedEvent,
+    },
+    ext::ft_contract,
+    internal::{
+        assert_no_blocking_bids, checked_add_u128, checked_sub_u128, execute_accept_bid,
+        execute_accept_bid_with_escrow, update_bid_in_list,
+    },
+    models::{Action, Auction, AuctionMode, Bid, BidStatus, SealedCommit},
+    views::{AuctionView, SealedCommitView},
+    ShedaContract, ShedaContractExt,
+};
+
+/// The auction on a property, if one is still running or waiting to be
+/// settled. A settled auction is history and no longer constrains anything.
+pub(crate) fn open_auction(contract: &ShedaContract, property_id: u64) -> Option<Auction> {
+    contract
+        .auctions
+        .get(&property_id)
+        .filter(|auction| !auction.settled)
+        .cloned()
+}
+
+/// Refuse a manual acceptance while an auction decides the winner.
+///
+/// Letting the seller accept a bid by hand mid-auction would sell the property
+/// to whoever they liked regardless of the bids everyone else placed under the
+/// auction's rules.
+pub(crate) fn assert_no_open_auction(contract: &ShedaContract, property_id: u64) {
+    require!(
+        open_auction(contract, property_id).is_none(),
+        "This property is being auctioned — the winner is decided by settle_auction"
+    );
+}
+
+/// Refuse to unwind the bid currently leading an auction.
+///
+/// The leading bid is what every other bidder had to beat, and their bids
+/// were refunded on that basis. Letting it be cancelled (or rejected by the
+/// seller) would leave the auction with no leader and nobody left to sell to.
+pub(crate) fn assert_not_leading_bid(contract: &ShedaContract, property_id: u64, bid_id: u64) {
+    if let Some(Auction {
+        mode: AuctionMode::English { highest_bid_id, .. },
+        ..
+    }) = open_auction(contract, property_id)
+    {
+        require!(
+            highest_bid_id != Some(bid_id),
+            "This bid is leading an auction and stays committed until it is settled"
+        );
+    }
+}
+
+/// Apply an open auction's rules to a bid `ft_on_transfer` just recorded.
+///
+/// Called after the bid is stored, so any panic here rolls the whole transfer
+/// back and the token contract returns the funds to the bidder. Returns the
+/// part of the transfer to hand back, which `ft_on_transfer` passes on as its
+/// refund value.
+pub(crate) fn on_bid_placed(contract: &mut ShedaContract, property_id: u64, bid_id: u64) -> u128 {
+    let Some(auction) = open_auction(contract, property_id) else {
+        return 0;
+    };
+
+    let now = env::block_timestamp();
+    require!(now >= auction.start_time, "The auction has not started yet");
+
+    let bid = contract
+        .bids
+        .get(&property_id)
+        .and_then(|bids| bids.iter().find(|b| b.id == bid_id).cloned())
+        .expect("Bid not found for the property");
+    require!(
+        matches!(bid.action, Action::Purchase),
+        "Auctions only take Purchase bids"
+    );
+
+    match auction.mode {
+        AuctionMode::English { .. } => {
+            place_english_bid(contract, auction, bid, now);
+            0
+        }
+        AuctionMode::Sealed { .. } => {
+            env::panic_str("This auction takes sealed bids — send a SealedCommit instead")
+        }
+        AuctionMode::Dutch { .. } => buy_at_dutch_price(contract, auction, bid, now),
+    }
+}
+
+fn place_english_bid(contract: &mut ShedaContract, mut auction: Auction, bid: Bid, now: u64) {
+    require!(now < auction.end_time, "The auction has ended");
+
+    let AuctionMode::English {
+        reserve_price,
+        min_increment,
+        highest_bid_id,
+    } = auction.mode
+    else {
+        return;
+    };
+    let property_id = auction.property_id;
+
+    let previous = highest_bid_id.and_then(|id| {
+        contract
+            .bids
+            .get(&property_id)
+            .and_then(|bids| bids.iter().find(|b| b.id == id).cloned())
+    });
+
+    match &previous {
+        Some(previous) => {
+            let minimum = checked_add_u128(previous.amount, min_increment, "auction minimum");
+            require!(
+                bid.amount >= minimum,
+                format!(
+                    "Bid must be at least {} to beat the current highest",
+                    minimum
+                )
+            );
+            require!(
+                previous.stablecoin_token == bid.stablecoin_token,
+                "Auction bids must all be in the same stablecoin"
+            );
+        }
+        None => require!(bid.amount > 0, "Bid amount must be greater than zero"),
+    }
+
+    // The auction's own clock decides when this bid stops being live. The
+    // ordinary expiry would otherwise have execute_accept_bid reject a winning
+    // bid because the auction simply ran longer than bid_expiry_ns.
+    if let Some(bids) = contract.bids.get_mut(&property_id) {
+        let _ = update_bid_in_list(bids, bid.id, |b| {
+            b.expires_at = None;
+        });
+    }
+
+    auction.mode = AuctionMode::English {
+        reserve_price,
+        min_increment,
+        highest_bid_id: Some(bid.id),
+    };
+    contract.auctions.insert(property_id, auction);
+
+    if let Some(previous) = previous {
+        if previous.status == BidStatus::Pending {
+            refund_outbid(contract, property_id, previous.id, "outbid");
+        }
+    }
+}
+
+/// What a Dutch auction is asking at `now`: `start_price` until it starts,
+/// falling linearly to `floor_price` at `end_time`, and the floor after that.
+pub(crate) fn dutch_price(auction: &Auction, now: u64) -> Option<u128> {
+    let AuctionMode::Dutch {
+        start_price,
+        floor_price,
+        ..
+    } = auction.mode
+    else {
+        return None;
+    };
+
+    if now <= auction.start_time {
+        return Some(start_price);
+    }
+    if now >= auction.end_time {
+        return Some(floor_price);
+    }
+
+    let elapsed = u128::from(now - auction.start_time);
+    let duration = u128::from(auction.end_time - auction.start_time);
+    let decay = (start_price - floor_price)
+        .checked_mul(elapsed)
+        .unwrap_or_else(|| env::panic_str("Overflow in dutch_price"))
+        / duration;
+    Some(start_price - decay)
+}
+
+/// The first bid that meets the current Dutch price buys the property.
+///
+/// The bid is trimmed to the price, the rest goes back through the
+/// `ft_on_transfer` refund, and the sale runs through the same acceptance path
+/// the seller would have used by hand — the fast path, or escrow if the
+/// listing asked for it.
+fn buy_at_dutch_price(
+    contract: &mut ShedaContract,
+    mut auction: Auction,
+    bid: Bid,
+    now: u64,
+) -> u128 {
+    let AuctionMode::Dutch { use_escrow, .. } = auction.mode else {
+        return 0;
+    };
+    let property_id = auction.property_id;
+
+    let price = dutch_price(&auction, now).unwrap_or_default();
+    require!(
+        bid.amount >= price,
+        format!("The current price is {}", price)
+    );
+    let overpayment = bid.amount - price;
+
+    if let Some(bids) = contract.bids.get_mut(&property_id) {
+        let _ = update_bid_in_list(bids, bid.id, |b| {
+            b.amount = price;
+            b.expires_at = None;
+        });
+    }
+    if overpayment > 0 {
+        let current_balance = *contract
+            .stable_coin_balances
+            .get(&bid.stablecoin_token)
+            .unwrap_or(&0);
+        contract.stable_coin_balances.insert(
+            bid.stablecoin_token.clone(),
+            checked_sub_u128(current_balance, overpayment, "dutch overpayment"),
+        );
+    }
+
+    auction.settled = true;
+    contract.auctions.insert(property_id, auction);
+
+    emit_event(
+        "AuctionSettled",
+        AuctionSettledEvent {
+            token_id: property_id,
+            winning_bid_id: Some(bid.id),
+            winner_id: Some(bid.bidder.clone()),
+            amount: price,
+            reserve_met: true,
+        },
+    );
+
+    if use_escrow {
+        execute_accept_bid_with_escrow(contract, property_id, bid.id);
+    } else {
+        #[allow(unused_must_use)]
+        execute_accept_bid(contract, property_id, bid.id);
+    }
+
+    overpayment
+}
+
+/// The commitment a sealed bid has to match on reveal.
+///
+/// Binding the bidder into the preimage stops anyone from copying another
+/// bidder's hash and revealing it once the original is revealed.
+pub(crate) fn sealed_bid_commitment(bidder: &AccountId, amount: u128, salt: &str) -> String {
+    env::sha256(format!("{}:{}:{}", bidder, amount, salt).as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Record a sealed commitment funded through `ft_on_transfer`.
+///
+/// The deposit goes into the escrow balance like any bid, but no `Bid`
+/// exists until the reveal picks a winner — so nothing about the amount the
+/// bidder has in mind shows up in `get_bids_for_property`.
+pub(crate) fn record_sealed_commit(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    bidder: AccountId,
+    deposit: u128,
+    commitment: String,
+) {
+    let auction = open_auction(contract, property_id)
+        .filter(|auction| matches!(auction.mode, AuctionMode::Sealed { .. }))
+        .expect("This property is not taking sealed bids");
+
+    let now = env::block_timestamp();
+    require!(now >= auction.start_time, "The auction has not started yet");
+    require!(now < auction.end_time, "The commit window has closed");
+    require!(deposit > 0, "A sealed bid needs a deposit");
+    require!(
+        commitment.len() == 64 && commitment.chars().all(|c| c.is_ascii_hexdigit()),
+        "Commitment must be a hex-encoded sha256 hash"
+    );
+
+    let stablecoin_token = env::predecessor_account_id();
+    let commits = contract.sealed_commits.entry(property_id).or_default();
+    // Reveals are compared by amount alone, which only means anything if
+    // every deposit is in the same token.
+    if let Some(first) = commits.first() {
+        require!(
+            first.stablecoin_token == stablecoin_token,
+            "Sealed bids must all be in the same stablecoin"
+        );
+    }
+
+    let commit_id = contract.bid_counter;
+    commits.push(SealedCommit {
+        id: commit_id,
+        property_id,
+        bidder: bidder.clone(),
+        commitment: commitment.to_ascii_lowercase(),
+        deposit,
+        stablecoin_token: stablecoin_token.clone(),
+        created_at: now,
+        revealed_amount: None,
+        refunded: false,
+    });
+    contract.bid_counter = contract
+        .bid_counter
+        .checked_add(1)
+        .unwrap_or_else(|| env::panic_str("Overflow in bid_counter"));
+
+    let current_balance = *contract
+        .stable_coin_balances
+        .get(&stablecoin_token)
+        .unwrap_or(&0);
+    contract.stable_coin_balances.insert(
+        stablecoin_token,
+        checked_add_u128(current_balance, deposit, "sealed bid deposit"),
+    );
+
+    emit_event(
+        "SealedBidCommitted",
+        SealedBidCommittedEvent {
+            token_id: property_id,
+            commit_id,
+            bidder_id: bidder,
+            deposit,
+        },
+    );
+}
+
+/// Refund a bid that no longer has a chance of winning.
+///
+/// Same optimistic pattern as the other refund paths: decrement the ledger,
+/// mark the bid `Rejected`, and let `refund_pending_bid_callback` put both
+/// back if the transfer fails.
+fn refund_outbid(contract: &mut ShedaContract, property_id: u64, bid_id: u64, reason: &str) {
+    // Co-bidders get back what they put in; the rest is the bidder's.
+    crate::joint::refund_co_bidders(contract, property_id, bid_id);
+    let bid = {
+        let bids = contract
+            .bids
+            .get_mut(&property_id)
+            .expect("Bid does not exist");
+        update_bid_in_list(bids, bid_id, |b| {
+            b.status = BidStatus::Rejected;
+            b.updated_at = env::block_timestamp();
+        })
+    };
+
+    let current_balance = *contract
+        .stable_coin_balances
+        .get(&bid.stablecoin_token)
+        .unwrap_or(&0);
+    contract.stable_coin_balances.insert(
+        bid.stablecoin_token.clone(),
+        checked_sub_u128(current_balance, bid.amount, "auction refund"),
+    );
+
+    emit_event(
+        "BidRefunded",
+        BidRefundedEvent {
+            token_id: property_id,
+            bid_id,
+            bidder_id: bid.bidder.clone(),
+            amount: bid.amount,
+            reason: reason.to_string(),
+        },
+    );
+
+    #[allow(unused_must_use)]
+    ft_contract::ext(bid.stablecoin_token.clone())
+        .with_attached_deposit(NearToken::from_yoctonear(1))
+        .with_static_gas(Gas::from_tgas(30))
+        .ft_transfer(bid.bidder.clone(), U128(bid.amount))
+        .then(
+            ShedaContract::ext(env::current_account_id())
+                .with_static_gas(Gas::from_tgas(20))
+                .refund_pending_bid_callback(
+                    property_id,
+                    bid_id,
+                    bid.stablecoin_token.clone(),
+                    bid.amount,
+                ),
+        );
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Shared by every `start_*_auction`: who may start one, on what, and
+    /// recording it.
+    fn begin_auction(
+        &mut self,
+        property_id: u64,
+        start_time: Option<u64>,
+        end_time: u64,
+        mode: AuctionMode,
+        reserve_price: u128,
+    ) {
+        let property = self
+            .properties
+            .get(&property_id)
+            .expect("Property not found")
+            .clone();
+
+        assert_eq!(
+            property.owner_id,
+            env::predecessor_account_id(),
+            "Only the property owner can auction it"
+        );
+        require!(property.is_for_sale, "Property is not for sale");
+        require!(
+            property.active_lease.is_none(),
+            "Cannot auction a property with an active lease"
+        );
+        require!(
+            open_auction(self, property_id).is_none(),
+            "This property already has an auction in progress"
+        );
+
+        // Bids placed before the auction weren't made under its rules, so the
+        // seller has to clear them first rather than have them silently
+        // compete (or silently lose).
+        assert_no_blocking_bids(self, property_id, "auction");
+
+        // A previous sealed auction's commits are only history once every
+        // deposit is back with its bidder; anyone can push those through
+        // with claim_sealed_refund.
+        if let Some(commits) = self.sealed_commits.get(&property_id) {
+            require!(
+                commits.iter().all(|commit| commit.refunded),
+                "A previous sealed auction still has deposits to refund"
+            );
+        }
+        self.sealed_commits.remove(&property_id);
+
+        let now = env::block_timestamp();
+        let start_time = start_time.unwrap_or(now).max(now);
+        require!(
+            end_time > start_time,
+            "The auction must end after it starts"
+        );
+
+        let mode_name = match mode {
+            AuctionMode::English { .. } => "English",
+            AuctionMode::Sealed { .. } => "Sealed",
+            AuctionMode::Dutch { .. } => "Dutch",
+        };
+        self.auctions.insert(
+            property_id,
+            Auction {
+                property_id,
+                seller_id: property.owner_id.clone(),
+                start_time,
+                end_time,
+                mode,
+                settled: false,
+            },
+        );
+
+        emit_event(
+            "AuctionStarted",
+            AuctionStartedEvent {
+                token_id: property_id,
+                seller_id: property.owner_id,
+                mode: mode_name.to_string(),
+                start_time,
+                end_time,
+                reserve_price,
+            },
+        );
+    }
+
+    /// Put a listed property up for an English auction.
+    ///
+    /// Bids come in through `ft_transfer_call` as usual; each must beat the
+    /// current highest by `min_increment`, and the bid it beats is refunded
+    /// immediately. Once `end_time` passes anyone can call `settle_auction`,
+    /// which sells to the highest bidder if they reached `reserve_price`.
+    ///
+    /// `start_time` in the past (or `None`) starts the auction now.
+    #[payable]
+    pub fn start_auction(
+        &mut self,
+        property_id: u64,
+        start_time: Option<u64>,
+        end_time: u64,
+        reserve_price: U128,
+        min_increment: U128,
+    ) {
+        assert_one_yocto();
+        require!(
+            min_increment.0 > 0,
+            "min_increment must be greater than zero"
+        );
+
+        self.begin_auction(
+            property_id,
+            start_time,
+            end_time,
+            AuctionMode::English {
+                reserve_price: reserve_price.0,
+                min_increment: min_increment.0,
+                highest_bid_id: None,
+            },
+            reserve_price.0,
+        );
+    }
+
+    /// Put a listed property up for a sealed-bid tender.
+    ///
+    /// Until `commit_end` bidders send a deposit through `ft_transfer_call`
+    /// with a `SealedCommit` message carrying the hash of their bid (see
+    /// `hash_sealed_bid`). Between `commit_end` and `reveal_end` each reveals
+    /// amount and salt with `reveal_sealed_bid`. After `reveal_end`,
+    /// `settle_auction` hands the highest valid reveal at or above
+    /// `reserve_price` to the escrow flow, exactly as if the seller had
+    /// called `accept_bid_with_escrow` on it.
+    #[payable]
+    pub fn start_sealed_auction(
+        &mut self,
+        property_id: u64,
+        start_time: Option<u64>,
+        commit_end: u64,
+        reveal_end: u64,
+        reserve_price: U128,
+    ) {
+        assert_one_yocto();
+        require!(
+            reveal_end > commit_end,
+            "The reveal window must end after the commit window"
+        );
+
+        self.begin_auction(
+            property_id,
+            start_time,
+            commit_end,
+            AuctionMode::Sealed {
+                reserve_price: reserve_price.0,
+                reveal_end,
+                leading_commit_id: None,
+            },
+            reserve_price.0,
+        );
+    }
+
+    /// Put a listed property up for a Dutch auction.
+    ///
+    /// The asking price falls linearly from `start_price` at `start_time` to
+    /// `floor_price` at `end_time`, then holds at the floor. The first
+    /// `ft_transfer_call` bid that covers the current price buys the property
+    /// outright — no acceptance step — and anything paid above the price is
+    /// returned with the transfer. With `use_escrow` the sale goes through the
+    /// escrow flow instead of paying the seller immediately.
+    #[payable]
+    pub fn start_dutch_auction(
+        &mut self,
+        property_id: u64,
+        start_time: Option<u64>,
+        end_time: u64,
+        start_price: U128,
+        floor_price: U128,
+        use_escrow: bool,
+    ) {
+        assert_one_yocto();
+        require!(floor_price.0 > 0, "floor_price must be greater than zero");
+        require!(
+            start_price.0 >= floor_price.0,
+            "start_price must not be below floor_price"
+        );
+
+        self.begin_auction(
+            property_id,
+            start_time,
+            end_time,
+            AuctionMode::Dutch {
+                start_price: start_price.0,
+                floor_price: floor_price.0,
+                use_escrow,
+            },
+            floor_price.0,
+        );
+    }
+
+    /// Withdraw an auction before anyone has bid on it.
+    ///
+    /// Once there is a bid the seller is committed: bidders were refunded on
+    /// the understanding that the auction runs to its end.
+    #[payable]
+    pub fn cancel_auction(&mut self, property_id: u64) {
+        assert_one_yocto();
+
+        let auction = open_auction(self, property_id).expect("No open auction for this property");
+        assert_eq!(
+            auction.seller_id,
+            env::predecessor_account_id(),
+            "Only the seller can cancel the auction"
+        );
+
+        let has_bids = match auction.mode {
+            AuctionMode::English { highest_bid_id, .. } => highest_bid_id.is_some(),
+            AuctionMode::Sealed { .. } => self
+                .sealed_commits
+                .get(&property_id)
+                .is_some_and(|commits| !commits.is_empty()),
+            // Nobody can have bid: the first bid that meets the price ends it.
+            AuctionMode::Dutch { .. } => false,
+        };
+        require!(
+            !has_bids,
+            "The auction already has bids and must run to its end"
+        );
+
+        self.auctions.remove(&property_id);
+
+        emit_event(
+            "AuctionCancelled",
+            AuctionCancelledEvent {
+                token_id: property_id,
+                seller_id: auction.seller_id,
+            },
+        );
+    }
+
+    /// Open a sealed bid during the reveal window.
+    ///
+    /// A reveal for more than the deposit behind it is recorded but can't
+    /// win — the contract has no way to collect the difference. That commit
+    /// is refunded like any other losing one.
+    pub fn reveal_sealed_bid(
+        &mut self,
+        property_id: u64,
+        commit_id: u64,
+        amount: U128,
+        salt: String,
+    ) {
+        let mut auction =
+            open_auction(self, property_id).expect("No open auction for this property");
+        let AuctionMode::Sealed {
+            reserve_price,
+            reveal_end,
+            leading_commit_id,
+        } = auction.mode
+        else {
+            env::panic_str("This is not a sealed-bid auction");
+        };
+
+        let now = env::block_timestamp();
+        require!(now >= auction.end_time, "The commit window is still open");
+        require!(now < reveal_end, "The reveal window has closed");
+
+        let commits = self
+            .sealed_commits
+            .get_mut(&property_id)
+            .expect("No sealed bids for this property");
+        let leading_amount = leading_commit_id
+            .and_then(|id| commits.iter().find(|commit| commit.id == id))
+            .and_then(|commit| commit.revealed_amount)
+            .unwrap_or(0);
+        let commit = commits
+            .iter_mut()
+            .find(|commit| commit.id == commit_id)
+            .expect("Sealed bid not found");
+
+        assert_eq!(
+            commit.bidder,
+            env::predecessor_account_id(),
+            "Only the bidder can reveal their bid"
+        );
+        require!(
+            commit.revealed_amount.is_none(),
+            "This bid has already been revealed"
+        );
+        require!(
+            sealed_bid_commitment(&commit.bidder, amount.0, &salt) == commit.commitment,
+            "Amount and salt do not match the commitment"
+        );
+
+        commit.revealed_amount = Some(amount.0);
+        let valid = amount.0 > 0 && amount.0 <= commit.deposit;
+        let bidder_id = commit.bidder.clone();
+
+        // Ties go to whoever revealed first.
+        if valid && amount.0 > leading_amount {
+            auction.mode = AuctionMode::Sealed {
+                reserve_price,
+                reveal_end,
+                leading_commit_id: Some(commit_id),
+            };
+            self.auctions.insert(property_id, auction);
+        }
+
+        emit_event(
+            "SealedBidRevealed",
+            SealedBidRevealedEvent {
+                token_id: property_id,
+                commit_id,
+                bidder_id,
+                amount: amount.0,
+                valid,
+            },
+        );
+    }
+
+    /// Close an auction once it is over. Callable by anyone.
+    ///
+    /// English: if the highest bid met the reserve this goes through the same
+    /// fast path as `accept_bid` — the seller is paid and the NFT moves to
+    /// the winner. Otherwise the highest bid is refunded and the property
+    /// stays with the seller, still listed.
+    ///
+    /// Sealed (after the reveal window): the winning reveal becomes an
+    /// accepted `Bid` in escrow and the deal carries on through the document
+    /// steps. Every other deposit, and the winner's excess, is returned
+    /// through `claim_sealed_refund`.
+    pub fn settle_auction(&mut self, property_id: u64) -> Option<Promise> {
+        let mut auction =
+            open_auction(self, property_id).expect("No open auction for this property");
+
+        let closes_at = match auction.mode {
+            AuctionMode::English { .. } => auction.end_time,
+            AuctionMode::Sealed { reveal_end, .. } => reveal_end,
+            AuctionMode::Dutch { .. } => env::panic_str(
+                "A Dutch auction settles on the first payment that meets its price; \
+                 use cancel_auction to withdraw it",
+            ),
+        };
+        require!(
+            env::block_timestamp() >= closes_at,
+            "The auction has not ended yet"
+        );
+
+        let mode = auction.mode.clone();
+        auction.settled = true;
+        self.auctions.insert(property_id, auction);
+
+        match mode {
+            AuctionMode::English {
+                reserve_price,
+                highest_bid_id,
+                ..
+            } => self.settle_english(property_id, reserve_price, highest_bid_id),
+            AuctionMode::Sealed {
+                reserve_price,
+                leading_commit_id,
+                ..
+            } => {
+                self.settle_sealed(property_id, reserve_price, leading_commit_id);
+                None
+            }
+            AuctionMode::Dutch { .. } => None,
+        }
+    }
+
+    fn settle_english(
+        &mut self,
+        property_id: u64,
+        reserve_price: u128,
+        highest_bid_id: Option<u64>,
+    ) -> Option<Promise> {
+        let highest = highest_bid_id.and_then(|id| {
+            self.bids
+                .get(&property_id)
+                .and_then(|bids| bids.iter().find(|b| b.id == id).cloned())
+        });
+
+        let Some(highest) = highest.filter(|bid| bid.status == BidStatus::Pending) else {
+            log!("Auction on property {} closed with no bids", property_id);
+            emit_event(
+                "AuctionSettled",
+                AuctionSettledEvent {
+                    token_id: property_id,
+                    winning_bid_id: None,
+                    winner_id: None,
+                    amount: 0,
+                    reserve_met: false,
+                },
+            );
+            return None;
+        };
+
+        let reserve_met = highest.amount >= reserve_price;
+        emit_event(
+            "AuctionSettled",
+            AuctionSettledEvent {
+                token_id: property_id,
+                winning_bid_id: reserve_met.then_some(highest.id),
+                winner_id: reserve_met.then(|| highest.bidder.clone()),
+                amount: highest.amount,
+                reserve_met,
+            },
+        );
+
+        if !reserve_met {
+            log!(
+                "Auction on property {} closed below the reserve ({} < {}); highest bid refunded",
+                property_id,
+                highest.amount,
+                reserve_price
+            );
+            refund_outbid(self, property_id, highest.id, "auction_reserve_not_met");
+            return None;
+        }
+
+        Some(execute_accept_bid(self, property_id, highest.id))
+    }
+
+    fn settle_sealed(
+        &mut self,
+        property_id: u64,
+        reserve_price: u128,
+        leading_commit_id: Option<u64>,
+    ) {
+        let leader = leading_commit_id.and_then(|id| {
+            self.sealed_commits
+                .get(&property_id)
+                .and_then(|commits| commits.iter().find(|commit| commit.id == id).cloned())
+        });
+        let winner = leader.and_then(|commit| {
+            let amount = commit.revealed_amount.unwrap_or(0);
+            (amount >= reserve_price).then_some((commit, amount))
+        });
+
+        let Some((commit, amount)) = winner else {
+            log!(
+                "Sealed auction on property {} closed without a winner; deposits are claimable",
+                property_id
+            );
+            emit_event(
+                "AuctionSettled",
+                AuctionSettledEvent {
+                    token_id: property_id,
+                    winning_bid_id: None,
+                    winner_id: None,
+                    amount: 0,
+                    reserve_met: false,
+                },
+            );
+            return;
+        };
+
+        // The winner's deposit covered the bid, so nothing is refunded unless
+        // they over-funded — and then only the difference.
+        if commit.deposit == amount {
+            if let Some(commits) = self.sealed_commits.get_mut(&property_id) {
+                if let Some(entry) = commits.iter_mut().find(|c| c.id == commit.id) {
+                    entry.refunded = true;
+                }
+            }
+        }
+
+        let now = env::block_timestamp();
+        self.bids.entry(property_id).or_default().push(Bid {
+            id: commit.id,
+            bidder: commit.bidder.clone(),
+            property_id,
+            amount,
+            created_at: commit.created_at,
+            updated_at: now,
+            status: BidStatus::Pending,
+            document_token_id: None,
+            document_image_uri: None,
+            document_description: None,
+            escrow_release_tx: None,
+            dispute_reason: None,
+            expires_at: None,
+            escrow_release_after: None,
+            action: Action::Purchase,
+            stablecoin_token: commit.stablecoin_token.clone(),
+            lease_id: None,
+        });
+
+        emit_event(
+            "AuctionSettled",
+            AuctionSettledEvent {
+                token_id: property_id,
+                winning_bid_id: Some(commit.id),
+                winner_id: Some(commit.bidder),
+                amount,
+                reserve_met: true,
+            },
+        );
+
+        execute_accept_bid_with_escrow(self, property_id, commit.id);
+    }
+
+    /// Return a sealed-bid deposit once the auction is settled.
+    ///
+    /// Callable by anyone — the money always goes to the bidder, and the
+    /// seller needs every deposit cleared before auctioning the property
+    /// again. For the winner this returns only what they deposited beyond
+    /// their winning bid.
+    pub fn claim_sealed_refund(&mut self, property_id: u64, commit_id: u64) -> Promise {
+        require!(
+            open_auction(self, property_id).is_none(),
+            "Deposits are refunded once the auction is settled"
+        );
+
+        // The winning commit's Bid shares its id and holds what it paid.
+        let committed = self
+            .bids
+            .get(&property_id)
+            .and_then(|bids| bids.iter().find(|bid| bid.id == commit_id))
+            .map(|bid| bid.amount)
+            .unwrap_or(0);
+
+        let commit = {
+            let commits = self
+                .sealed_commits
+                .get_mut(&property_id)
+                .expect("No sealed bids for this property");
+            let commit = commits
+                .iter_mut()
+                .find(|commit| commit.id == commit_id)
+                .expect("Sealed bid not found");
+            require!(!commit.refunded, "This deposit has already been refunded");
+            commit.refunded = true;
+            commit.clone()
+        };
+
+        let amount = checked_sub_u128(commit.deposit, committed, "sealed refund");
+        require!(amount > 0, "Nothing to refund");
+
+        let current_balance = *self
+            .stable_coin_balances
+            .get(&commit.stablecoin_token)
+            .unwrap_or(&0);
+        self.stable_coin_balances.insert(
+            commit.stablecoin_token.clone(),
+            checked_sub_u128(current_balance, amount, "sealed refund"),
+        );
+
+        ft_contract::ext(commit.stablecoin_token.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(Gas::from_tgas(30))
+            .ft_transfer(commit.bidder.clone(), U128(amount))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas::from_tgas(20))
+                    .claim_sealed_refund_callback(
+                        property_id,
+                        commit_id,
+                        commit.stablecoin_token,
+                        amount,
+                    ),
+            )
+    }
+
+    #[private]
+    pub fn claim_sealed_refund_callback(
+        &mut self,
+        property_id: u64,
+        commit_id: u64,
+        stablecoin_token: AccountId,
+        amount: u128,
+    ) {
+        let commits = self.sealed_commits.get_mut(&property_id);
+        let Some(commit) =
+            commits.and_then(|commits| commits.iter_mut().find(|commit| commit.id == commit_id))
+        else {
+            return;
+        };
+
+        if crate::internal::promise_failed(0) {
+            // Put it back so the refund can be claimed again.
+            commit.refunded = false;
+            let current_balance = *self
+                .stable_coin_balances
+                .get(&stablecoin_token)
+                .unwrap_or(&0);
+            self.stable_coin_balances.insert(
+                stablecoin_token,
+                checked_add_u128(current_balance, amount, "sealed refund revert"),
+            );
+            log!("Sealed bid refund failed, deposit restored");
+        } else {
+            emit_event(
+                "SealedCommitRefunded",
+                SealedCommitRefundedEvent {
+                    token_id: property_id,
+                    commit_id,
+                    bidder_id: commit.bidder.clone(),
+                    amount,
+                },
+            );
+        }
+    }
+
+    pub fn get_auction(&self, property_id: u64) -> Option<AuctionView> {
+        self.auctions
+            .get(&property_id)
+            .map(|auction| auction.into())
+    }
+
+    /// Sealed bids on a property. The amount stays hidden until revealed.
+    pub fn get_sealed_commits(&self, property_id: u64) -> Vec<SealedCommitView> {
+        self.sealed_commits
+            .get(&property_id)
+            .map(|commits| commits.iter().map(|commit| commit.into()).collect())
+            .unwrap_or_default()
+    }
+
+    /// The commitment to send for a sealed bid.
+    ///
+    /// A convenience for clients and tests: the RPC node answering this sees
+    /// the amount, so bidders who care should compute the same
+    /// `sha256("{bidder}:{amount}:{salt}")` locally instead.
+    pub fn hash_sealed_bid(&self, bidder: AccountId, amount: U128, salt: String) -> String {
+        sealed_bid_commitment(&bidder, amount.0, &salt)
+    }
+}
@@ -0,0 +1,187 @@
+use near_sdk::{env, json_types::U128, log, near_bindgen, require, AccountId};
+
+use crate::{
+    events::{emit_event, DisputeBondSettledEvent},
+    internal::{checked_add_u128, credit_escrow, debit_escrow},
+    models::{DisputeBond, DisputeCase, DisputeMsg},
+    views::DisputeBondView,
+    ShedaContract, ShedaContractExt,
+};
+
+/// Called by the plain dispute methods: once a bond is required, disputes
+/// can only be raised by sending it.
+pub(crate) fn assert_no_bond_required(contract: &ShedaContract) {
+    require!(
+        contract.dispute_bond == 0,
+        format!(
+            "Raising a dispute takes a bond of {}; send it with ft_transfer_call",
+            contract.dispute_bond
+        )
+    );
+}
+
+fn assert_lease_token(contract: &ShedaContract, lease_id: u64, token: &AccountId) {
+    let escrow_token = contract
+        .leases
+        .get(&lease_id)
+        .map(|lease| &lease.escrow_token)
+        .expect("Lease not found");
+    require!(
+        token == escrow_token,
+        "The bond must be in the lease's stablecoin"
+    );
+}
+
+/// Handle an `ft_transfer_call` carrying a `DisputeMsg`: post the bond and
+/// raise the dispute. Returns the part of the transfer to hand back.
+pub(crate) fn on_transfer(
+    contract: &mut ShedaContract,
+    sender_id: AccountId,
+    amount: u128,
+    msg: DisputeMsg,
+) -> u128 {
+    let bond = contract.dispute_bond;
+    require!(
+        bond > 0,
+        "No dispute bond is required; raise the dispute directly"
+    );
+    require!(amount >= bond, format!("The dispute bond is {}", bond));
+    let token = env::predecessor_account_id();
+
+    let case = match msg {
+        DisputeMsg::RaiseLeaseDispute { lease_id, reason } => {
+            assert_lease_token(contract, lease_id, &token);
+            crate::internal::internal_raise_dispute(
+                contract,
+                sender_id.clone(),
+                lease_id,
+                reason,
+                bond,
+            );
+            DisputeCase::Lease { lease_id }
+        }
+        DisputeMsg::RaiseOwnerLeaseDispute {
+            lease_id,
+            reason,
+            claim,
+        } => {
+            assert_lease_token(contract, lease_id, &token);
+            crate::internal::internal_raise_owner_dispute(
+                contract,
+                sender_id.clone(),
+                lease_id,
+                reason,
+                claim.0,
+                bond,
+            );
+            DisputeCase::Lease { lease_id }
+        }
+        DisputeMsg::DisputeDepositDeductions { lease_id, reason } => {
+            assert_lease_token(contract, lease_id, &token);
+            crate::deposit::dispute_deductions(contract, sender_id.clone(), lease_id, reason, bond);
+            DisputeCase::Lease { lease_id }
+        }
+        DisputeMsg::RaiseBidDispute {
+            property_id,
+            bid_id,
+            reason,
+        } => {
+            let stablecoin_token = contract
+                .bids
+                .get(&property_id)
+                .and_then(|bids| bids.iter().find(|bid| bid.id == bid_id))
+                .map(|bid| bid.stablecoin_token.clone())
+                .expect("Bid does not exist");
+            require!(
+                token == stablecoin_token,
+                "The bond must be in the bid's stablecoin"
+            );
+            crate::internal::internal_raise_bid_dispute(
+                contract,
+                sender_id.clone(),
+                property_id,
+                bid_id,
+                reason,
+                bond,
+            );
+            DisputeCase::Bid {
+                property_id,
+                bid_id,
+            }
+        }
+    };
+
+    credit_escrow(contract, &token, bond, "dispute bond");
+    contract.dispute_bonds.insert(
+        case,
+        DisputeBond {
+            posted_by: sender_id,
+            stablecoin_token: token,
+            amount: bond,
+            forfeit_to_treasury: contract.dispute_bond_to_treasury,
+        },
+    );
+    amount - bond
+}
+
+/// Release the bond on a dispute that has been decided for `winner_id`.
+/// The bond goes back to whoever posted it unless the other side won, when
+/// it's paid to them or to the treasury. With no winner — a split — it goes
+/// back too. Disputes raised without a bond have nothing to release.
+pub(crate) fn release(
+    contract: &mut ShedaContract,
+    case: &DisputeCase,
+    winner_id: Option<&AccountId>,
+) {
+    let Some(bond) = contract.dispute_bonds.remove(case) else {
+        return;
+    };
+    let forfeited = winner_id.is_some_and(|winner_id| *winner_id != bond.posted_by);
+    let token = bond.stablecoin_token;
+
+    debit_escrow(contract, &token, bond.amount, "dispute bond");
+    let paid_to = if !forfeited {
+        Some(bond.posted_by.clone())
+    } else if bond.forfeit_to_treasury {
+        let accrued = contract.accrued_fees.entry(token.clone()).or_insert(0);
+        *accrued = checked_add_u128(*accrued, bond.amount, "accrued fees");
+        None
+    } else {
+        winner_id.cloned()
+    };
+    if let Some(recipient) = paid_to.clone() {
+        #[allow(unused_must_use)]
+        crate::payout::send_payouts(&token, vec![(recipient, bond.amount)]);
+    }
+
+    log!(
+        "Dispute bond of {} posted by {} {}",
+        bond.amount,
+        bond.posted_by,
+        if forfeited { "forfeited" } else { "returned" }
+    );
+    emit_event(
+        "DisputeBondSettled",
+        DisputeBondSettledEvent {
+            case: case.clone(),
+            posted_by: bond.posted_by,
+            amount: bond.amount,
+            forfeited,
+            paid_to,
+        },
+    );
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// The bond held against a dispute, if one was posted.
+    pub fn get_dispute_bond(&self, case: DisputeCase) -> Option<DisputeBondView> {
+        self.dispute_bonds.get(&case).map(|bond| bond.into())
+    }
+
+    /// The bond a dispute takes to raise, and whether a lost one goes to the
+    /// treasury.
+    pub fn get_dispute_bond_terms(&self) -> (U128, bool) {
+        (U128(self.dispute_bond), self.dispute_bond_to_treasury)
+    }
+}
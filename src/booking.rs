@@ -0,0 +1,467 @@
+use near_sdk::{assert_one_yocto, env, json_types::U128, near_bindgen, require, AccountId};
+
+use crate::{
+    events::{emit_event, BookingCancelledEvent, BookingSettledEvent, StayBookedEvent},
+    internal::{checked_add_u128, checked_add_u64, checked_mul_u64, credit_escrow, debit_escrow},
+    models::{Action, Booking, BookingStatus, BookingTerms, CancellationPolicy},
+    views::{BookedRangeView, BookingTermsView, BookingView, MAX_PAGINATION_LIMIT},
+    ShedaContract, ShedaContractExt,
+};
+
+const NIGHT_NS: u64 = 24 * 60 * 60 * 1_000_000_000;
+const NIGHTS_PER_WEEK: u32 = 7;
+
+fn owner_of(contract: &ShedaContract, property_id: u64) -> AccountId {
+    contract
+        .properties
+        .get(&property_id)
+        .expect("Property not found")
+        .owner_id
+        .clone()
+}
+
+fn booking_for(contract: &ShedaContract, booking_id: u64) -> Booking {
+    contract
+        .bookings
+        .get(&booking_id)
+        .cloned()
+        .expect("Booking not found")
+}
+
+fn terms_for(contract: &ShedaContract, property_id: u64) -> BookingTerms {
+    contract
+        .booking_terms
+        .get(&property_id)
+        .cloned()
+        .expect("Property isn't open for short stays")
+}
+
+fn check_out_of(check_in: u64, nights: u32) -> u64 {
+    checked_add_u64(
+        check_in,
+        checked_mul_u64(u64::from(nights), NIGHT_NS, "booking nights"),
+        "booking check_out",
+    )
+}
+
+/// Every full week at the weekly rate, if there is one, and the nights left
+/// over at the nightly rate.
+fn price_of(terms: &BookingTerms, nights: u32) -> u128 {
+    let (weeks, extra_nights) = match terms.weekly_rate {
+        Some(_) => (nights / NIGHTS_PER_WEEK, nights % NIGHTS_PER_WEEK),
+        None => (0, nights),
+    };
+    checked_add_u128(
+        terms.weekly_rate.unwrap_or(0) * u128::from(weeks),
+        terms.nightly_rate * u128::from(extra_nights),
+        "booking price",
+    )
+}
+
+/// A booking that still holds its nights on the calendar.
+fn holds_calendar(booking: &Booking) -> bool {
+    booking.status != BookingStatus::Cancelled
+}
+
+/// Bookings on the property that hold any of the nights in
+/// `[check_in, check_out)`.
+fn clashing_bookings(
+    contract: &ShedaContract,
+    property_id: u64,
+    check_in: u64,
+    check_out: u64,
+) -> Vec<(u64, Booking)> {
+    contract
+        .property_bookings
+        .get(&property_id)
+        .map(|booking_ids| {
+            booking_ids
+                .iter()
+                .filter_map(|id| Some((*id, contract.bookings.get(id)?.clone())))
+                .filter(|(_, booking)| holds_calendar(booking))
+                .filter(|(_, booking)| booking.check_in < check_out && check_in < booking.check_out)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// What the guest gets back for cancelling now, under the policy they booked.
+fn guest_refund(booking: &Booking, now: u64) -> u128 {
+    let notice = booking.check_in.saturating_sub(now);
+    let half = booking.amount / 2;
+    match booking.cancellation_policy {
+        CancellationPolicy::Flexible if notice >= NIGHT_NS => booking.amount,
+        CancellationPolicy::Flexible => 0,
+        CancellationPolicy::Moderate if notice >= 5 * NIGHT_NS => booking.amount,
+        CancellationPolicy::Moderate => half,
+        CancellationPolicy::Strict if notice >= 7 * NIGHT_NS => half,
+        CancellationPolicy::Strict => 0,
+    }
+}
+
+/// A guest is staying or due to. An unsettled stay still owes the current
+/// owner, so it counts too.
+pub(crate) fn has_upcoming_bookings(contract: &ShedaContract, property_id: u64) -> bool {
+    let now = env::block_timestamp();
+    clashing_bookings(contract, property_id, 0, u64::MAX)
+        .into_iter()
+        .any(|(_, booking)| booking.status == BookingStatus::Confirmed || booking.check_out > now)
+}
+
+/// A property with short stays still to come can't be sold or let out from
+/// under its guests. Called wherever a bid is accepted.
+pub(crate) fn assert_no_upcoming_bookings(contract: &ShedaContract, property_id: u64) {
+    require!(
+        !has_upcoming_bookings(contract, property_id),
+        "The property has short stays booked; settle or cancel them first"
+    );
+}
+
+/// Book `nights` nights from `check_in` with an `ft_on_transfer` `Book`.
+///
+/// The nights must be free on the calendar and the property not let on a
+/// lease. The payment is held until check-in, when `settle_booking` pays it
+/// to the owner. Returns what was sent beyond the price.
+pub(crate) fn book(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    check_in: u64,
+    nights: u32,
+    sender_id: AccountId,
+    amount: u128,
+) -> u128 {
+    let terms = terms_for(contract, property_id);
+    let stablecoin_token = env::predecessor_account_id();
+    let has_active_lease = contract
+        .properties
+        .get(&property_id)
+        .expect("Property not found")
+        .active_lease
+        .as_ref()
+        .is_some_and(|lease| lease.active);
+    require!(!has_active_lease, "The property is let on a lease");
+    crate::instalment::assert_no_lien(contract, property_id);
+    require!(
+        sender_id != owner_of(contract, property_id),
+        "The owner can't book their own property"
+    );
+
+    let now = env::block_timestamp();
+    require!(check_in > now, "Check-in must be in the future");
+    require!(
+        nights >= terms.min_nights && nights <= terms.max_nights,
+        format!(
+            "Stays run from {} to {} nights",
+            terms.min_nights, terms.max_nights
+        )
+    );
+    let check_out = check_out_of(check_in, nights);
+    require!(
+        clashing_bookings(contract, property_id, check_in, check_out).is_empty(),
+        "Those nights are already booked"
+    );
+
+    let price = price_of(&terms, nights);
+    require!(amount >= price, format!("The stay comes to {}", price));
+
+    let booking_id = contract.booking_counter;
+    contract.booking_counter = checked_add_u64(contract.booking_counter, 1, "booking_counter");
+    contract.bookings.insert(
+        booking_id,
+        Booking {
+            property_id,
+            guest_id: sender_id.clone(),
+            check_in,
+            check_out,
+            nights,
+            amount: price,
+            stablecoin_token: stablecoin_token.clone(),
+            cancellation_policy: terms.cancellation_policy,
+            status: BookingStatus::Confirmed,
+            refunded: 0,
+            created_at: now,
+        },
+    );
+    contract
+        .property_bookings
+        .entry(property_id)
+        .or_default()
+        .push(booking_id);
+    credit_escrow(contract, &stablecoin_token, price, "booking");
+
+    emit_event(
+        "StayBooked",
+        StayBookedEvent {
+            token_id: property_id,
+            booking_id,
+            guest_id: sender_id,
+            check_in,
+            check_out,
+            amount: price,
+        },
+    );
+
+    amount - price
+}
+
+/// Send a booking's payment out and settle the ledger: `guest_refund` back
+/// to the guest and the rest, less fees, to the owner as they've asked.
+fn pay_out(
+    contract: &mut ShedaContract,
+    booking: &Booking,
+    owner_id: AccountId,
+    guest_refund: u128,
+) {
+    debit_escrow(
+        contract,
+        &booking.stablecoin_token,
+        booking.amount,
+        "booking",
+    );
+
+    let net = crate::fees::charge(
+        contract,
+        booking.property_id,
+        &Action::Lease,
+        &booking.stablecoin_token,
+        &owner_id,
+        booking.amount - guest_refund,
+    );
+    let mut payouts = crate::payout::owner_payouts(
+        contract,
+        booking.property_id,
+        &booking.stablecoin_token,
+        owner_id,
+        net,
+    );
+    payouts.push((booking.guest_id.clone(), guest_refund));
+    #[allow(unused_must_use)]
+    crate::payout::send_payouts(&booking.stablecoin_token, payouts);
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Owner opens a property for short stays, priced per night, or per week
+    /// for stays of a week or more if `weekly_rate` is set. Guests book with
+    /// an `ft_on_transfer` `Book` in any accepted stablecoin. New terms apply
+    /// to bookings made from now on.
+    #[payable]
+    pub fn set_booking_terms(
+        &mut self,
+        property_id: u64,
+        nightly_rate: U128,
+        weekly_rate: Option<U128>,
+        min_nights: u32,
+        max_nights: u32,
+        cancellation_policy: CancellationPolicy,
+    ) {
+        assert_one_yocto();
+
+        assert_eq!(
+            owner_of(self, property_id),
+            env::predecessor_account_id(),
+            "Only the property owner can set booking terms"
+        );
+        // The instalment buyer is living there.
+        crate::instalment::assert_no_lien(self, property_id);
+        require!(nightly_rate.0 > 0, "Nightly rate must be greater than zero");
+        require!(
+            weekly_rate.is_none_or(|rate| rate.0 > 0),
+            "Weekly rate must be greater than zero"
+        );
+        require!(
+            min_nights > 0 && min_nights <= max_nights,
+            "Minimum stay must be at least a night and no longer than the maximum"
+        );
+
+        self.booking_terms.insert(
+            property_id,
+            BookingTerms {
+                nightly_rate: nightly_rate.0,
+                weekly_rate: weekly_rate.map(|rate| rate.0),
+                min_nights,
+                max_nights,
+                cancellation_policy,
+            },
+        );
+    }
+
+    /// Owner stops taking bookings. Stays already booked go ahead.
+    #[payable]
+    pub fn clear_booking_terms(&mut self, property_id: u64) {
+        assert_one_yocto();
+
+        assert_eq!(
+            owner_of(self, property_id),
+            env::predecessor_account_id(),
+            "Only the property owner can clear booking terms"
+        );
+        self.booking_terms.remove(&property_id);
+    }
+
+    /// Guest or owner cancels a stay before check-in. The guest gets back
+    /// what the booking's cancellation policy allows, and the owner keeps the
+    /// rest; the owner cancelling refunds the guest in full. The nights are
+    /// freed either way.
+    #[payable]
+    pub fn cancel_booking(&mut self, booking_id: u64) -> U128 {
+        assert_one_yocto();
+
+        let booking = booking_for(self, booking_id);
+        let owner_id = owner_of(self, booking.property_id);
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == booking.guest_id || caller == owner_id,
+            "Only the guest or the owner can cancel a booking"
+        );
+        require!(
+            booking.status == BookingStatus::Confirmed,
+            "Booking isn't confirmed"
+        );
+        let now = env::block_timestamp();
+        require!(now < booking.check_in, "The stay has already begun");
+
+        let refunded = if caller == owner_id {
+            booking.amount
+        } else {
+            guest_refund(&booking, now)
+        };
+        let retained = booking.amount - refunded;
+
+        if let Some(booking) = self.bookings.get_mut(&booking_id) {
+            booking.status = BookingStatus::Cancelled;
+            booking.refunded = refunded;
+        }
+        pay_out(self, &booking, owner_id, refunded);
+
+        emit_event(
+            "BookingCancelled",
+            BookingCancelledEvent {
+                token_id: booking.property_id,
+                booking_id,
+                cancelled_by: caller,
+                refunded,
+                retained,
+            },
+        );
+
+        U128(refunded)
+    }
+
+    /// Pay a stay to the owner once the guest has checked in. Anyone may
+    /// call it.
+    pub fn settle_booking(&mut self, booking_id: u64) {
+        let booking = booking_for(self, booking_id);
+        require!(
+            booking.status == BookingStatus::Confirmed,
+            "Booking isn't confirmed"
+        );
+        require!(
+            env::block_timestamp() >= booking.check_in,
+            "The stay hasn't begun"
+        );
+        let owner_id = owner_of(self, booking.property_id);
+
+        if let Some(booking) = self.bookings.get_mut(&booking_id) {
+            booking.status = BookingStatus::Completed;
+        }
+        pay_out(self, &booking, owner_id.clone(), 0);
+
+        emit_event(
+            "BookingSettled",
+            BookingSettledEvent {
+                token_id: booking.property_id,
+                booking_id,
+                owner_id,
+                amount: booking.amount,
+            },
+        );
+    }
+
+    pub fn get_booking_terms(&self, property_id: u64) -> Option<BookingTermsView> {
+        self.booking_terms
+            .get(&property_id)
+            .map(|terms| terms.into())
+    }
+
+    pub fn get_booking(&self, booking_id: u64) -> Option<BookingView> {
+        self.bookings
+            .get(&booking_id)
+            .map(|booking| (booking_id, booking).into())
+    }
+
+    /// A property's bookings in the order they were made, cancelled ones
+    /// included.
+    pub fn get_bookings_by_property(
+        &self,
+        property_id: u64,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<BookingView> {
+        let limit = limit.min(MAX_PAGINATION_LIMIT);
+        self.property_bookings
+            .get(&property_id)
+            .map(|booking_ids| {
+                booking_ids
+                    .iter()
+                    .skip(from_index as usize)
+                    .take(limit as usize)
+                    .filter_map(|id| Some((*id, self.bookings.get(id)?).into()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn get_bookings_by_guest(&self, guest_id: AccountId) -> Vec<BookingView> {
+        self.bookings
+            .iter()
+            .filter(|(_, booking)| booking.guest_id == guest_id)
+            .map(|(booking_id, booking)| (*booking_id, booking).into())
+            .collect()
+    }
+
+    /// The property's calendar between `from` and `to`: every booked stretch
+    /// that overlaps it, earliest first.
+    pub fn get_booked_ranges(&self, property_id: u64, from: u64, to: u64) -> Vec<BookedRangeView> {
+        let mut ranges: Vec<BookedRangeView> = clashing_bookings(self, property_id, from, to)
+            .into_iter()
+            .map(|(booking_id, booking)| BookedRangeView {
+                booking_id,
+                check_in: booking.check_in,
+                check_out: booking.check_out,
+            })
+            .collect();
+        ranges.sort_by_key(|range| range.check_in);
+        ranges
+    }
+
+    /// Whether `nights` nights from `check_in` could be booked right now.
+    pub fn is_available(&self, property_id: u64, check_in: u64, nights: u32) -> bool {
+        let Some(terms) = self.booking_terms.get(&property_id) else {
+            return false;
+        };
+        let has_active_lease = self
+            .properties
+            .get(&property_id)
+            .and_then(|property| property.active_lease.as_ref())
+            .is_some_and(|lease| lease.active);
+        let Some(check_out) = u64::from(nights)
+            .checked_mul(NIGHT_NS)
+            .and_then(|length| check_in.checked_add(length))
+        else {
+            return false;
+        };
+        !has_active_lease
+            && check_in > env::block_timestamp()
+            && nights >= terms.min_nights
+            && nights <= terms.max_nights
+            && clashing_bookings(self, property_id, check_in, check_out).is_empty()
+    }
+
+    /// What a stay of `nights` nights would cost under the current terms.
+    pub fn quote_booking(&self, property_id: u64, nights: u32) -> Option<String> {
+        self.booking_terms
+            .get(&property_id)
+            .map(|terms| price_of(terms, nights).to_string())
+    }
+}
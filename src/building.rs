@@ -0,0 +1,301 @@
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, AccountId};
+
+use crate::{
+    events::{emit_event, BuildingUnitAddedEvent, BuildingUnitRemovedEvent},
+    internal::checked_add_u128,
+    models::{Action, BookingStatus, Property},
+    views::{BuildingSummaryView, PropertyView, UnitOccupancyView},
+    ShedaContract, ShedaContractExt,
+};
+
+fn property_for(contract: &ShedaContract, property_id: u64) -> Property {
+    contract
+        .properties
+        .get(&property_id)
+        .cloned()
+        .expect("Property not found")
+}
+
+fn units_of(contract: &ShedaContract, building_id: u64) -> Vec<u64> {
+    contract
+        .building_units
+        .get(&building_id)
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn is_let(property: &Property) -> bool {
+    property
+        .active_lease
+        .as_ref()
+        .is_some_and(|lease| lease.active)
+}
+
+/// Someone lives there: a running lease, or a short stay under way or to come.
+fn is_occupied(contract: &ShedaContract, property: &Property) -> bool {
+    is_let(property) || crate::booking::has_upcoming_bookings(contract, property.id)
+}
+
+/// What a unit has brought in: `(lease, rent, stays)`. Lease income is what
+/// its leases were let for, renewals included and damage deposits not;
+/// stays count what the owner kept of cancelled ones.
+fn unit_income(contract: &ShedaContract, unit_id: u64) -> (u128, u128, u128) {
+    let lease = contract
+        .bids
+        .get(&unit_id)
+        .map(|bids| {
+            bids.iter()
+                .filter(|bid| matches!(bid.action, Action::Lease) && bid.lease_id.is_some())
+                .map(|bid| bid.amount - crate::deposit::held_back(contract, bid.id))
+                .fold(0, |total, amount| {
+                    checked_add_u128(total, amount, "unit lease income")
+                })
+        })
+        .unwrap_or(0);
+    let rent = contract
+        .rent_schedules
+        .values()
+        .filter(|schedule| schedule.property_id == unit_id)
+        .flat_map(|schedule| schedule.payments.iter())
+        .fold(0, |total, payment| {
+            checked_add_u128(total, payment.amount + payment.late_fee, "unit rent income")
+        });
+    let stays = contract
+        .property_bookings
+        .get(&unit_id)
+        .map(|booking_ids| {
+            booking_ids
+                .iter()
+                .filter_map(|id| contract.bookings.get(id))
+                .map(|booking| match booking.status {
+                    BookingStatus::Completed => booking.amount,
+                    BookingStatus::Cancelled => booking.amount - booking.refunded,
+                    BookingStatus::Confirmed => 0,
+                })
+                .fold(0, |total, amount| {
+                    checked_add_u128(total, amount, "unit booking income")
+                })
+        })
+        .unwrap_or(0);
+    (lease, rent, stays)
+}
+
+/// A building can't change hands, or be let as a whole, while anyone is
+/// living in one of its units. Called wherever the NFT moves and wherever a
+/// bid is accepted.
+pub(crate) fn assert_units_vacant(contract: &ShedaContract, property_id: u64) {
+    let occupied = units_of(contract, property_id).into_iter().any(|unit_id| {
+        contract
+            .properties
+            .get(&unit_id)
+            .is_some_and(|unit| is_occupied(contract, unit))
+    });
+    require!(!occupied, "A unit in this building is occupied");
+}
+
+/// A unit can't be let while its whole building is. Called wherever a bid is
+/// accepted.
+pub(crate) fn assert_building_not_let(contract: &ShedaContract, property_id: u64) {
+    let building_let = contract
+        .unit_building
+        .get(&property_id)
+        .and_then(|building_id| contract.properties.get(building_id))
+        .is_some_and(is_let);
+    require!(!building_let, "The building this unit is in is let");
+}
+
+/// Keep the hierarchy whole when a property is deleted: a building must have
+/// no units left, and a unit drops out of its building.
+pub(crate) fn on_property_deleted(contract: &mut ShedaContract, property_id: u64) {
+    require!(
+        units_of(contract, property_id).is_empty(),
+        "Remove the building's units before deleting it"
+    );
+    if let Some(building_id) = contract.unit_building.remove(&property_id) {
+        detach(contract, building_id, property_id);
+    }
+}
+
+fn detach(contract: &mut ShedaContract, building_id: u64, unit_id: u64) {
+    let mut units = units_of(contract, building_id);
+    units.retain(|id| *id != unit_id);
+    if units.is_empty() {
+        contract.building_units.remove(&building_id);
+    } else {
+        contract.building_units.insert(building_id, units);
+    }
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Owner puts a property they own into a building as one of its units.
+    /// Each unit stays its own NFT, listed, let, booked and sold on its own;
+    /// the building ties them together and can't change hands while any of
+    /// them is occupied.
+    #[payable]
+    pub fn add_building_unit(&mut self, building_id: u64, unit_id: u64) {
+        assert_one_yocto();
+
+        require!(building_id != unit_id, "A building can't be its own unit");
+        let building = property_for(self, building_id);
+        let unit = property_for(self, unit_id);
+        let caller = env::predecessor_account_id();
+        require!(
+            building.owner_id == caller && unit.owner_id == caller,
+            "Only the owner of both can add a unit to a building"
+        );
+        require!(
+            !self.unit_building.contains_key(&building_id),
+            "A unit can't have units of its own"
+        );
+        require!(
+            units_of(self, unit_id).is_empty(),
+            "A building can't be a unit of another"
+        );
+        require!(
+            !self.unit_building.contains_key(&unit_id),
+            "The unit is already in a building"
+        );
+        require!(!is_let(&building), "A let building can't take on units");
+
+        self.building_units
+            .entry(building_id)
+            .or_default()
+            .push(unit_id);
+        self.unit_building.insert(unit_id, building_id);
+
+        emit_event(
+            "BuildingUnitAdded",
+            BuildingUnitAddedEvent {
+                token_id: building_id,
+                unit_id,
+                owner_id: caller,
+            },
+        );
+    }
+
+    /// Building owner takes a unit out of the building. The unit carries on
+    /// as a property of its own.
+    #[payable]
+    pub fn remove_building_unit(&mut self, building_id: u64, unit_id: u64) {
+        assert_one_yocto();
+
+        assert_eq!(
+            property_for(self, building_id).owner_id,
+            env::predecessor_account_id(),
+            "Only the building owner can remove a unit"
+        );
+        require!(
+            self.unit_building.get(&unit_id) == Some(&building_id),
+            "The unit isn't in this building"
+        );
+
+        self.unit_building.remove(&unit_id);
+        detach(self, building_id, unit_id);
+
+        emit_event(
+            "BuildingUnitRemoved",
+            BuildingUnitRemovedEvent {
+                token_id: building_id,
+                unit_id,
+            },
+        );
+    }
+
+    /// The units of a building, in the order they were added.
+    pub fn get_building_units(&self, building_id: u64) -> Vec<PropertyView> {
+        units_of(self, building_id)
+            .iter()
+            .filter_map(|unit_id| self.properties.get(unit_id))
+            .map(|unit| self.property_to_view(unit))
+            .collect()
+    }
+
+    /// The building a unit is in, if any.
+    pub fn get_unit_building(&self, unit_id: u64) -> Option<u64> {
+        self.unit_building.get(&unit_id).copied()
+    }
+
+    /// Occupancy and income across a building's units.
+    pub fn get_building_summary(&self, building_id: u64) -> Option<BuildingSummaryView> {
+        let building = self.properties.get(&building_id)?;
+        let mut summary = BuildingSummaryView {
+            building_id,
+            owner_id: building.owner_id.to_string(),
+            unit_count: 0,
+            occupied_units: 0,
+            vacant_units: 0,
+            occupancy_bps: 0,
+            lease_income: String::new(),
+            rent_income: String::new(),
+            booking_income: String::new(),
+            total_income: String::new(),
+            units: Vec::new(),
+        };
+        let (mut lease_total, mut rent_total, mut booking_total) = (0u128, 0u128, 0u128);
+
+        for unit_id in units_of(self, building_id) {
+            let Some(unit) = self.properties.get(&unit_id) else {
+                continue;
+            };
+            let occupied = is_occupied(self, unit);
+            let (lease, rent, stays) = unit_income(self, unit_id);
+            lease_total = checked_add_u128(lease_total, lease, "building lease income");
+            rent_total = checked_add_u128(rent_total, rent, "building rent income");
+            booking_total = checked_add_u128(booking_total, stays, "building booking income");
+
+            summary.unit_count += 1;
+            if occupied {
+                summary.occupied_units += 1;
+            } else {
+                summary.vacant_units += 1;
+            }
+            summary.units.push(UnitOccupancyView {
+                unit_id,
+                owner_id: unit.owner_id.to_string(),
+                is_for_sale: unit.is_for_sale,
+                is_for_lease: unit.lease_duration_months.is_some(),
+                occupied,
+                tenant_id: unit
+                    .active_lease
+                    .as_ref()
+                    .filter(|lease| lease.active)
+                    .map(|lease| lease.tenant_id.to_string()),
+                income: checked_add_u128(
+                    checked_add_u128(lease, rent, "unit income"),
+                    stays,
+                    "unit income",
+                )
+                .to_string(),
+            });
+        }
+
+        if summary.unit_count > 0 {
+            summary.occupancy_bps = (summary.occupied_units * 10_000 / summary.unit_count) as u16;
+        }
+        summary.lease_income = lease_total.to_string();
+        summary.rent_income = rent_total.to_string();
+        summary.booking_income = booking_total.to_string();
+        summary.total_income = checked_add_u128(
+            checked_add_u128(lease_total, rent_total, "building income"),
+            booking_total,
+            "building income",
+        )
+        .to_string();
+        Some(summary)
+    }
+
+    /// Summaries of every building an account owns.
+    pub fn get_buildings_by_owner(&self, owner_id: AccountId) -> Vec<BuildingSummaryView> {
+        self.property_per_owner
+            .get(&owner_id)
+            .map(|property_ids| {
+                property_ids
+                    .iter()
+                    .filter(|id| self.building_units.contains_key(id))
+                    .filter_map(|id| self.get_building_summary(*id))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
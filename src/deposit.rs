@@ -0,0 +1,505 @@
+use near_sdk::{assert_one_yocto, env, json_types::U128, near_bindgen, require, AccountId};
+
+use crate::{
+    events::{emit_event, DamageDepositSettledEvent, DepositDeductionsFiledEvent},
+    internal::{checked_add_u128, checked_add_u64, debit_escrow},
+    models::{Action, Bid, DamageDeposit, DamageDepositStatus, DepositDeduction, DisputeWinner},
+    views::DamageDepositView,
+    ShedaContract, ShedaContractExt,
+};
+
+fn bid_on(contract: &ShedaContract, property_id: u64, bid_id: u64) -> Bid {
+    contract
+        .bids
+        .get(&property_id)
+        .and_then(|bids| bids.iter().find(|b| b.id == bid_id).cloned())
+        .expect("Bid not found for the property")
+}
+
+fn deposit_for(contract: &ShedaContract, lease_id: u64) -> DamageDeposit {
+    contract
+        .damage_deposits
+        .get(&lease_id)
+        .cloned()
+        .expect("This lease has no damage deposit")
+}
+
+fn owner_of(contract: &ShedaContract, property_id: u64) -> AccountId {
+    contract
+        .properties
+        .get(&property_id)
+        .expect("Property not found")
+        .owner_id
+        .clone()
+}
+
+/// Mark part of a freshly placed lease bid as its damage deposit. Called from
+/// `ft_on_transfer` once the bid is in.
+pub(crate) fn record_deposit_bid(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    bid_id: u64,
+    damage_deposit: u128,
+) {
+    let bid = bid_on(contract, property_id, bid_id);
+    require!(
+        matches!(bid.action, Action::Lease),
+        "A damage deposit only applies to a lease"
+    );
+    require!(
+        damage_deposit > 0,
+        "Damage deposit must be greater than zero"
+    );
+    require!(
+        damage_deposit < bid.amount,
+        "The bid must cover more than the damage deposit"
+    );
+    let required = contract
+        .damage_deposit_terms
+        .get(&property_id)
+        .copied()
+        .unwrap_or(0);
+    require!(
+        damage_deposit >= required,
+        format!("This property asks for a damage deposit of {}", required)
+    );
+
+    contract.bid_damage_deposits.insert(bid_id, damage_deposit);
+}
+
+/// The part of bid `bid_id` the owner isn't paid, because it's the damage
+/// deposit. Zero for every other bid.
+pub(crate) fn held_back(contract: &ShedaContract, bid_id: u64) -> u128 {
+    contract
+        .bid_damage_deposits
+        .get(&bid_id)
+        .copied()
+        .unwrap_or(0)
+}
+
+/// `caller` contests the deductions filed against their deposit, having
+/// posted `bond_amount` if a bond is required.
+pub(crate) fn dispute_deductions(
+    contract: &mut ShedaContract,
+    caller: AccountId,
+    lease_id: u64,
+    reason: String,
+    bond_amount: u128,
+) {
+    let deposit = deposit_for(contract, lease_id);
+    assert_eq!(
+        deposit.tenant_id, caller,
+        "Only the tenant can dispute the deductions"
+    );
+    require!(
+        deposit.status == DamageDepositStatus::DeductionsFiled,
+        "No deductions are waiting on the tenant"
+    );
+    require!(
+        deposit
+            .deadline
+            .is_some_and(|deadline| env::block_timestamp() <= deadline),
+        "The window to dispute the deductions has closed"
+    );
+
+    crate::internal::internal_raise_dispute(contract, caller, lease_id, reason, bond_amount);
+
+    if let Some(deposit) = contract.damage_deposits.get_mut(&lease_id) {
+        deposit.status = DamageDepositStatus::Disputed;
+        deposit.deadline = None;
+    }
+}
+
+/// The rent is what a lease bid holds beyond its deposit, so the bid can't be
+/// brought down to the deposit or below it.
+pub(crate) fn assert_covers_deposit(contract: &ShedaContract, bid_id: u64, amount: u128) {
+    require!(
+        amount > held_back(contract, bid_id),
+        "The bid must stay above its damage deposit"
+    );
+}
+
+/// A lease bid on a property that asks for a damage deposit must have lodged
+/// it. Checked wherever a bid is accepted.
+pub(crate) fn assert_deposit_lodged(contract: &ShedaContract, property_id: u64, bid_id: u64) {
+    let Some(required) = contract.damage_deposit_terms.get(&property_id).copied() else {
+        return;
+    };
+    if !matches!(bid_on(contract, property_id, bid_id).action, Action::Lease) {
+        return;
+    }
+    require!(
+        held_back(contract, bid_id) >= required,
+        format!(
+            "This property asks for a damage deposit of {}, lodged with the bid",
+            required
+        )
+    );
+}
+
+pub(crate) fn assert_no_deposit(contract: &ShedaContract, bid_id: u64, message: &str) {
+    require!(held_back(contract, bid_id) == 0, message);
+}
+
+/// The rent part of a lease bid has reached the owner: hold its deposit
+/// against the lease until the lease ends.
+pub(crate) fn hold_for_lease(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    bid_id: u64,
+    lease_id: u64,
+) {
+    let Some(amount) = contract.bid_damage_deposits.remove(&bid_id) else {
+        return;
+    };
+    let bid = bid_on(contract, property_id, bid_id);
+
+    contract.damage_deposits.insert(
+        lease_id,
+        DamageDeposit {
+            property_id,
+            bid_id,
+            tenant_id: bid.bidder,
+            stablecoin_token: bid.stablecoin_token,
+            amount,
+            deductions: Vec::new(),
+            deadline: None,
+            status: DamageDepositStatus::Held,
+            refunded: 0,
+        },
+    );
+    if let Some(property) = contract.properties.get_mut(&property_id) {
+        property.damage_escrow =
+            checked_add_u128(property.damage_escrow, amount, "property damage escrow");
+    }
+}
+
+/// The lease has ended: the owner's window for deductions opens. With no
+/// window configured the deposit goes straight back. Returns what was
+/// refunded now.
+pub(crate) fn on_lease_expired(contract: &mut ShedaContract, lease_id: u64) -> u128 {
+    match contract.damage_deposits.get(&lease_id) {
+        Some(deposit) if deposit.status == DamageDepositStatus::Held => {}
+        _ => return 0,
+    }
+
+    if contract.deposit_claim_window_ns == 0 {
+        return settle(contract, lease_id, 0);
+    }
+
+    let deadline = checked_add_u64(
+        env::block_timestamp(),
+        contract.deposit_claim_window_ns,
+        "deposit claim deadline",
+    );
+    if let Some(deposit) = contract.damage_deposits.get_mut(&lease_id) {
+        deposit.status = DamageDepositStatus::AwaitingDeductions;
+        deposit.deadline = Some(deadline);
+    }
+    0
+}
+
+/// An admin decided the disputed deductions on `lease_id`: the winner gets
+/// `payout` of the deposit and the other side the rest. Returns what the
+/// winner was paid, or None if the lease's dispute isn't about its deposit.
+pub(crate) fn settle_disputed(
+    contract: &mut ShedaContract,
+    lease_id: u64,
+    winner: &DisputeWinner,
+    payout: u128,
+) -> Option<u128> {
+    let deposit = contract
+        .damage_deposits
+        .get(&lease_id)
+        .filter(|deposit| deposit.status == DamageDepositStatus::Disputed)?;
+    let to_winner = payout.min(deposit.amount);
+    let deducted = match winner {
+        DisputeWinner::Owner => to_winner,
+        DisputeWinner::Tenant => deposit.amount - to_winner,
+    };
+    settle(contract, lease_id, deducted);
+    Some(to_winner)
+}
+
+/// What an owner's dispute on `lease_id` can claim: the damage deposit, while
+/// it's held and not already being settled through deductions. It's the only
+/// money the contract still holds for a running lease — the lease payment
+/// went to the owner when escrow was released.
+pub(crate) fn claimable(contract: &ShedaContract, lease_id: u64) -> u128 {
+    contract
+        .damage_deposits
+        .get(&lease_id)
+        .filter(|deposit| {
+            matches!(
+                deposit.status,
+                DamageDepositStatus::Held | DamageDepositStatus::AwaitingDeductions
+            )
+        })
+        .map(|deposit| deposit.amount)
+        .unwrap_or(0)
+}
+
+/// An owner's claim on `lease_id` was upheld: pay `claim` out of the deposit,
+/// capped at what's left of it. The rest stays held against the lease.
+/// Returns what the owner was paid.
+pub(crate) fn pay_owner_claim(contract: &mut ShedaContract, lease_id: u64, claim: u128) -> u128 {
+    let paid = claim.min(claimable(contract, lease_id));
+    if paid == 0 {
+        return 0;
+    }
+    let deposit = deposit_for(contract, lease_id);
+
+    if let Some(deposit) = contract.damage_deposits.get_mut(&lease_id) {
+        deposit.amount -= paid;
+    }
+    if let Some(property) = contract.properties.get_mut(&deposit.property_id) {
+        property.damage_escrow = property.damage_escrow.saturating_sub(paid);
+    }
+    debit_escrow(
+        contract,
+        &deposit.stablecoin_token,
+        paid,
+        "damage deposit claim",
+    );
+
+    let owner_id = owner_of(contract, deposit.property_id);
+    let payouts = crate::payout::owner_payouts(
+        contract,
+        deposit.property_id,
+        &deposit.stablecoin_token,
+        owner_id,
+        paid,
+    );
+    #[allow(unused_must_use)]
+    crate::payout::send_payouts(&deposit.stablecoin_token, payouts);
+    paid
+}
+
+/// Pay the deposit out: `deducted` to the owner, the rest back to the tenant.
+/// Returns the refund.
+fn settle(contract: &mut ShedaContract, lease_id: u64, deducted: u128) -> u128 {
+    let deposit = deposit_for(contract, lease_id);
+    let owner_id = owner_of(contract, deposit.property_id);
+    let refunded = deposit.amount - deducted;
+
+    if let Some(deposit) = contract.damage_deposits.get_mut(&lease_id) {
+        deposit.status = DamageDepositStatus::Settled;
+        deposit.deadline = None;
+        deposit.refunded = refunded;
+    }
+    if let Some(property) = contract.properties.get_mut(&deposit.property_id) {
+        property.damage_escrow = property.damage_escrow.saturating_sub(deposit.amount);
+    }
+    debit_escrow(
+        contract,
+        &deposit.stablecoin_token,
+        deposit.amount,
+        "damage deposit",
+    );
+
+    emit_event(
+        "DamageDepositSettled",
+        DamageDepositSettledEvent {
+            token_id: deposit.property_id,
+            lease_id,
+            owner_id: owner_id.clone(),
+            tenant_id: deposit.tenant_id.clone(),
+            deducted,
+            refunded,
+        },
+    );
+
+    let mut payouts = crate::payout::owner_payouts(
+        contract,
+        deposit.property_id,
+        &deposit.stablecoin_token,
+        owner_id,
+        deducted,
+    );
+    payouts.push((deposit.tenant_id, refunded));
+    #[allow(unused_must_use)]
+    crate::payout::send_payouts(&deposit.stablecoin_token, payouts);
+
+    refunded
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Owner sets the damage deposit tenants must lodge with a lease bid.
+    /// Zero stops asking for one. Leases already running keep the deposit
+    /// they were let with.
+    #[payable]
+    pub fn set_damage_deposit(&mut self, property_id: u64, amount: U128) {
+        assert_one_yocto();
+
+        let property = self
+            .properties
+            .get(&property_id)
+            .expect("Property not found");
+        assert_eq!(
+            property.owner_id,
+            env::predecessor_account_id(),
+            "Only the property owner can set the damage deposit"
+        );
+        require!(
+            property.lease_duration_months.is_some(),
+            "Property is not for lease"
+        );
+
+        if amount.0 == 0 {
+            self.damage_deposit_terms.remove(&property_id);
+        } else {
+            self.damage_deposit_terms.insert(property_id, amount.0);
+        }
+    }
+
+    /// Owner itemises what they're keeping out of the deposit of an ended
+    /// lease. Open until the claim deadline; the tenant then has the same
+    /// window again to accept or dispute.
+    #[payable]
+    pub fn file_deposit_deductions(&mut self, lease_id: u64, deductions: Vec<DepositDeduction>) {
+        assert_one_yocto();
+
+        let deposit = deposit_for(self, lease_id);
+        let owner_id = owner_of(self, deposit.property_id);
+        assert_eq!(
+            owner_id,
+            env::predecessor_account_id(),
+            "Only the property owner can file deductions"
+        );
+        require!(
+            deposit.status == DamageDepositStatus::AwaitingDeductions,
+            "The deposit isn't open for deductions"
+        );
+        let now = env::block_timestamp();
+        require!(
+            deposit.deadline.is_some_and(|deadline| now <= deadline),
+            "The window for deductions has closed"
+        );
+        require!(!deductions.is_empty(), "File at least one deduction");
+        let mut deducted: u128 = 0;
+        for deduction in &deductions {
+            require!(
+                !deduction.description.trim().is_empty(),
+                "Every deduction needs a description"
+            );
+            require!(
+                deduction.amount.0 > 0,
+                "Every deduction must be greater than zero"
+            );
+            deducted = checked_add_u128(deducted, deduction.amount.0, "deposit deductions");
+        }
+        require!(
+            deducted <= deposit.amount,
+            "Deductions can't exceed the deposit"
+        );
+
+        let respond_by = checked_add_u64(now, self.deposit_claim_window_ns, "deposit response");
+        if let Some(deposit) = self.damage_deposits.get_mut(&lease_id) {
+            deposit.deductions = deductions;
+            deposit.status = DamageDepositStatus::DeductionsFiled;
+            deposit.deadline = Some(respond_by);
+        }
+
+        emit_event(
+            "DepositDeductionsFiled",
+            DepositDeductionsFiledEvent {
+                token_id: deposit.property_id,
+                lease_id,
+                owner_id,
+                tenant_id: deposit.tenant_id,
+                deducted,
+                respond_by,
+            },
+        );
+    }
+
+    /// Tenant agrees to the deductions: the owner is paid them and the rest
+    /// of the deposit comes back.
+    #[payable]
+    pub fn accept_deposit_deductions(&mut self, lease_id: u64) {
+        assert_one_yocto();
+
+        let deposit = deposit_for(self, lease_id);
+        assert_eq!(
+            deposit.tenant_id,
+            env::predecessor_account_id(),
+            "Only the tenant can accept the deductions"
+        );
+        require!(
+            deposit.status == DamageDepositStatus::DeductionsFiled,
+            "No deductions are waiting on the tenant"
+        );
+        settle(self, lease_id, deposit.total_deductions());
+    }
+
+    /// Tenant contests the deductions. This raises a dispute on the lease;
+    /// the deposit stays held until an admin resolves it. While a dispute
+    /// bond is required it is raised with a
+    /// `DisputeMsg::DisputeDepositDeductions` instead.
+    #[payable]
+    pub fn dispute_deposit_deductions(&mut self, lease_id: u64, reason: String) {
+        assert_one_yocto();
+        crate::bond::assert_no_bond_required(self);
+        dispute_deductions(self, env::predecessor_account_id(), lease_id, reason, 0);
+    }
+
+    /// Pay out a deposit nobody is acting on.
+    ///
+    /// Anyone may call it once the current deadline has passed: with no
+    /// deductions filed the whole deposit goes back to the tenant, and filed
+    /// deductions the tenant didn't answer stand. The owner can also call it
+    /// early to waive deductions altogether.
+    pub fn settle_damage_deposit(&mut self, lease_id: u64) -> U128 {
+        let deposit = deposit_for(self, lease_id);
+        let past_deadline = deposit
+            .deadline
+            .is_some_and(|deadline| env::block_timestamp() > deadline);
+
+        let deducted = match deposit.status {
+            DamageDepositStatus::AwaitingDeductions => {
+                require!(
+                    past_deadline
+                        || env::predecessor_account_id() == owner_of(self, deposit.property_id),
+                    "The owner can still file deductions"
+                );
+                0
+            }
+            DamageDepositStatus::DeductionsFiled => {
+                require!(past_deadline, "The tenant can still answer the deductions");
+                deposit.total_deductions()
+            }
+            _ => env::panic_str("The deposit isn't waiting on a deadline"),
+        };
+        U128(settle(self, lease_id, deducted))
+    }
+
+    /// The damage deposit held against a lease, if it has one.
+    pub fn get_damage_deposit(&self, lease_id: u64) -> Option<DamageDepositView> {
+        self.damage_deposits
+            .get(&lease_id)
+            .map(|deposit| (lease_id, deposit).into())
+    }
+
+    /// Every damage deposit a tenant has lodged, settled ones included.
+    pub fn get_damage_deposits_by_tenant(&self, tenant_id: AccountId) -> Vec<DamageDepositView> {
+        self.damage_deposits
+            .iter()
+            .filter(|(_, deposit)| deposit.tenant_id == tenant_id)
+            .map(|(lease_id, deposit)| (*lease_id, deposit).into())
+            .collect()
+    }
+
+    /// The damage deposit a property asks for; "0" if none.
+    pub fn get_damage_deposit_required(&self, property_id: u64) -> String {
+        self.damage_deposit_terms
+            .get(&property_id)
+            .copied()
+            .unwrap_or(0)
+            .to_string()
+    }
+
+    pub fn get_deposit_claim_window(&self) -> u64 {
+        self.deposit_claim_window_ns
+    }
+}
@@ -0,0 +1,316 @@
+use near_contract_standards::non_fungible_token::core::NonFungibleTokenCore;
+use near_sdk::{
+    assert_one_yocto, env, json_types::U128, log, near_bindgen, require, AccountId, Gas, NearToken,
+    Promise,
+};
+
+use crate::{
+    events::{emit_event, BalancePaidEvent, EarnestMoneyForfeitedEvent},
+    ext::ft_contract,
+    internal::{
+        checked_add_u128, checked_add_u64, credit_escrow, debit_escrow, lock_bid, remove_token,
+        unlock_bid, update_bid_in_list,
+    },
+    models::{Action, Bid, BidStatus, EarnestMoney},
+    views::EarnestMoneyView,
+    ShedaContract, ShedaContractExt,
+};
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+fn bid_on(contract: &ShedaContract, property_id: u64, bid_id: u64) -> Bid {
+    contract
+        .bids
+        .get(&property_id)
+        .and_then(|bids| bids.iter().find(|b| b.id == bid_id).cloned())
+        .expect("Bid not found for the property")
+}
+
+fn earnest_terms(contract: &ShedaContract, bid_id: u64) -> EarnestMoney {
+    contract
+        .earnest_deposits
+        .get(&bid_id)
+        .cloned()
+        .expect("Not an earnest-money bid")
+}
+
+/// Turn a freshly placed bid into an earnest-money bid for `offer_price`.
+///
+/// Called from `ft_on_transfer` once the bid is in; the transferred amount is
+/// the deposit.
+pub(crate) fn record_earnest_bid(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    bid_id: u64,
+    offer_price: u128,
+) {
+    let bid = bid_on(contract, property_id, bid_id);
+    require!(
+        matches!(bid.action, Action::Purchase),
+        "Earnest money only applies to a purchase"
+    );
+    // An auction sells to a full price, settled at once.
+    crate::auction::assert_no_open_auction(contract, property_id);
+
+    let min_deposit = offer_price * u128::from(contract.earnest_min_deposit_bps) / BPS_DENOMINATOR;
+    require!(
+        bid.amount >= min_deposit,
+        format!("Earnest money must be at least {}", min_deposit)
+    );
+    require!(
+        bid.amount < offer_price,
+        "Earnest money must be less than the offer price — place an ordinary bid instead"
+    );
+
+    contract.earnest_deposits.insert(
+        bid_id,
+        EarnestMoney {
+            offer_price,
+            deposit: bid.amount,
+            balance_due_by: None,
+            balance_paid: false,
+        },
+    );
+}
+
+pub(crate) fn assert_not_earnest(contract: &ShedaContract, bid_id: u64, message: &str) {
+    require!(!contract.earnest_deposits.contains_key(&bid_id), message);
+}
+
+/// Start the balance deadline when the buyer confirms the documents. A no-op
+/// for an ordinary bid.
+pub(crate) fn start_balance_clock(contract: &mut ShedaContract, bid_id: u64) {
+    let window = contract.balance_due_window_ns;
+    if let Some(terms) = contract.earnest_deposits.get_mut(&bid_id) {
+        terms.balance_due_by = Some(checked_add_u64(
+            env::block_timestamp(),
+            window,
+            "balance deadline",
+        ));
+    }
+}
+
+/// Escrow can only be released to the seller once the full price is in.
+pub(crate) fn assert_balance_paid(contract: &ShedaContract, bid_id: u64) {
+    if let Some(terms) = contract.earnest_deposits.get(&bid_id) {
+        require!(
+            terms.balance_paid,
+            "The balance on this earnest-money bid hasn't been paid"
+        );
+    }
+}
+
+/// Pay the balance on an earnest-money bid with an `ft_on_transfer`
+/// `PayBalance`. Returns what was sent beyond the balance, for
+/// `ft_on_transfer` to hand back.
+pub(crate) fn pay_balance(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    bid_id: u64,
+    sender_id: AccountId,
+    amount: u128,
+) -> u128 {
+    let bid = bid_on(contract, property_id, bid_id);
+    require!(
+        bid.bidder == sender_id,
+        "Only the bidder can pay the balance"
+    );
+    require!(
+        bid.stablecoin_token == env::predecessor_account_id(),
+        "The balance must be paid in the bid's stablecoin"
+    );
+    require!(
+        bid.status == BidStatus::DocsConfirmed,
+        "The balance falls due once the documents are confirmed"
+    );
+
+    let terms = earnest_terms(contract, bid_id);
+    require!(!terms.balance_paid, "The balance has already been paid");
+    if let Some(due_by) = terms.balance_due_by {
+        require!(
+            env::block_timestamp() <= due_by,
+            "The balance deadline has passed"
+        );
+    }
+
+    let due = terms.offer_price - bid.amount;
+    require!(
+        amount >= due,
+        format!("The balance due on this bid is {}", due)
+    );
+
+    let total_paid = checked_add_u128(bid.amount, due, "earnest balance");
+    if let Some(bids) = contract.bids.get_mut(&property_id) {
+        let _ = update_bid_in_list(bids, bid_id, |b| {
+            b.amount = total_paid;
+            b.updated_at = env::block_timestamp();
+        });
+    }
+    if let Some(terms) = contract.earnest_deposits.get_mut(&bid_id) {
+        terms.balance_paid = true;
+    }
+    credit_escrow(contract, &bid.stablecoin_token, due, "earnest balance");
+
+    emit_event(
+        "BalancePaid",
+        BalancePaidEvent {
+            token_id: property_id,
+            bid_id,
+            bidder_id: bid.bidder,
+            amount: due,
+            total_paid,
+        },
+    );
+
+    amount - due
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Close out an earnest-money deal whose balance never arrived.
+    ///
+    /// Anyone may call it once the deadline has passed. The seller keeps the
+    /// forfeited share of the deposit (`earnest_forfeit_bps`), the buyer gets
+    /// the rest back, the agreement is burned and the property goes back on
+    /// the market.
+    #[payable]
+    pub fn forfeit_earnest_money(&mut self, property_id: u64, bid_id: u64) -> Promise {
+        assert_one_yocto();
+
+        let bid = bid_on(self, property_id, bid_id);
+        require!(
+            bid.status == BidStatus::DocsConfirmed,
+            "Only a confirmed deal awaiting its balance can be forfeited"
+        );
+        let terms = earnest_terms(self, bid_id);
+        require!(!terms.balance_paid, "The balance has been paid");
+        let due_by = terms
+            .balance_due_by
+            .expect("The balance deadline hasn't started");
+        require!(
+            env::block_timestamp() > due_by,
+            "The balance deadline hasn't passed yet"
+        );
+
+        lock_bid(self, property_id, bid_id);
+
+        let seller_id = self
+            .properties
+            .get(&property_id)
+            .expect("Property not found")
+            .owner_id
+            .clone();
+        let forfeited = bid.amount * u128::from(self.earnest_forfeit_bps) / BPS_DENOMINATOR;
+        let refunded = bid.amount - forfeited;
+        debit_escrow(self, &bid.stablecoin_token, bid.amount, "earnest forfeit");
+
+        // The buyer defaulted on the agreement, so it doesn't stay with them.
+        if let Some(document) = bid
+            .document_token_id
+            .clone()
+            .and_then(|id| self.tokens.nft_token(id))
+        {
+            let document_token_id = document.token_id;
+            remove_token(self, document_token_id.clone(), &document.owner_id);
+            log!(
+                "Burned agreement {} — balance on bid {} was never paid",
+                document_token_id,
+                bid_id
+            );
+        }
+        if let Some(bids) = self.bids.get_mut(&property_id) {
+            let _ = update_bid_in_list(bids, bid_id, |b| {
+                b.status = BidStatus::Cancelled;
+                b.updated_at = env::block_timestamp();
+                b.document_token_id = None;
+            });
+        }
+        if let Some(property) = self.properties.get_mut(&property_id) {
+            property.is_for_sale = true;
+        }
+
+        emit_event(
+            "EarnestMoneyForfeited",
+            EarnestMoneyForfeitedEvent {
+                token_id: property_id,
+                bid_id,
+                bidder_id: bid.bidder.clone(),
+                seller_id: seller_id.clone(),
+                forfeited,
+                refunded,
+            },
+        );
+
+        let mut payouts = crate::payout::owner_payouts(
+            self,
+            property_id,
+            &bid.stablecoin_token,
+            seller_id,
+            forfeited,
+        );
+        payouts.push((bid.bidder, refunded));
+        let payouts: Vec<(AccountId, U128)> = payouts
+            .into_iter()
+            .filter(|(_, amount)| *amount > 0)
+            .map(|(account, amount)| (account, U128(amount)))
+            .collect();
+        let transfer = |(account, amount): &(AccountId, U128)| {
+            ft_contract::ext(bid.stablecoin_token.clone())
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(Gas::from_tgas(30))
+                .ft_transfer(account.clone(), *amount)
+        };
+        let mut transfers = payouts.iter().map(transfer);
+        // Everything may have gone to shareholders; still settle the bid.
+        let first = transfers.next().unwrap_or_else(|| {
+            Promise::new(env::current_account_id()).transfer(NearToken::from_yoctonear(0))
+        });
+        transfers.fold(first, |all, next| all.and(next)).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(Gas::from_tgas(20))
+                .forfeit_earnest_money_callback(
+                    property_id,
+                    bid_id,
+                    bid.stablecoin_token.clone(),
+                    payouts,
+                ),
+        )
+    }
+
+    #[private]
+    pub fn forfeit_earnest_money_callback(
+        &mut self,
+        property_id: u64,
+        bid_id: u64,
+        stablecoin_token: AccountId,
+        payouts: Vec<(AccountId, U128)>,
+    ) {
+        unlock_bid(self, property_id, bid_id);
+        for (index, (account, amount)) in payouts.into_iter().enumerate() {
+            if crate::internal::promise_failed(index as u64) {
+                // The forfeit itself stands; the recipient can claim it.
+                crate::payout::hold_for_claim(self, &account, &stablecoin_token, amount.0);
+                log!(
+                    "Earnest-money payout of {} to {} failed, held for them to claim",
+                    amount.0,
+                    account
+                );
+            }
+        }
+    }
+
+    /// The closing terms of an earnest-money bid, if `bid_id` is one.
+    pub fn get_earnest_money(&self, bid_id: u64) -> Option<EarnestMoneyView> {
+        self.earnest_deposits.get(&bid_id).map(|terms| terms.into())
+    }
+
+    /// Minimum deposit (bps of the offer), forfeited share (bps of the
+    /// deposit) and the balance window in nanoseconds.
+    pub fn get_earnest_money_terms(&self) -> (u16, u16, u64) {
+        (
+            self.earnest_min_deposit_bps,
+            self.earnest_forfeit_bps,
+            self.balance_due_window_ns,
+        )
+    }
+}
@@ -1,3 +1,4 @@
+use crate::models::DisputeCase;
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{log, AccountId};
 
@@ -108,6 +109,37 @@ pub struct DisputeRaisedEvent {
     pub bond_amount: u128,
 }
 
+/// Event emitted when a dispute bond is returned or forfeited; `paid_to` is
+/// None when it went to the treasury
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DisputeBondSettledEvent {
+    pub case: DisputeCase,
+    pub posted_by: AccountId,
+    pub amount: u128,
+    pub forfeited: bool,
+    pub paid_to: Option<AccountId>,
+}
+
+/// Event emitted when an owner raises a dispute on a lease
+#[derive(Serialize, Deserialize)]
+pub struct OwnerDisputeRaisedEvent {
+    pub token_id: u64,
+    pub lease_id: u64,
+    pub owner_id: AccountId,
+    pub claim: u128,
+    pub response_deadline: u64,
+    pub bond_amount: u128,
+}
+
+/// Event emitted when a tenant contests an owner's dispute
+#[derive(Serialize, Deserialize)]
+pub struct DisputeContestedEvent {
+    pub token_id: u64,
+    pub lease_id: u64,
+    pub tenant_id: AccountId,
+}
+
 /// Event emitted when a dispute is resolved by admin
 #[derive(Serialize, Deserialize)]
 pub struct DisputeResolvedEvent {
@@ -117,7 +149,11 @@ pub struct DisputeResolvedEvent {
     pub escrow_returned: u128,
 }
 
-/// Event emitted when a lease expires automatically
+/// Event emitted when a lease expires automatically.
+///
+/// `escrow_returned` is the damage deposit refunded on the spot, which only
+/// happens when there's no window for deductions; otherwise the refund comes
+/// with `DamageDepositSettled`.
 #[derive(Serialize, Deserialize)]
 pub struct LeaseExpiredEvent {
     pub token_id: u64,
@@ -203,3 +239,477 @@ pub struct BidDisputeResolvedEvent {
     pub buyer_refund: u128,
     pub seller_payout: u128,
 }
+
+/// Event emitted when a seller puts a property up for auction
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuctionStartedEvent {
+    pub token_id: u64,
+    pub seller_id: AccountId,
+    pub mode: String,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub reserve_price: u128,
+}
+
+/// Event emitted when an auction closes, whether or not anything sold.
+///
+/// `winning_bid_id` is `None` when there were no bids or the best one missed
+/// the reserve — in both cases the property stays with the seller.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuctionSettledEvent {
+    pub token_id: u64,
+    pub winning_bid_id: Option<u64>,
+    pub winner_id: Option<AccountId>,
+    pub amount: u128,
+    pub reserve_met: bool,
+}
+
+/// Event emitted when a seller withdraws an auction nobody has bid on yet
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuctionCancelledEvent {
+    pub token_id: u64,
+    pub seller_id: AccountId,
+}
+
+/// Event emitted when a bidder commits a sealed bid
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SealedBidCommittedEvent {
+    pub token_id: u64,
+    pub commit_id: u64,
+    pub bidder_id: AccountId,
+    pub deposit: u128,
+}
+
+/// Event emitted when a sealed bid is revealed.
+///
+/// `valid` is false when the revealed amount exceeds the deposit behind it;
+/// such a bid can't win and is refunded like any other loser.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SealedBidRevealedEvent {
+    pub token_id: u64,
+    pub commit_id: u64,
+    pub bidder_id: AccountId,
+    pub amount: u128,
+    pub valid: bool,
+}
+
+/// Event emitted when a sealed-bid deposit (or the winner's excess) is returned
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SealedCommitRefundedEvent {
+    pub token_id: u64,
+    pub commit_id: u64,
+    pub bidder_id: AccountId,
+    pub amount: u128,
+}
+
+/// Event emitted for each round of a negotiation, from either side
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CounterOfferMadeEvent {
+    pub token_id: u64,
+    pub bid_id: u64,
+    pub round: u32,
+    pub proposer_id: AccountId,
+    pub amount: u128,
+    pub terms: Option<String>,
+}
+
+/// Event emitted when a bidder accepts the seller's counter-offer
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CounterOfferAcceptedEvent {
+    pub token_id: u64,
+    pub bid_id: u64,
+    pub round: u32,
+    pub bidder_id: AccountId,
+    pub previous_amount: u128,
+    pub amount: u128,
+}
+
+/// Event emitted when a bidder turns a counter-offer down
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CounterOfferDeclinedEvent {
+    pub token_id: u64,
+    pub bid_id: u64,
+    pub round: u32,
+    pub bidder_id: AccountId,
+}
+
+/// Event emitted when a bidder raises or lowers a pending bid in place
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BidAmendedEvent {
+    pub token_id: u64,
+    pub bid_id: u64,
+    pub bidder_id: AccountId,
+    pub previous_amount: u128,
+    pub new_amount: u128,
+}
+
+/// Event emitted when an earnest-money buyer pays the balance due at closing
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BalancePaidEvent {
+    pub token_id: u64,
+    pub bid_id: u64,
+    pub bidder_id: AccountId,
+    pub amount: u128,
+    pub total_paid: u128,
+}
+
+/// Event emitted when an earnest-money buyer missed the balance deadline.
+///
+/// `forfeited` went to the seller, `refunded` back to the buyer.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EarnestMoneyForfeitedEvent {
+    pub token_id: u64,
+    pub bid_id: u64,
+    pub bidder_id: AccountId,
+    pub seller_id: AccountId,
+    pub forfeited: u128,
+    pub refunded: u128,
+}
+
+/// Event emitted when a buyer pays an instalment. `remaining` is what is still
+/// owed after it.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InstalmentPaidEvent {
+    pub token_id: u64,
+    pub bid_id: u64,
+    pub buyer_id: AccountId,
+    pub instalment: u32,
+    pub amount: u128,
+    pub remaining: u128,
+}
+
+/// Event emitted when the last instalment is paid and title passes to the buyer
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InstalmentPlanCompletedEvent {
+    pub token_id: u64,
+    pub bid_id: u64,
+    pub buyer_id: AccountId,
+    pub seller_id: AccountId,
+    pub total_paid: u128,
+}
+
+/// Event emitted when a seller forecloses on a defaulted instalment plan
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InstalmentPlanDefaultedEvent {
+    pub token_id: u64,
+    pub bid_id: u64,
+    pub buyer_id: AccountId,
+    pub seller_id: AccountId,
+    pub paid_instalments: u32,
+    pub amount_paid: u128,
+}
+
+/// Event emitted for each period of rent paid
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentPaidEvent {
+    pub token_id: u64,
+    pub lease_id: u64,
+    pub tenant_id: AccountId,
+    pub period: u32,
+    pub due_at: u64,
+    pub amount: u128,
+    pub late_fee: u128,
+}
+
+/// Event emitted when an owner files deductions against a damage deposit
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DepositDeductionsFiledEvent {
+    pub token_id: u64,
+    pub lease_id: u64,
+    pub owner_id: AccountId,
+    pub tenant_id: AccountId,
+    pub deducted: u128,
+    pub respond_by: u64,
+}
+
+/// Event emitted when a damage deposit is paid out.
+///
+/// `deducted` went to the owner, `refunded` back to the tenant.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DamageDepositSettledEvent {
+    pub token_id: u64,
+    pub lease_id: u64,
+    pub owner_id: AccountId,
+    pub tenant_id: AccountId,
+    pub deducted: u128,
+    pub refunded: u128,
+}
+
+/// Event emitted when either side gives notice to end a lease early
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LeaseTerminationNoticedEvent {
+    pub token_id: u64,
+    pub lease_id: u64,
+    pub initiated_by: AccountId,
+    pub effective_at: u64,
+    pub refund_due: u128,
+}
+
+/// Event emitted when a lease ends early.
+///
+/// `refund` is what the owner owes the tenant for the unused term, after the
+/// `penalty`; `escrow_returned` is as in `LeaseExpiredEvent`.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LeaseTerminatedEarlyEvent {
+    pub token_id: u64,
+    pub lease_id: u64,
+    pub tenant_id: AccountId,
+    pub owner_id: AccountId,
+    pub initiated_by: AccountId,
+    pub refund: u128,
+    /// What the owner still owes the tenant; it stays payable.
+    pub refund_outstanding: u128,
+    pub penalty: u128,
+    pub escrow_returned: u128,
+}
+
+/// Event emitted when a lease passes to a new tenant
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LeaseAssignedEvent {
+    pub token_id: u64,
+    pub lease_id: u64,
+    pub from_tenant_id: AccountId,
+    pub to_tenant_id: AccountId,
+}
+
+/// Event emitted when a subtenant moves in under a primary lease
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SubleaseStartedEvent {
+    pub token_id: u64,
+    pub lease_id: u64,
+    pub parent_lease_id: u64,
+    pub tenant_id: AccountId,
+    pub subtenant_id: AccountId,
+    pub end_time: u64,
+}
+
+/// Event emitted when an invited co-bidder puts money into a bid
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BidCoFundedEvent {
+    pub token_id: u64,
+    pub bid_id: u64,
+    pub bidder_id: AccountId,
+    pub co_bidder_id: AccountId,
+    pub amount: u128,
+    pub new_amount: u128,
+}
+
+/// Event emitted when co-bidders are paid back their contributions
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CoBiddersRefundedEvent {
+    pub token_id: u64,
+    pub bid_id: u64,
+    pub refunds: Vec<(AccountId, u128)>,
+}
+
+/// Event emitted when the tenant or the owner opts out of auto-renewal
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AutoRenewalOptedOutEvent {
+    pub token_id: u64,
+    pub lease_id: u64,
+    pub opted_out_by: AccountId,
+    pub lease_end_time: u64,
+}
+
+/// Event emitted when a short stay is booked
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StayBookedEvent {
+    pub token_id: u64,
+    pub booking_id: u64,
+    pub guest_id: AccountId,
+    pub check_in: u64,
+    pub check_out: u64,
+    pub amount: u128,
+}
+
+/// Event emitted when a short-stay booking is cancelled
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BookingCancelledEvent {
+    pub token_id: u64,
+    pub booking_id: u64,
+    pub cancelled_by: AccountId,
+    pub refunded: u128,
+    pub retained: u128,
+}
+
+/// Event emitted when a booking's payment is released to the owner
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BookingSettledEvent {
+    pub token_id: u64,
+    pub booking_id: u64,
+    pub owner_id: AccountId,
+    pub amount: u128,
+}
+
+/// Event emitted when a property is added to a building as one of its units
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BuildingUnitAddedEvent {
+    pub token_id: u64,
+    pub unit_id: u64,
+    pub owner_id: AccountId,
+}
+
+/// Event emitted when a unit is taken out of its building
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BuildingUnitRemovedEvent {
+    pub token_id: u64,
+    pub unit_id: u64,
+}
+
+/// Event emitted when a property is split into ownership shares
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PropertyFractionalisedEvent {
+    pub token_id: u64,
+    pub manager_id: AccountId,
+    pub total_shares: u128,
+    pub reserve_price: u128,
+}
+
+/// Event emitted when ownership shares change hands
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SharesTransferredEvent {
+    pub token_id: u64,
+    pub from: AccountId,
+    pub to: AccountId,
+    pub shares: u128,
+}
+
+/// Event emitted when a fractionalised property is back with one owner
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PropertyRecombinedEvent {
+    pub token_id: u64,
+    pub owner_id: AccountId,
+    pub paid_to_shareholders: u128,
+}
+
+/// Event emitted when an owner sets how a property's payments are split
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PayoutSplitSetEvent {
+    pub token_id: u64,
+    pub owner_id: AccountId,
+    pub recipients: Vec<(AccountId, u16)>,
+}
+
+/// Event emitted when a failed payout is held for its recipient to claim
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PayoutHeldEvent {
+    pub account_id: AccountId,
+    pub token: AccountId,
+    pub amount: u128,
+}
+
+/// Event emitted when fees are taken out of a payment to an owner
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeesCollectedEvent {
+    pub token_id: u64,
+    pub stablecoin_token: AccountId,
+    pub platform_fee: u128,
+    pub broker_id: Option<AccountId>,
+    pub commission: u128,
+}
+
+/// Event emitted when the contract owner withdraws accrued platform fees
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeesWithdrawnEvent {
+    pub stablecoin_token: AccountId,
+    pub amount: u128,
+    pub recipient: AccountId,
+}
+
+/// Event emitted when a property's royalties are set
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoyaltiesSetEvent {
+    pub token_id: u64,
+    pub set_by: AccountId,
+    pub royalties: Vec<(AccountId, u16)>,
+}
+
+/// Event emitted when royalties are paid out of a sale
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoyaltiesPaidEvent {
+    pub token_id: u64,
+    pub bid_id: Option<u64>,
+    pub royalties: Vec<(AccountId, u128)>,
+}
+
+/// Event emitted when a property is listed through nft_transfer_call or
+/// nft_approve
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PropertyListedEvent {
+    pub token_id: u64,
+    pub owner_id: AccountId,
+    pub price: u128,
+    pub is_for_sale: bool,
+    pub lease_duration_months: Option<u64>,
+    pub custody: bool,
+}
+
+/// Event emitted when evidence is added to a dispute's case file
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EvidenceSubmittedEvent {
+    pub case: DisputeCase,
+    pub submitted_by: AccountId,
+    pub uri: String,
+    pub content_hash: String,
+}
+
+/// Event emitted when a dispute is put before a juror panel
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ArbitrationRequestedEvent {
+    pub case: DisputeCase,
+    pub requested_by: AccountId,
+    pub panel: Vec<AccountId>,
+}
+
+/// Event emitted when a juror panel is closed; `raiser_won` is None when it
+/// reached no verdict
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ArbitrationDecidedEvent {
+    pub case: DisputeCase,
+    pub raiser_won: Option<bool>,
+    pub rewards: Vec<(AccountId, u128)>,
+    pub slashed: Vec<(AccountId, u128)>,
+}
@@ -0,0 +1,178 @@
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, AccountId};
+
+use crate::{
+    events::{emit_event, EvidenceSubmittedEvent},
+    models::{BidStatus, CaseFile, DisputeCase, EvidenceEntry},
+    views::{CaseFileView, EvidenceEntryView, MAX_PAGINATION_LIMIT},
+    ShedaContract, ShedaContractExt,
+};
+
+/// Enough for a case to be argued without a file growing without bound.
+const MAX_EVIDENCE_ENTRIES: usize = 100;
+const MAX_URI_LEN: usize = 512;
+const MAX_CONTENT_HASH_LEN: usize = 128;
+const MAX_DESCRIPTION_LEN: usize = 1_000;
+
+/// Open the case file on a dispute `raised_by` one side and answered by
+/// `respondent`, whose response period starts now. A lease disputed again
+/// after an earlier dispute was settled keeps its earlier evidence.
+pub(crate) fn open_case(
+    contract: &mut ShedaContract,
+    case: DisputeCase,
+    raised_by: AccountId,
+    respondent: AccountId,
+) {
+    let now = env::block_timestamp();
+    let response_deadline = now.saturating_add(contract.dispute_response_period_ns);
+    let entries = contract
+        .dispute_case_files
+        .remove(&case)
+        .map(|file| file.entries)
+        .unwrap_or_default();
+    contract.dispute_case_files.insert(
+        case,
+        CaseFile {
+            raised_by,
+            respondent,
+            opened_at: now,
+            response_deadline,
+            entries,
+        },
+    );
+}
+
+/// Open the case file on a lease dispute the tenant raised against their
+/// landlord.
+pub(crate) fn open_lease_case(contract: &mut ShedaContract, lease_id: u64, tenant_id: AccountId) {
+    let lease = contract
+        .leases
+        .get(&lease_id)
+        .cloned()
+        .expect("Lease not found");
+    let landlord = crate::sublease::landlord_of(contract, &lease);
+    open_case(
+        contract,
+        DisputeCase::Lease { lease_id },
+        tenant_id,
+        landlord,
+    );
+}
+
+/// Whether the dispute behind `case` is still waiting on a decision.
+fn is_open(contract: &ShedaContract, case: &DisputeCase) -> bool {
+    match case {
+        DisputeCase::Lease { lease_id } => contract
+            .leases
+            .get(lease_id)
+            .is_some_and(|lease| lease.has_open_dispute()),
+        DisputeCase::Bid {
+            property_id,
+            bid_id,
+        } => contract
+            .bids
+            .get(property_id)
+            .and_then(|bids| bids.iter().find(|bid| bid.id == *bid_id))
+            .is_some_and(|bid| bid.status == BidStatus::Disputed),
+    }
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Add a piece of evidence to a dispute's case file: a link to the
+    /// document, its hash so it can't be swapped later, and what it shows.
+    /// Either side can add evidence until the response period ends; admins
+    /// can add notes until the dispute is resolved.
+    #[payable]
+    pub fn submit_evidence(
+        &mut self,
+        case: DisputeCase,
+        uri: String,
+        content_hash: String,
+        description: String,
+    ) {
+        assert_one_yocto();
+
+        let caller = env::predecessor_account_id();
+        require!(is_open(self, &case), "The dispute is not open");
+        let file = self
+            .dispute_case_files
+            .get(&case)
+            .expect("No case file for this dispute");
+        if !self.admins.contains(&caller) {
+            require!(
+                caller == file.raised_by || caller == file.respondent,
+                "Only the parties to the dispute or an admin can submit evidence"
+            );
+            require!(
+                env::block_timestamp() <= file.response_deadline,
+                "The response period has ended"
+            );
+        }
+        require!(
+            file.entries.len() < MAX_EVIDENCE_ENTRIES,
+            "The case file is full"
+        );
+        require!(
+            !uri.is_empty() && uri.len() <= MAX_URI_LEN,
+            format!("The URI must be 1 to {} bytes", MAX_URI_LEN)
+        );
+        require!(
+            !content_hash.is_empty() && content_hash.len() <= MAX_CONTENT_HASH_LEN,
+            format!(
+                "The content hash must be 1 to {} bytes",
+                MAX_CONTENT_HASH_LEN
+            )
+        );
+        require!(
+            description.len() <= MAX_DESCRIPTION_LEN,
+            format!(
+                "The description can be at most {} bytes",
+                MAX_DESCRIPTION_LEN
+            )
+        );
+
+        emit_event(
+            "EvidenceSubmitted",
+            EvidenceSubmittedEvent {
+                case: case.clone(),
+                submitted_by: caller.clone(),
+                uri: uri.clone(),
+                content_hash: content_hash.clone(),
+            },
+        );
+        if let Some(file) = self.dispute_case_files.get_mut(&case) {
+            file.entries.push(EvidenceEntry {
+                submitted_by: caller,
+                uri,
+                content_hash,
+                description,
+                submitted_at: env::block_timestamp(),
+            });
+        }
+    }
+
+    pub fn get_dispute_case_file(&self, case: DisputeCase) -> Option<CaseFileView> {
+        self.dispute_case_files.get(&case).map(|file| file.into())
+    }
+
+    /// A dispute's evidence, oldest first.
+    pub fn get_dispute_evidence(
+        &self,
+        case: DisputeCase,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<EvidenceEntryView> {
+        let limit = limit.min(MAX_PAGINATION_LIMIT);
+        self.dispute_case_files
+            .get(&case)
+            .map(|file| {
+                file.entries
+                    .iter()
+                    .skip(from_index as usize)
+                    .take(limit as usize)
+                    .map(|entry| entry.into())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,331 @@
+use near_sdk::{
+    assert_one_yocto, env, json_types::U128, log, near_bindgen, require, AccountId, Gas, NearToken,
+};
+
+use crate::{
+    events::{emit_event, FeesCollectedEvent, FeesWithdrawnEvent},
+    ext::ft_contract,
+    internal::{checked_add_u128, checked_sub_u128},
+    models::{Action, Bid, BrokerCommission, FeeCharge, PlatformFee},
+    views::{AccruedFeeView, BrokerCommissionView, FeeQuoteView, PlatformFeeView},
+    ShedaContract, ShedaContractExt,
+};
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+fn bps_of(amount: u128, bps: u16) -> u128 {
+    amount
+        .checked_mul(u128::from(bps))
+        .unwrap_or_else(|| env::panic_str("Overflow in fee"))
+        / BPS_DENOMINATOR
+}
+
+fn owner_of(contract: &ShedaContract, property_id: u64) -> AccountId {
+    contract
+        .properties
+        .get(&property_id)
+        .map(|property| property.owner_id.clone())
+        .expect("Property not found")
+}
+
+fn platform_fee_for(
+    contract: &ShedaContract,
+    token: &AccountId,
+    action: &Action,
+) -> Option<PlatformFee> {
+    contract
+        .platform_fees
+        .get(token)?
+        .iter()
+        .find(|fee| fee.action == *action)
+        .cloned()
+}
+
+/// The fees on `amount` paid to a property's owner for a deal of kind
+/// `action`: the platform's cut first, then the listing broker's commission,
+/// neither taking more than is left.
+fn quote(
+    contract: &ShedaContract,
+    property_id: u64,
+    action: &Action,
+    token: &AccountId,
+    amount: u128,
+) -> FeeCharge {
+    let platform_fee = platform_fee_for(contract, token, action)
+        .map(|fee| checked_add_u128(bps_of(amount, fee.bps), fee.flat, "platform fee"))
+        .unwrap_or(0)
+        .min(amount);
+    let broker = contract.listing_brokers.get(&property_id);
+    let commission = broker
+        .map(|broker| bps_of(amount, broker.bps))
+        .unwrap_or(0)
+        .min(amount - platform_fee);
+    FeeCharge {
+        property_id,
+        stablecoin_token: token.clone(),
+        platform_fee,
+        broker_id: broker.map(|broker| broker.broker_id.clone()),
+        commission,
+    }
+}
+
+/// Book the platform's cut in the fee ledger and pay the broker. The tokens
+/// are already off the escrow ledger or were never on it.
+fn collect(contract: &mut ShedaContract, charge: FeeCharge) {
+    if charge.platform_fee == 0 && charge.commission == 0 {
+        return;
+    }
+    if charge.platform_fee > 0 {
+        let accrued = contract
+            .accrued_fees
+            .entry(charge.stablecoin_token.clone())
+            .or_insert(0);
+        *accrued = checked_add_u128(*accrued, charge.platform_fee, "accrued fees");
+    }
+    if let Some(broker_id) = charge.broker_id.clone().filter(|_| charge.commission > 0) {
+        #[allow(unused_must_use)]
+        crate::payout::send_payouts(
+            &charge.stablecoin_token,
+            vec![(broker_id, charge.commission)],
+        );
+    }
+
+    emit_event(
+        "FeesCollected",
+        FeesCollectedEvent {
+            token_id: charge.property_id,
+            stablecoin_token: charge.stablecoin_token,
+            platform_fee: charge.platform_fee,
+            broker_id: charge.broker_id,
+            commission: charge.commission,
+        },
+    );
+}
+
+/// Take the fees out of a payment to `payee` that can't be undone, and
+/// return what's left for them. Only payments to the owner pay fees; a
+/// landlord collecting from a subtenant is paid in full.
+pub(crate) fn charge(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    action: &Action,
+    token: &AccountId,
+    payee: &AccountId,
+    amount: u128,
+) -> u128 {
+    if *payee != owner_of(contract, property_id) {
+        return amount;
+    }
+    let charge = quote(contract, property_id, action, token, amount);
+    let net = amount - charge.platform_fee - charge.commission;
+    collect(contract, charge);
+    net
+}
+
+/// Work out the fees on paying `amount` of a bid to the owner and return
+/// what's left for them. Nothing is collected until the owner's transfer
+/// goes through: the bid's callback calls `settle_bid` or `drop_bid`.
+pub(crate) fn charge_bid(contract: &mut ShedaContract, bid: &Bid, amount: u128) -> u128 {
+    let charge = quote(
+        contract,
+        bid.property_id,
+        &bid.action,
+        &bid.stablecoin_token,
+        amount,
+    );
+    let net = amount - charge.platform_fee - charge.commission;
+    contract.pending_fees.insert(bid.id, charge);
+    net
+}
+
+/// The owner was paid; collect the fees held back from them.
+pub(crate) fn settle_bid(contract: &mut ShedaContract, bid_id: u64) {
+    if let Some(charge) = contract.pending_fees.remove(&bid_id) {
+        collect(contract, charge);
+    }
+}
+
+/// The owner's payment failed and goes back to escrow whole, fees and all.
+pub(crate) fn drop_bid(contract: &mut ShedaContract, bid_id: u64) {
+    contract.pending_fees.remove(&bid_id);
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Contract owner sets the platform fee on payments to property owners
+    /// for one kind of deal in one stablecoin: `bps` of each payment plus a
+    /// `flat` amount. Replaces any fee already set for the pair.
+    pub fn set_platform_fee(
+        &mut self,
+        action: Action,
+        stablecoin_token: AccountId,
+        bps: u16,
+        flat: U128,
+    ) {
+        self.assert_owner();
+        require!(
+            self.accepted_stablecoin.contains(&stablecoin_token),
+            "Stablecoin not accepted"
+        );
+        require!(bps <= 10_000, "Basis points can't exceed 10000");
+
+        let fees = self
+            .platform_fees
+            .entry(stablecoin_token.clone())
+            .or_default();
+        fees.retain(|fee| fee.action != action);
+        fees.push(PlatformFee {
+            action: action.clone(),
+            bps,
+            flat: flat.0,
+        });
+        log!(
+            "Platform fee on {:?} in {} set to {} bps + {} by owner {}",
+            action,
+            stablecoin_token,
+            bps,
+            flat.0,
+            env::predecessor_account_id()
+        );
+    }
+
+    /// Contract owner stops charging a platform fee for one kind of deal in
+    /// one stablecoin.
+    pub fn clear_platform_fee(&mut self, action: Action, stablecoin_token: AccountId) {
+        self.assert_owner();
+        if let Some(fees) = self.platform_fees.get_mut(&stablecoin_token) {
+            fees.retain(|fee| fee.action != action);
+            if fees.is_empty() {
+                self.platform_fees.remove(&stablecoin_token);
+            }
+        }
+    }
+
+    /// Owner attaches a broker to the listing, paid `bps` of every payment
+    /// the owner receives on it until cleared or the property changes hands.
+    #[payable]
+    pub fn set_listing_broker(&mut self, property_id: u64, broker_id: AccountId, bps: u16) {
+        assert_one_yocto();
+
+        let owner_id = owner_of(self, property_id);
+        assert_eq!(
+            owner_id,
+            env::predecessor_account_id(),
+            "Only the property owner can set a broker"
+        );
+        require!(broker_id != owner_id, "The owner can't be their own broker");
+        require!(
+            bps > 0 && bps <= 10_000,
+            "Commission must be between 1 and 10000 basis points"
+        );
+        self.listing_brokers
+            .insert(property_id, BrokerCommission { broker_id, bps });
+    }
+
+    /// Owner takes the broker off the listing.
+    #[payable]
+    pub fn clear_listing_broker(&mut self, property_id: u64) {
+        assert_one_yocto();
+
+        assert_eq!(
+            owner_of(self, property_id),
+            env::predecessor_account_id(),
+            "Only the property owner can clear a broker"
+        );
+        self.listing_brokers.remove(&property_id);
+    }
+
+    /// Contract owner withdraws platform fees accrued in `stablecoin_token`,
+    /// all of them unless `amount` is given.
+    pub fn withdraw_fees(&mut self, stablecoin_token: AccountId, amount: Option<U128>) -> U128 {
+        self.assert_owner();
+        let accrued = *self.accrued_fees.get(&stablecoin_token).unwrap_or(&0);
+        let amount = amount.map(|amount| amount.0).unwrap_or(accrued);
+        require!(amount > 0, "No fees to withdraw");
+        require!(amount <= accrued, "Not that much in fees accrued");
+        self.accrued_fees.insert(
+            stablecoin_token.clone(),
+            checked_sub_u128(accrued, amount, "fee withdrawal"),
+        );
+
+        let recipient = self.owner_id.clone();
+        #[allow(unused_must_use)]
+        ft_contract::ext(stablecoin_token.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(Gas::from_tgas(30))
+            .ft_transfer(recipient.clone(), U128(amount))
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas::from_tgas(10))
+                    .withdraw_fees_callback(stablecoin_token.clone(), U128(amount)),
+            );
+
+        emit_event(
+            "FeesWithdrawn",
+            FeesWithdrawnEvent {
+                stablecoin_token,
+                amount,
+                recipient,
+            },
+        );
+        U128(amount)
+    }
+
+    #[private]
+    pub fn withdraw_fees_callback(&mut self, stablecoin_token: AccountId, amount: U128) {
+        if crate::internal::promise_failed(0) {
+            let accrued = self.accrued_fees.entry(stablecoin_token).or_insert(0);
+            *accrued = checked_add_u128(*accrued, amount.0, "fee withdrawal revert");
+            log!("Fee withdrawal of {} failed, still accrued", amount.0);
+        }
+    }
+
+    pub fn get_platform_fees(&self) -> Vec<PlatformFeeView> {
+        self.platform_fees
+            .iter()
+            .flat_map(|(token, fees)| {
+                fees.iter().map(move |fee| PlatformFeeView {
+                    stablecoin_token: token.to_string(),
+                    action: fee.action.clone(),
+                    bps: fee.bps,
+                    flat: fee.flat.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn get_listing_broker(&self, property_id: u64) -> Option<BrokerCommissionView> {
+        self.listing_brokers
+            .get(&property_id)
+            .map(|broker| broker.into())
+    }
+
+    /// What would be taken out of `amount` paid to the property's owner for
+    /// a deal of kind `action`.
+    pub fn quote_fees(
+        &self,
+        property_id: u64,
+        action: Action,
+        stablecoin_token: AccountId,
+        amount: U128,
+    ) -> FeeQuoteView {
+        let charge = quote(self, property_id, &action, &stablecoin_token, amount.0);
+        FeeQuoteView {
+            platform_fee: charge.platform_fee.to_string(),
+            broker_id: charge.broker_id.map(|broker| broker.to_string()),
+            commission: charge.commission.to_string(),
+            net: (amount.0 - charge.platform_fee - charge.commission).to_string(),
+        }
+    }
+
+    /// Platform fees accrued and not yet withdrawn, per stablecoin.
+    pub fn get_accrued_fees(&self) -> Vec<AccruedFeeView> {
+        self.accrued_fees
+            .iter()
+            .map(|(token, amount)| AccruedFeeView {
+                stablecoin_token: token.to_string(),
+                amount: amount.to_string(),
+            })
+            .collect()
+    }
+}
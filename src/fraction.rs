@@ -0,0 +1,587 @@
+use near_sdk::{
+    assert_one_yocto, env, json_types::U128, log, near_bindgen, require, AccountId, Gas, NearToken,
+};
+
+use crate::{
+    events::{
+        emit_event, PropertyFractionalisedEvent, PropertyRecombinedEvent, SharesTransferredEvent,
+    },
+    ext::ft_contract,
+    internal::{
+        assert_no_blocking_bids, checked_add_u128, checked_sub_u128, credit_escrow, debit_escrow,
+        transfer_property_ownership,
+    },
+    models::{Action, Fractional, FractionalStatus, ShareHolding},
+    views::{FractionalView, ShareHoldingView},
+    ShedaContract, ShedaContractExt,
+};
+
+/// Scale of `Fractional::income_per_share`, so small payments over a large
+/// supply of shares don't round away to nothing.
+const SHARE_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+fn checked_mul_u128(left: u128, right: u128, label: &str) -> u128 {
+    left.checked_mul(right)
+        .unwrap_or_else(|| env::panic_str(&format!("Overflow in {}", label)))
+}
+
+fn active_fractional(contract: &ShedaContract, property_id: u64) -> Option<&Fractional> {
+    contract
+        .fractional_properties
+        .get(&property_id)
+        .filter(|fractional| fractional.status == FractionalStatus::Active)
+}
+
+fn fractional_for(contract: &ShedaContract, property_id: u64) -> Fractional {
+    active_fractional(contract, property_id)
+        .cloned()
+        .expect("Property isn't fractionalised")
+}
+
+fn shares_of(contract: &ShedaContract, property_id: u64, account_id: &AccountId) -> u128 {
+    contract
+        .share_holdings
+        .get(&property_id)
+        .and_then(|holdings| {
+            holdings
+                .iter()
+                .find(|holding| holding.account_id == *account_id)
+        })
+        .map(|holding| holding.shares)
+        .unwrap_or(0)
+}
+
+/// Income accrued on a stake since it was last settled.
+fn accrued(income_per_share: u128, holding: &ShareHolding) -> u128 {
+    checked_mul_u128(
+        holding.shares,
+        income_per_share - holding.income_snapshot,
+        "share income",
+    ) / SHARE_PRECISION
+}
+
+/// Bring every stake up to date with the income received so far, so shares
+/// can change hands without moving income with them.
+fn settle_holdings(contract: &mut ShedaContract, property_id: u64) {
+    let income_per_share = contract
+        .fractional_properties
+        .get(&property_id)
+        .map(|fractional| fractional.income_per_share)
+        .unwrap_or(0);
+    if let Some(holdings) = contract.share_holdings.get_mut(&property_id) {
+        for holding in holdings.iter_mut() {
+            holding.unclaimed = checked_add_u128(
+                holding.unclaimed,
+                accrued(income_per_share, holding),
+                "share income",
+            );
+            holding.income_snapshot = income_per_share;
+        }
+    }
+}
+
+fn move_shares(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    from: &AccountId,
+    to: &AccountId,
+    shares: u128,
+) {
+    settle_holdings(contract, property_id);
+    let income_per_share = contract
+        .fractional_properties
+        .get(&property_id)
+        .map(|fractional| fractional.income_per_share)
+        .unwrap_or(0);
+    let holdings = contract
+        .share_holdings
+        .get_mut(&property_id)
+        .expect("Property has no shareholders");
+
+    let sender = holdings
+        .iter_mut()
+        .find(|holding| holding.account_id == *from)
+        .expect("You hold no shares in this property");
+    sender.shares = checked_sub_u128(sender.shares, shares, "share transfer");
+
+    match holdings
+        .iter_mut()
+        .find(|holding| holding.account_id == *to)
+    {
+        Some(receiver) => {
+            receiver.shares = checked_add_u128(receiver.shares, shares, "share transfer");
+        }
+        None => holdings.push(ShareHolding {
+            account_id: to.clone(),
+            shares,
+            income_snapshot: income_per_share,
+            unclaimed: 0,
+        }),
+    }
+    // A holder with nothing left to own or claim drops off the ledger.
+    holdings.retain(|holding| holding.shares > 0 || holding.unclaimed > 0);
+}
+
+/// Who holds the NFT when nobody is leasing it: the contract while the
+/// property is fractionalised or listed in its custody, otherwise its owner.
+pub(crate) fn nft_custodian(contract: &ShedaContract, property_id: u64) -> AccountId {
+    if active_fractional(contract, property_id).is_some()
+        || crate::listing::in_custody(contract, property_id)
+    {
+        return env::current_account_id();
+    }
+    contract
+        .properties
+        .get(&property_id)
+        .expect("Property not found")
+        .owner_id
+        .clone()
+}
+
+/// A fractionalised property is only let, in its income token; it leaves the
+/// shareholders by buyout, not by sale. Called wherever a bid is accepted.
+pub(crate) fn assert_fraction_allows_bid(contract: &ShedaContract, property_id: u64, bid_id: u64) {
+    let Some(fractional) = active_fractional(contract, property_id) else {
+        return;
+    };
+    let bid = contract
+        .bids
+        .get(&property_id)
+        .and_then(|bids| bids.iter().find(|b| b.id == bid_id))
+        .expect("Bid not found for the property");
+    require!(
+        matches!(bid.action, Action::Lease),
+        "A fractionalised property can only be bought out, not sold"
+    );
+    require!(
+        bid.stablecoin_token == fractional.income_token,
+        format!(
+            "Leases on this property are paid in {}",
+            fractional.income_token
+        )
+    );
+}
+
+/// Whether a property's income goes to its shareholders rather than its owner.
+pub(crate) fn is_fractionalised(contract: &ShedaContract, property_id: u64) -> bool {
+    active_fractional(contract, property_id).is_some()
+}
+
+/// Keep what a fractionalised property earns in the contract for its
+/// shareholders, pro rata, to claim when they like.
+pub(crate) fn share_income(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    token: &AccountId,
+    amount: u128,
+) {
+    let fractional = fractional_for(contract, property_id);
+    require!(
+        *token == fractional.income_token,
+        "Lease income must be in the property's income token"
+    );
+
+    let rise = checked_mul_u128(amount, SHARE_PRECISION, "share income") / fractional.total_shares;
+    if let Some(fractional) = contract.fractional_properties.get_mut(&property_id) {
+        fractional.income_per_share =
+            checked_add_u128(fractional.income_per_share, rise, "share income");
+        fractional.total_income = checked_add_u128(fractional.total_income, amount, "share income");
+    }
+    credit_escrow(contract, token, amount, "share income");
+    log!(
+        "{} of lease income on property {} shared among its shareholders",
+        amount,
+        property_id
+    );
+}
+
+/// Buy out every other shareholder with an `ft_on_transfer` `BuyOutShares`
+/// paid in the income token. Each of them is owed their part of the reserve
+/// price, to claim when they like; the buyer takes the NFT. Returns what was
+/// sent beyond the price.
+pub(crate) fn buy_out(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    sender_id: AccountId,
+    amount: u128,
+) -> u128 {
+    let fractional = fractional_for(contract, property_id);
+    require!(
+        fractional.income_token == env::predecessor_account_id(),
+        "A buyout is paid in the property's income token"
+    );
+    let others = fractional.total_shares - shares_of(contract, property_id, &sender_id);
+    require!(others > 0, "You already hold every share");
+    let price = checked_mul_u128(fractional.reserve_price, others, "buyout price")
+        .div_ceil(fractional.total_shares);
+    require!(amount >= price, format!("The buyout comes to {}", price));
+
+    credit_escrow(contract, &fractional.income_token, price, "buyout");
+    settle_holdings(contract, property_id);
+    let holders: Vec<(AccountId, u128)> = contract
+        .share_holdings
+        .get(&property_id)
+        .map(|holdings| {
+            holdings
+                .iter()
+                .filter(|holding| holding.account_id != sender_id && holding.shares > 0)
+                .map(|holding| (holding.account_id.clone(), holding.shares))
+                .collect()
+        })
+        .unwrap_or_default();
+    for (account_id, shares) in holders {
+        move_shares(contract, property_id, &account_id, &sender_id, shares);
+        let proceeds = checked_mul_u128(fractional.reserve_price, shares, "buyout proceeds")
+            / fractional.total_shares;
+        if let Some(holding) = contract
+            .share_holdings
+            .get_mut(&property_id)
+            .and_then(|holdings| {
+                holdings
+                    .iter_mut()
+                    .find(|holding| holding.account_id == account_id)
+            })
+        {
+            holding.unclaimed = checked_add_u128(holding.unclaimed, proceeds, "buyout proceeds");
+        } else {
+            contract
+                .share_holdings
+                .entry(property_id)
+                .or_default()
+                .push(ShareHolding {
+                    account_id,
+                    shares: 0,
+                    income_snapshot: fractional.income_per_share,
+                    unclaimed: proceeds,
+                });
+        }
+    }
+
+    recombine(contract, property_id, &sender_id, price);
+    amount - price
+}
+
+/// Hand the NFT and the property to whoever now holds every share.
+fn recombine(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    owner_id: &AccountId,
+    paid_to_shareholders: u128,
+) {
+    let is_let = contract
+        .properties
+        .get(&property_id)
+        .expect("Property not found")
+        .active_lease
+        .as_ref()
+        .is_some_and(|lease| lease.active);
+    require!(
+        !is_let,
+        "The property can be recombined once its lease ends"
+    );
+
+    if let Some(fractional) = contract.fractional_properties.get_mut(&property_id) {
+        fractional.status = FractionalStatus::Recombined;
+        fractional.recombined_by = Some(owner_id.clone());
+    }
+    if let Some(holdings) = contract.share_holdings.get_mut(&property_id) {
+        for holding in holdings.iter_mut() {
+            holding.shares = 0;
+        }
+        holdings.retain(|holding| holding.unclaimed > 0);
+    }
+
+    contract.tokens.internal_transfer(
+        &env::current_account_id(),
+        owner_id,
+        &property_id.to_string(),
+        None,
+        None,
+    );
+    transfer_property_ownership(contract, property_id, owner_id);
+
+    emit_event(
+        "PropertyRecombined",
+        PropertyRecombinedEvent {
+            token_id: property_id,
+            owner_id: owner_id.clone(),
+            paid_to_shareholders,
+        },
+    );
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Owner splits a property into `total_shares` ownership shares, all
+    /// theirs to begin with. The NFT is locked in the contract; the owner
+    /// stays on as manager and lets the property as before, paid in
+    /// `income_token`, while the lease income is shared out among whoever
+    /// holds the shares. Anyone can buy the other shareholders out at their
+    /// part of `reserve_price`.
+    #[payable]
+    pub fn fractionalise_property(
+        &mut self,
+        property_id: u64,
+        total_shares: U128,
+        reserve_price: U128,
+        income_token: AccountId,
+    ) {
+        assert_one_yocto();
+
+        let property = self
+            .properties
+            .get(&property_id)
+            .cloned()
+            .expect("Property not found");
+        let owner_id = env::predecessor_account_id();
+        assert_eq!(
+            property.owner_id, owner_id,
+            "Only the property owner can fractionalise it"
+        );
+        require!(total_shares.0 > 0, "Total shares must be greater than zero");
+        require!(
+            reserve_price.0 > 0,
+            "Reserve price must be greater than zero"
+        );
+        require!(
+            self.accepted_stablecoin.contains(&income_token),
+            "StablecoinNotAccepted"
+        );
+        require!(
+            property.active_lease.is_none(),
+            "Cannot fractionalise a property with an active lease"
+        );
+        require!(
+            self.tokens.owner_by_id.get(&property_id.to_string()) == Some(owner_id.clone()),
+            "You must hold the property's NFT"
+        );
+        require!(
+            self.share_holdings
+                .get(&property_id)
+                .is_none_or(|holdings| holdings.is_empty()),
+            "Earlier shareholders still have proceeds to claim"
+        );
+        assert_no_blocking_bids(self, property_id, "fractionalise");
+        crate::auction::assert_no_open_auction(self, property_id);
+        crate::instalment::assert_no_lien(self, property_id);
+        crate::listing::assert_not_in_custody(self, property_id);
+
+        self.tokens.internal_transfer(
+            &owner_id,
+            &env::current_account_id(),
+            &property_id.to_string(),
+            None,
+            None,
+        );
+        if let Some(property) = self.properties.get_mut(&property_id) {
+            property.is_for_sale = false;
+        }
+
+        self.fractional_properties.insert(
+            property_id,
+            Fractional {
+                manager_id: owner_id.clone(),
+                total_shares: total_shares.0,
+                reserve_price: reserve_price.0,
+                income_token,
+                income_per_share: 0,
+                total_income: 0,
+                status: FractionalStatus::Active,
+                recombined_by: None,
+                created_at: env::block_timestamp(),
+            },
+        );
+        self.share_holdings.insert(
+            property_id,
+            vec![ShareHolding {
+                account_id: owner_id.clone(),
+                shares: total_shares.0,
+                income_snapshot: 0,
+                unclaimed: 0,
+            }],
+        );
+
+        emit_event(
+            "PropertyFractionalised",
+            PropertyFractionalisedEvent {
+                token_id: property_id,
+                manager_id: owner_id,
+                total_shares: total_shares.0,
+                reserve_price: reserve_price.0,
+            },
+        );
+    }
+
+    /// Shareholder passes some of their shares on. Income already earned
+    /// stays with them.
+    #[payable]
+    pub fn transfer_shares(&mut self, property_id: u64, receiver_id: AccountId, shares: U128) {
+        assert_one_yocto();
+
+        fractional_for(self, property_id);
+        let sender_id = env::predecessor_account_id();
+        require!(
+            sender_id != receiver_id,
+            "Can't transfer shares to yourself"
+        );
+        require!(shares.0 > 0, "Shares must be greater than zero");
+        require!(
+            shares_of(self, property_id, &sender_id) >= shares.0,
+            "You don't hold that many shares"
+        );
+
+        move_shares(self, property_id, &sender_id, &receiver_id, shares.0);
+
+        emit_event(
+            "SharesTransferred",
+            SharesTransferredEvent {
+                token_id: property_id,
+                from: sender_id,
+                to: receiver_id,
+                shares: shares.0,
+            },
+        );
+    }
+
+    /// Whoever holds every share takes the property back out of the
+    /// contract.
+    #[payable]
+    pub fn recombine_shares(&mut self, property_id: u64) {
+        assert_one_yocto();
+
+        let fractional = fractional_for(self, property_id);
+        let caller = env::predecessor_account_id();
+        require!(
+            shares_of(self, property_id, &caller) == fractional.total_shares,
+            "Only the holder of every share can recombine the property"
+        );
+        settle_holdings(self, property_id);
+        recombine(self, property_id, &caller, 0);
+    }
+
+    /// Shareholder claims their lease income, and buyout proceeds if they
+    /// were bought out.
+    pub fn claim_share_income(&mut self, property_id: u64) -> U128 {
+        let account_id = env::predecessor_account_id();
+        let token = self
+            .fractional_properties
+            .get(&property_id)
+            .expect("Property was never fractionalised")
+            .income_token
+            .clone();
+        settle_holdings(self, property_id);
+
+        let holdings = self
+            .share_holdings
+            .get_mut(&property_id)
+            .expect("Property has no shareholders");
+        let holding = holdings
+            .iter_mut()
+            .find(|holding| holding.account_id == account_id)
+            .expect("You hold no shares in this property");
+        let amount = holding.unclaimed;
+        require!(amount > 0, "Nothing to claim");
+        holding.unclaimed = 0;
+        holdings.retain(|holding| holding.shares > 0 || holding.unclaimed > 0);
+        debit_escrow(self, &token, amount, "share income claim");
+
+        #[allow(unused_must_use)]
+        ft_contract::ext(token.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(Gas::from_tgas(30))
+            .ft_transfer(account_id.clone(), U128(amount))
+            .then(
+                ShedaContract::ext(env::current_account_id())
+                    .with_static_gas(Gas::from_tgas(15))
+                    .share_claim_callback(property_id, account_id, token, U128(amount)),
+            );
+
+        U128(amount)
+    }
+
+    #[private]
+    pub fn share_claim_callback(
+        &mut self,
+        property_id: u64,
+        account_id: AccountId,
+        token: AccountId,
+        amount: U128,
+    ) {
+        if crate::internal::promise_failed(0) {
+            // Put it back where the holder can claim it again.
+            credit_escrow(self, &token, amount.0, "share income claim revert");
+            let holdings = self.share_holdings.entry(property_id).or_default();
+            match holdings
+                .iter_mut()
+                .find(|holding| holding.account_id == account_id)
+            {
+                Some(holding) => {
+                    holding.unclaimed =
+                        checked_add_u128(holding.unclaimed, amount.0, "share income claim");
+                }
+                None => holdings.push(ShareHolding {
+                    account_id: account_id.clone(),
+                    shares: 0,
+                    income_snapshot: 0,
+                    unclaimed: amount.0,
+                }),
+            }
+            log!(
+                "Share income claim of {} by {} failed, still claimable",
+                amount.0,
+                account_id
+            );
+        }
+    }
+
+    pub fn get_fractional_property(&self, property_id: u64) -> Option<FractionalView> {
+        self.fractional_properties
+            .get(&property_id)
+            .map(|fractional| (property_id, fractional).into())
+    }
+
+    /// A property's share ledger, with what each holder can claim now.
+    pub fn get_shareholders(&self, property_id: u64) -> Vec<ShareHoldingView> {
+        let income_per_share = self
+            .fractional_properties
+            .get(&property_id)
+            .map(|fractional| fractional.income_per_share)
+            .unwrap_or(0);
+        self.share_holdings
+            .get(&property_id)
+            .map(|holdings| {
+                holdings
+                    .iter()
+                    .map(|holding| ShareHoldingView {
+                        property_id,
+                        account_id: holding.account_id.to_string(),
+                        shares: holding.shares.to_string(),
+                        claimable: (holding.unclaimed + accrued(income_per_share, holding))
+                            .to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Every property an account holds shares in or has something to claim
+    /// from.
+    pub fn get_shares_by_account(&self, account_id: AccountId) -> Vec<ShareHoldingView> {
+        self.share_holdings
+            .keys()
+            .flat_map(|property_id| self.get_shareholders(*property_id))
+            .filter(|view| view.account_id == account_id.as_str())
+            .collect()
+    }
+
+    /// What `account_id` would pay to buy out every other shareholder.
+    pub fn get_buyout_price(&self, property_id: u64, account_id: AccountId) -> Option<String> {
+        let fractional = active_fractional(self, property_id)?;
+        let others = fractional.total_shares - shares_of(self, property_id, &account_id);
+        Some(
+            (fractional.reserve_price * others)
+                .div_ceil(fractional.total_shares)
+                .to_string(),
+        )
+    }
+}
@@ -0,0 +1,334 @@
+use near_sdk::{assert_one_yocto, env, log, near_bindgen, require, AccountId};
+
+use crate::{
+    events::{
+        emit_event, InstalmentPaidEvent, InstalmentPlanCompletedEvent, InstalmentPlanDefaultedEvent,
+    },
+    internal::{checked_add_u64, remove_token, transfer_property_ownership, update_bid_in_list},
+    models::{Action, Bid, BidStatus, InstalmentPlan, InstalmentStatus},
+    views::InstalmentPlanView,
+    ShedaContract, ShedaContractExt,
+};
+
+/// Thirty years of monthly payments.
+const MAX_INSTALMENTS: u32 = 360;
+
+fn bid_on(contract: &ShedaContract, property_id: u64, bid_id: u64) -> Bid {
+    contract
+        .bids
+        .get(&property_id)
+        .and_then(|bids| bids.iter().find(|b| b.id == bid_id).cloned())
+        .expect("Bid not found for the property")
+}
+
+fn active_plan(contract: &ShedaContract, bid_id: u64) -> InstalmentPlan {
+    let plan = contract
+        .instalment_plans
+        .get(&bid_id)
+        .cloned()
+        .expect("Not an instalment bid");
+    require!(
+        plan.status == InstalmentStatus::Active,
+        "This instalment plan isn't running"
+    );
+    plan
+}
+
+/// Turn a freshly placed bid into an instalment bid. Called from
+/// `ft_on_transfer` once the bid is in; the transferred amount is the down
+/// payment.
+pub(crate) fn record_instalment_bid(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    bid_id: u64,
+    price: u128,
+    instalment_count: u32,
+    interval_ns: u64,
+) {
+    let bid = bid_on(contract, property_id, bid_id);
+    require!(
+        matches!(bid.action, Action::Purchase),
+        "Instalments only apply to a purchase"
+    );
+    crate::auction::assert_no_open_auction(contract, property_id);
+    require!(
+        instalment_count > 0 && instalment_count <= MAX_INSTALMENTS,
+        format!("Between 1 and {} instalments", MAX_INSTALMENTS)
+    );
+    require!(
+        interval_ns > 0,
+        "Instalment interval must be greater than zero"
+    );
+    require!(
+        bid.amount < price,
+        "The down payment must be less than the price — place an ordinary bid instead"
+    );
+
+    contract.instalment_plans.insert(
+        bid_id,
+        InstalmentPlan {
+            price,
+            down_payment: bid.amount,
+            instalment_count,
+            interval_ns,
+            paid_instalments: 0,
+            next_due_at: None,
+            status: InstalmentStatus::Pending,
+        },
+    );
+}
+
+pub(crate) fn is_instalment_bid(contract: &ShedaContract, bid_id: u64) -> bool {
+    contract.instalment_plans.contains_key(&bid_id)
+}
+
+pub(crate) fn assert_not_instalment(contract: &ShedaContract, bid_id: u64, message: &str) {
+    require!(!is_instalment_bid(contract, bid_id), message);
+}
+
+/// The NFT is with a buyer who still owes on it; it can't move and the
+/// property can't be offered again until the plan ends.
+pub(crate) fn assert_no_lien(contract: &ShedaContract, property_id: u64) {
+    require!(
+        !contract.property_liens.contains_key(&property_id),
+        "The property is under an instalment lien"
+    );
+}
+
+/// The down payment has been released to the seller: the buyer takes the NFT,
+/// the seller keeps the title, and the first instalment falls due.
+///
+/// Called from `release_escrow_callback` in place of the usual handover.
+pub(crate) fn take_possession(contract: &mut ShedaContract, property_id: u64, bid_id: u64) {
+    let bid = bid_on(contract, property_id, bid_id);
+
+    contract.tokens.internal_transfer(
+        &crate::fraction::nft_custodian(contract, property_id),
+        &bid.bidder,
+        &property_id.to_string(),
+        None,
+        None,
+    );
+    // A foreclosure hands the NFT back to the seller, not the contract.
+    contract.external_listings.remove(&property_id);
+    if let Some(property) = contract.properties.get_mut(&property_id) {
+        property.is_for_sale = false;
+    }
+
+    let now = env::block_timestamp();
+    if let Some(plan) = contract.instalment_plans.get_mut(&bid_id) {
+        plan.status = InstalmentStatus::Active;
+        plan.next_due_at = Some(checked_add_u64(now, plan.interval_ns, "instalment due"));
+    }
+    contract.property_liens.insert(property_id, bid_id);
+}
+
+/// Pay the next instalment with an `ft_on_transfer` `PayInstalment`, passed
+/// straight on to the seller. Returns what was sent beyond the instalment.
+///
+/// A late instalment is still accepted for as long as the seller hasn't
+/// foreclosed.
+pub(crate) fn pay_instalment(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    bid_id: u64,
+    sender_id: AccountId,
+    amount: u128,
+) -> u128 {
+    let bid = bid_on(contract, property_id, bid_id);
+    require!(
+        bid.bidder == sender_id,
+        "Only the buyer can pay an instalment"
+    );
+    require!(
+        bid.stablecoin_token == env::predecessor_account_id(),
+        "Instalments must be paid in the bid's stablecoin"
+    );
+
+    let plan = active_plan(contract, bid_id);
+    let instalment = plan.paid_instalments;
+    let due = plan.instalment_amount(instalment);
+    require!(
+        amount >= due,
+        format!("Instalment {} comes to {}", instalment + 1, due)
+    );
+
+    let seller_id = contract
+        .properties
+        .get(&property_id)
+        .expect("Property does not exist")
+        .owner_id
+        .clone();
+
+    let completed = {
+        let plan = contract
+            .instalment_plans
+            .get_mut(&bid_id)
+            .expect("Not an instalment bid");
+        plan.paid_instalments += 1;
+        plan.next_due_at = plan
+            .next_due_at
+            .map(|due_at| checked_add_u64(due_at, plan.interval_ns, "instalment due"));
+        if plan.paid_instalments == plan.instalment_count {
+            plan.status = InstalmentStatus::Completed;
+            plan.next_due_at = None;
+        }
+        plan.clone()
+    };
+
+    emit_event(
+        "InstalmentPaid",
+        InstalmentPaidEvent {
+            token_id: property_id,
+            bid_id,
+            buyer_id: bid.bidder.clone(),
+            instalment: instalment + 1,
+            amount: due,
+            remaining: completed.price - completed.amount_paid(),
+        },
+    );
+
+    let net = crate::fees::charge(
+        contract,
+        property_id,
+        &bid.action,
+        &bid.stablecoin_token,
+        &seller_id,
+        due,
+    );
+    let net = crate::royalty::charge(
+        contract,
+        property_id,
+        &bid.stablecoin_token,
+        &seller_id,
+        due,
+        net,
+    );
+    let payouts = crate::payout::owner_payouts(
+        contract,
+        property_id,
+        &bid.stablecoin_token,
+        seller_id.clone(),
+        net,
+    );
+    #[allow(unused_must_use)]
+    crate::payout::send_payouts(&bid.stablecoin_token, payouts);
+
+    if completed.status == InstalmentStatus::Completed {
+        // Paid off: the lien lifts and the title follows the NFT the buyer
+        // already holds.
+        contract.property_liens.remove(&property_id);
+        transfer_property_ownership(contract, property_id, &bid.bidder);
+
+        emit_event(
+            "InstalmentPlanCompleted",
+            InstalmentPlanCompletedEvent {
+                token_id: property_id,
+                bid_id,
+                buyer_id: bid.bidder,
+                seller_id,
+                total_paid: completed.price,
+            },
+        );
+    }
+
+    amount - due
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Seller takes the property back from a buyer who stopped paying.
+    ///
+    /// Open once an instalment is more than `instalment_grace_period_ns`
+    /// overdue. The NFT returns to the seller, the agreement is burned, the
+    /// property goes back on the market, and what was paid stays with the
+    /// seller.
+    #[payable]
+    pub fn foreclose_instalment_plan(&mut self, property_id: u64, bid_id: u64) {
+        assert_one_yocto();
+
+        let seller_id = self
+            .properties
+            .get(&property_id)
+            .expect("Property not found")
+            .owner_id
+            .clone();
+        assert_eq!(
+            seller_id,
+            env::predecessor_account_id(),
+            "Only the seller can foreclose"
+        );
+        let bid = bid_on(self, property_id, bid_id);
+        let plan = active_plan(self, bid_id);
+        let due_at = plan.next_due_at.expect("No instalment is due");
+        let default_at = checked_add_u64(
+            due_at,
+            self.instalment_grace_period_ns,
+            "instalment grace period",
+        );
+        require!(
+            env::block_timestamp() > default_at,
+            "The missed instalment is still within its grace period"
+        );
+
+        let token_id = property_id.to_string();
+        let holder = self
+            .tokens
+            .owner_by_id
+            .get(&token_id)
+            .expect("Property token not found");
+        self.tokens
+            .internal_transfer(&holder, &seller_id, &token_id, None, None);
+        self.property_liens.remove(&property_id);
+
+        if let Some(document_token_id) = bid.document_token_id.clone() {
+            if let Some(document_holder) = self.tokens.owner_by_id.get(&document_token_id) {
+                remove_token(self, document_token_id.clone(), &document_holder);
+                log!(
+                    "Burned agreement {} — instalment bid {} defaulted",
+                    document_token_id,
+                    bid_id
+                );
+            }
+        }
+        if let Some(bids) = self.bids.get_mut(&property_id) {
+            let _ = update_bid_in_list(bids, bid_id, |b| {
+                b.status = BidStatus::Cancelled;
+                b.updated_at = env::block_timestamp();
+                b.document_token_id = None;
+            });
+        }
+        if let Some(plan) = self.instalment_plans.get_mut(&bid_id) {
+            plan.status = InstalmentStatus::Defaulted;
+        }
+        if let Some(property) = self.properties.get_mut(&property_id) {
+            property.is_for_sale = true;
+        }
+
+        emit_event(
+            "InstalmentPlanDefaulted",
+            InstalmentPlanDefaultedEvent {
+                token_id: property_id,
+                bid_id,
+                buyer_id: bid.bidder,
+                seller_id,
+                paid_instalments: plan.paid_instalments,
+                amount_paid: plan.amount_paid(),
+            },
+        );
+    }
+
+    /// The schedule of an instalment bid, if `bid_id` is one.
+    pub fn get_instalment_plan(&self, bid_id: u64) -> Option<InstalmentPlanView> {
+        self.instalment_plans.get(&bid_id).map(|plan| plan.into())
+    }
+
+    /// The instalment bid holding a lien on the property, if any.
+    pub fn get_property_lien(&self, property_id: u64) -> Option<u64> {
+        self.property_liens.get(&property_id).copied()
+    }
+
+    pub fn get_instalment_grace_period(&self) -> u64 {
+        self.instalment_grace_period_ns
+    }
+}
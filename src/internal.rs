@@ -8,34 +8,72 @@ use crate::{
     events::{
         emit_event, BidApprovedEvent, BidCancelledByBuyerEvent, BidCancelledEvent,
         BidDisputeResolvedEvent, BidRefundedEvent, BidRejectedEvent, DealFinalizedEvent,
-        DisputeRaisedEvent, LeaseExpiredEvent, LeaseRenewedEvent, PropertyDeletedEvent,
-        PropertyDelistedEvent,
+        DisputeContestedEvent, DisputeRaisedEvent, LeaseExpiredEvent, LeaseRenewedEvent,
+        OwnerDisputeRaisedEvent, PropertyDeletedEvent, PropertyDelistedEvent,
     },
     ext::ft_contract,
     models::{Action, Bid, BidStatus, DisputeResolution},
     ShedaContract,
 };
 
-fn checked_add_u128(left: u128, right: u128, label: &str) -> u128 {
+/// Whether promise `index` of the calling callback's results failed.
+pub(crate) fn promise_failed(index: u64) -> bool {
+    // `promise_result_checked` needs a size limit the callbacks have no use
+    // for; they only look at success or failure.
+    #[allow(deprecated)]
+    let result = env::promise_result(index);
+    matches!(result, PromiseResult::Failed)
+}
+
+pub(crate) fn checked_add_u128(left: u128, right: u128, label: &str) -> u128 {
     left.checked_add(right)
         .unwrap_or_else(|| env::panic_str(&format!("Overflow in {}", label)))
 }
 
-fn checked_sub_u128(left: u128, right: u128, label: &str) -> u128 {
+pub(crate) fn checked_sub_u128(left: u128, right: u128, label: &str) -> u128 {
     left.checked_sub(right)
         .unwrap_or_else(|| env::panic_str(&format!("Underflow in {}", label)))
 }
 
-fn checked_add_u64(left: u64, right: u64, label: &str) -> u64 {
+pub(crate) fn checked_add_u64(left: u64, right: u64, label: &str) -> u64 {
     left.checked_add(right)
         .unwrap_or_else(|| env::panic_str(&format!("Overflow in {}", label)))
 }
 
-fn checked_mul_u64(left: u64, right: u64, label: &str) -> u64 {
+pub(crate) fn checked_mul_u64(left: u64, right: u64, label: &str) -> u64 {
     left.checked_mul(right)
         .unwrap_or_else(|| env::panic_str(&format!("Overflow in {}", label)))
 }
 
+/// Add to the escrow ledger for `token` — the `stable_coin_balances` entry
+/// every deposit and payout has to keep in step with the real FT balance.
+pub(crate) fn credit_escrow(
+    contract: &mut ShedaContract,
+    token: &AccountId,
+    amount: u128,
+    label: &str,
+) {
+    let current_balance = *contract.stable_coin_balances.get(token).unwrap_or(&0);
+    contract.stable_coin_balances.insert(
+        token.clone(),
+        checked_add_u128(current_balance, amount, label),
+    );
+}
+
+/// Take from the escrow ledger for `token`; see `credit_escrow`.
+pub(crate) fn debit_escrow(
+    contract: &mut ShedaContract,
+    token: &AccountId,
+    amount: u128,
+    label: &str,
+) {
+    let current_balance = *contract.stable_coin_balances.get(token).unwrap_or(&0);
+    contract.stable_coin_balances.insert(
+        token.clone(),
+        checked_sub_u128(current_balance, amount, label),
+    );
+}
+
 fn bid_lock_key(property_id: u64, bid_id: u64) -> String {
     format!("bid:{}:{}", property_id, bid_id)
 }
@@ -137,17 +175,25 @@ pub fn burn_nft(contract: &mut ShedaContract, token_id: String) {
         "Only owner can burn"
     );
 
+    remove_token(contract, token_id, &token.owner_id);
+}
+
+/// Remove a token and everything attached to it, without checking who asked.
+///
+/// `burn_nft` is the holder burning their own token; this is for the contract
+/// unwinding one of its agreements after the holder lost the right to it.
+pub(crate) fn remove_token(contract: &mut ShedaContract, token_id: String, owner_id: &AccountId) {
     // Remove token ownership and metadata
     contract.tokens.owner_by_id.remove(&token_id);
     if let Some(tokens_per_owner) = contract.tokens.tokens_per_owner.as_mut() {
-        let mut owner_tokens = tokens_per_owner.get(&token.owner_id).unwrap_or_else(|| {
+        let mut owner_tokens = tokens_per_owner.get(owner_id).unwrap_or_else(|| {
             env::panic_str("Unable to access tokens per owner in unguarded call.")
         });
         owner_tokens.remove(&token_id);
         if owner_tokens.is_empty() {
-            tokens_per_owner.remove(&token.owner_id);
+            tokens_per_owner.remove(owner_id);
         } else {
-            tokens_per_owner.insert(&token.owner_id.clone(), &owner_tokens);
+            tokens_per_owner.insert(owner_id, &owner_tokens);
         }
     }
     if let Some(token_metadata_by_id) = contract.tokens.token_metadata_by_id.as_mut() {
@@ -159,6 +205,50 @@ pub fn burn_nft(contract: &mut ShedaContract, token_id: String) {
 }
 
 pub fn internal_accept_bid(contract: &mut ShedaContract, property_id: u64, bid_id: u64) -> Promise {
+    let owner_id = contract
+        .properties
+        .get(&property_id)
+        .expect("Property does not exist")
+        .owner_id
+        .clone();
+
+    assert_eq!(
+        owner_id,
+        env::predecessor_account_id(),
+        "Only the property owner can accept bids"
+    );
+
+    crate::auction::assert_no_open_auction(contract, property_id);
+
+    execute_accept_bid(contract, property_id, bid_id)
+}
+
+/// The fast-path acceptance itself: pay the seller, then hand over the NFT in
+/// `accept_bid_callback`.
+///
+/// Split out of `internal_accept_bid` so a settled auction can go through the
+/// exact same path. Callers are responsible for deciding *who* may accept —
+/// this only checks the property and the bid.
+pub(crate) fn execute_accept_bid(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    bid_id: u64,
+) -> Promise {
+    crate::earnest::assert_not_earnest(
+        contract,
+        bid_id,
+        "An earnest-money bid can only be accepted with accept_bid_with_escrow",
+    );
+    crate::instalment::assert_not_instalment(
+        contract,
+        bid_id,
+        "An instalment bid can only be accepted with accept_bid_with_escrow",
+    );
+    crate::deposit::assert_deposit_lodged(contract, property_id, bid_id);
+    crate::booking::assert_no_upcoming_bookings(contract, property_id);
+    crate::building::assert_units_vacant(contract, property_id);
+    crate::building::assert_building_not_let(contract, property_id);
+    crate::fraction::assert_fraction_allows_bid(contract, property_id, bid_id);
     lock_bid(contract, property_id, bid_id);
     let (owner_id, has_active_lease) = {
         let property = contract
@@ -175,12 +265,6 @@ pub fn internal_accept_bid(contract: &mut ShedaContract, property_id: u64, bid_i
         )
     };
 
-    assert_eq!(
-        owner_id,
-        env::predecessor_account_id(),
-        "Only the property owner can accept bids"
-    );
-
     // finalize_accepted_bid() (Purchase and Lease alike) transfers the NFT
     // from property.owner_id to the bidder. While a lease is active the NFT
     // is actually held by the tenant, not the owner, so that transfer would
@@ -234,11 +318,19 @@ pub fn internal_accept_bid(contract: &mut ShedaContract, property_id: u64, bid_i
         },
     );
 
-    // Part 1: Transfer stablecoin from contract to property owner
-    let promise = ft_contract::ext(bid.stablecoin_token.clone())
-        .with_attached_deposit(NearToken::from_yoctonear(1))
-        .with_static_gas(Gas::from_tgas(30))
-        .ft_transfer(owner_id.clone(), U128(bid.amount));
+    // Part 1: Transfer stablecoin from contract to property owner. A damage
+    // deposit stays behind, held against the lease, and fees and royalties
+    // are held back until the transfer goes through.
+    let payment = bid.amount - crate::deposit::held_back(contract, bid_id);
+    let net = crate::fees::charge_bid(contract, &bid, payment);
+    let net = crate::royalty::hold_back(contract, &bid, payment, net);
+    let promise = crate::payout::pay_owner(
+        contract,
+        property_id,
+        &bid.stablecoin_token,
+        owner_id.clone(),
+        net,
+    );
 
     // Update stablecoin balance after payment to seller
     let current_balance = *contract
@@ -247,11 +339,12 @@ pub fn internal_accept_bid(contract: &mut ShedaContract, property_id: u64, bid_i
         .unwrap_or(&0);
     contract.stable_coin_balances.insert(
         bid.stablecoin_token.clone(),
-        checked_sub_u128(current_balance, bid.amount, "accept_bid balance"),
+        checked_sub_u128(current_balance, payment, "accept_bid balance"),
     );
 
     if contract.mock_transfers_enabled {
         unlock_bid(contract, property_id, bid_id);
+        crate::fees::settle_bid(contract, bid_id);
         finalize_accepted_bid(contract, property_id, bid_id);
         return Promise::new(env::current_account_id()).transfer(NearToken::from_yoctonear(0));
     }
@@ -271,10 +364,13 @@ pub fn accept_bid_callback(contract: &mut ShedaContract, property_id: u64, bid_i
     match env::promise_result(0) {
         PromiseResult::Successful(_) => {
             log!("ft_transfer successful, proceeding with NFT transfer and bid updates");
+            crate::fees::settle_bid(contract, bid_id);
             finalize_accepted_bid(contract, property_id, bid_id);
         }
         PromiseResult::Failed => {
             log!("ft_transfer failed, reverting. NFT and bid remain unchanged");
+            crate::fees::drop_bid(contract, bid_id);
+            crate::royalty::drop_pending(contract, bid_id);
 
             // Revert the stablecoin balance update
             let bid = {
@@ -286,9 +382,10 @@ pub fn accept_bid_callback(contract: &mut ShedaContract, property_id: u64, bid_i
                 .stable_coin_balances
                 .get(&bid.stablecoin_token)
                 .unwrap_or(&0);
+            let payment = bid.amount - crate::deposit::held_back(contract, bid_id);
             contract.stable_coin_balances.insert(
                 bid.stablecoin_token.clone(),
-                checked_add_u128(current_balance, bid.amount, "accept_bid revert"),
+                checked_add_u128(current_balance, payment, "accept_bid revert"),
             );
 
             if let Some(bids) = contract.bids.get_mut(&property_id) {
@@ -372,11 +469,16 @@ pub fn internal_reject_bid(contract: &mut ShedaContract, property_id: u64, bid_i
         "Only the property owner can reject bids"
     );
 
+    crate::auction::assert_not_leading_bid(contract, property_id, bid_id);
+
     assert_eq!(
         bid.property_id, property_id,
         "Bid is not for the specified property"
     );
 
+    // Co-bidders get back what they put in; the rest is the bidder's.
+    let bid = crate::joint::refund_co_bidders(contract, property_id, bid_id);
+
     // Refund stablecoin to bidder
     let refund_promise = ft_contract::ext(bid.stablecoin_token.clone())
         .with_attached_deposit(NearToken::from_yoctonear(1))
@@ -441,6 +543,8 @@ pub fn internal_cancel_bid(contract: &mut ShedaContract, property_id: u64, bid_i
         "Only the bidder can cancel their bid"
     );
 
+    crate::auction::assert_not_leading_bid(contract, property_id, bid_id);
+
     assert_eq!(
         bid.property_id, property_id,
         "Bid is not for the specified property"
@@ -463,6 +567,9 @@ pub fn internal_cancel_bid(contract: &mut ShedaContract, property_id: u64, bid_i
         }
     }
 
+    // Co-bidders get back what they put in; the rest is the bidder's.
+    let bid = crate::joint::refund_co_bidders(contract, property_id, bid_id);
+
     // Refund stablecoin to bidder
     let refund_promise = ft_contract::ext(bid.stablecoin_token.clone())
         .with_attached_deposit(NearToken::from_yoctonear(1))
@@ -609,6 +716,9 @@ fn internal_buyer_cancel_staged(
         }
     };
 
+    // Co-bidders get back what they put in; the rest is the bidder's.
+    let bid = crate::joint::refund_co_bidders(contract, property_id, bid_id);
+
     let refund_promise = ft_contract::ext(bid.stablecoin_token.clone())
         .with_attached_deposit(NearToken::from_yoctonear(1))
         .with_static_gas(Gas::from_tgas(30))
@@ -752,6 +862,34 @@ pub fn internal_accept_bid_with_escrow(
     contract: &mut ShedaContract,
     property_id: u64,
     bid_id: u64,
+) -> bool {
+    let owner_id = contract
+        .properties
+        .get(&property_id)
+        .expect("Property does not exist")
+        .owner_id
+        .clone();
+
+    assert_eq!(
+        owner_id,
+        env::predecessor_account_id(),
+        "Only the property owner can accept bids"
+    );
+
+    crate::auction::assert_no_open_auction(contract, property_id);
+
+    execute_accept_bid_with_escrow(contract, property_id, bid_id)
+}
+
+/// The escrow-path acceptance itself, without the caller checks.
+///
+/// Split out of `internal_accept_bid_with_escrow` for the same reason as
+/// `execute_accept_bid`: a sealed-bid auction hands its winner to this exact
+/// flow once the reveal window closes.
+pub(crate) fn execute_accept_bid_with_escrow(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    bid_id: u64,
 ) -> bool {
     let (owner_id, lease_duration_months, has_active_lease) = {
         let property = contract
@@ -769,18 +907,17 @@ pub fn internal_accept_bid_with_escrow(
         )
     };
 
-    assert_eq!(
-        owner_id,
-        env::predecessor_account_id(),
-        "Only the property owner can accept bids"
-    );
-
     // See internal_accept_bid for why this has to be rejected here rather
     // than left to fail later at NFT-transfer time.
     require!(
         !has_active_lease,
         "Cannot accept a bid while the property has an active lease"
     );
+    crate::deposit::assert_deposit_lodged(contract, property_id, bid_id);
+    crate::booking::assert_no_upcoming_bookings(contract, property_id);
+    crate::building::assert_units_vacant(contract, property_id);
+    crate::building::assert_building_not_let(contract, property_id);
+    crate::fraction::assert_fraction_allows_bid(contract, property_id, bid_id);
 
     let now = env::block_timestamp();
     let bid_snapshot = {
@@ -826,6 +963,9 @@ pub fn internal_accept_bid_with_escrow(
 
     lock_bid(contract, property_id, bid_id);
 
+    // Co-bidders on the losing bids are paid back their part first.
+    crate::joint::refund_pending_co_bidders(contract, property_id, Some(bid_id));
+
     if let Some(bids) = contract.bids.get_mut(&property_id) {
         for other_bid in bids.iter_mut() {
             if other_bid.id == bid_id || other_bid.status != BidStatus::Pending {
@@ -904,6 +1044,8 @@ pub fn internal_accept_bid_with_escrow(
         contract.leases.insert(lease.id, lease);
         contract.lease_counter = checked_add_u64(contract.lease_counter, 1, "lease_counter");
         contract.properties.insert(property_id, updated_property);
+        crate::rent::start_schedule(contract, property_id, lease_id);
+        crate::joint::record_co_tenants(contract, &bid, lease_id);
 
         let mut tenant_leases = contract
             .lease_per_tenant
@@ -955,13 +1097,16 @@ fn finalize_accepted_bid(contract: &mut ShedaContract, property_id: u64, bid_id:
 
     // Transfer NFT to bidder
     contract.tokens.internal_transfer(
-        &property.owner_id,
+        &crate::fraction::nft_custodian(contract, property_id),
         &bid.bidder,
         &property_id.to_string(),
         None,
         None,
     );
 
+    // Co-bidders on the losing bids are paid back their part first.
+    crate::joint::refund_pending_co_bidders(contract, property_id, Some(bid_id));
+
     if let Some(bids) = contract.bids.get_mut(&property_id) {
         for other_bid in bids.iter_mut() {
             if other_bid.id == bid_id {
@@ -1030,6 +1175,8 @@ fn finalize_accepted_bid(contract: &mut ShedaContract, property_id: u64, bid_id:
             );
 
             transfer_property_ownership(contract, property_id, &bid.bidder);
+            crate::joint::record_co_owners(contract, &bid);
+            crate::royalty::pay(contract, &bid);
         }
         Action::Lease => {
             let mut updated_property = property.clone();
@@ -1058,6 +1205,9 @@ fn finalize_accepted_bid(contract: &mut ShedaContract, property_id: u64, bid_id:
             contract.leases.insert(lease.id, lease);
             contract.lease_counter = checked_add_u64(contract.lease_counter, 1, "lease_counter");
             contract.properties.insert(property_id, updated_property);
+            crate::rent::start_schedule(contract, property_id, lease_id);
+            crate::joint::record_co_tenants(contract, &bid, lease_id);
+            crate::deposit::hold_for_lease(contract, property_id, bid_id, lease_id);
 
             let mut tenant_leases = contract
                 .lease_per_tenant
@@ -1109,6 +1259,13 @@ pub fn internal_accept_lease_renewal(
     property_id: u64,
     bid_id: u64,
 ) -> Promise {
+    // The tenant's deposit is already held against the lease being renewed.
+    crate::deposit::assert_no_deposit(
+        contract,
+        bid_id,
+        "A renewal bid can't lodge a damage deposit",
+    );
+    crate::fraction::assert_fraction_allows_bid(contract, property_id, bid_id);
     lock_bid(contract, property_id, bid_id);
 
     let (owner_id, lease_duration_months, current_lease) = {
@@ -1130,6 +1287,7 @@ pub fn internal_accept_lease_renewal(
     );
 
     let lease = current_lease.expect("Property has no active lease to renew");
+    crate::termination::assert_no_notice(contract, lease.id);
     let duration_months =
         lease_duration_months.expect("Property is not configured with a lease duration");
 
@@ -1190,10 +1348,14 @@ pub fn internal_accept_lease_renewal(
         },
     );
 
-    let promise = ft_contract::ext(bid.stablecoin_token.clone())
-        .with_attached_deposit(NearToken::from_yoctonear(1))
-        .with_static_gas(Gas::from_tgas(30))
-        .ft_transfer(owner_id.clone(), U128(bid.amount));
+    let net = crate::fees::charge_bid(contract, &bid, bid.amount);
+    let promise = crate::payout::pay_owner(
+        contract,
+        property_id,
+        &bid.stablecoin_token,
+        owner_id.clone(),
+        net,
+    );
 
     let current_balance = *contract
         .stable_coin_balances
@@ -1220,6 +1382,7 @@ pub fn accept_lease_renewal_callback(
     unlock_bid(contract, property_id, bid_id);
     match env::promise_result(0) {
         PromiseResult::Successful(_) => {
+            crate::fees::settle_bid(contract, bid_id);
             let bid = {
                 let bids = contract.bids.get(&property_id).expect("Bid does not exist");
                 get_bid_from_list(bids, bid_id)
@@ -1301,8 +1464,10 @@ pub fn accept_lease_renewal_callback(
                     new_end_time,
                 },
             );
+            crate::renewal::on_renewed(contract, lease_id, bid_id, bid.amount);
         }
         PromiseResult::Failed => {
+            crate::fees::drop_bid(contract, bid_id);
             let bid = {
                 let bids: &Vec<Bid> = contract.bids.get(&property_id).expect("Bid does not exist");
                 get_bid_from_list(bids, bid_id)
@@ -1498,6 +1663,10 @@ pub fn internal_confirm_document_receipt(
         env::panic_str("Bid does not exist");
     }
 
+    // An earnest-money buyer has confirmed what they're buying; the rest of
+    // the price is due from here.
+    crate::earnest::start_balance_clock(contract, bid_id);
+
     true
 }
 
@@ -1527,6 +1696,8 @@ pub fn internal_release_escrow(
         env::panic_str("Bid is not in a document confirmed state");
     }
 
+    crate::earnest::assert_balance_paid(contract, bid_id);
+
     if let Some(unlock_at) = bid.escrow_release_after {
         require!(
             env::block_timestamp() >= unlock_at,
@@ -1534,10 +1705,13 @@ pub fn internal_release_escrow(
         );
     }
 
-    let promise = ft_contract::ext(bid.stablecoin_token.clone())
-        .with_attached_deposit(NearToken::from_yoctonear(1))
-        .with_static_gas(Gas::from_tgas(30))
-        .ft_transfer(property.owner_id.clone(), U128(bid.amount));
+    // A damage deposit stays behind, held against the lease.
+    let payment = bid.amount - crate::deposit::held_back(contract, bid_id);
+    let owner_id = property.owner_id.clone();
+    let net = crate::fees::charge_bid(contract, &bid, payment);
+    let net = crate::royalty::hold_back(contract, &bid, payment, net);
+    let promise =
+        crate::payout::pay_owner(contract, property_id, &bid.stablecoin_token, owner_id, net);
 
     let current_balance = *contract
         .stable_coin_balances
@@ -1545,7 +1719,7 @@ pub fn internal_release_escrow(
         .unwrap_or(&0);
     contract.stable_coin_balances.insert(
         bid.stablecoin_token.clone(),
-        checked_sub_u128(current_balance, bid.amount, "release_escrow"),
+        checked_sub_u128(current_balance, payment, "release_escrow"),
     );
 
     promise.then(
@@ -1559,11 +1733,11 @@ pub fn release_escrow_callback(contract: &mut ShedaContract, property_id: u64, b
     unlock_bid(contract, property_id, bid_id);
     match env::promise_result(0) {
         PromiseResult::Successful(_) => {
-            let property = contract
-                .properties
-                .get(&property_id)
-                .expect("Property does not exist")
-                .clone();
+            crate::fees::settle_bid(contract, bid_id);
+            require!(
+                contract.properties.contains_key(&property_id),
+                "Property does not exist"
+            );
 
             let bid = {
                 let bids = contract.bids.get(&property_id).expect("Bid does not exist");
@@ -1579,9 +1753,15 @@ pub fn release_escrow_callback(contract: &mut ShedaContract, property_id: u64, b
             }
 
             match bid.action {
+                // Only the down payment was released; title waits for the
+                // last instalment.
+                Action::Purchase if crate::instalment::is_instalment_bid(contract, bid_id) => {
+                    crate::instalment::take_possession(contract, property_id, bid_id);
+                    crate::royalty::pay(contract, &bid);
+                }
                 Action::Purchase => {
                     contract.tokens.internal_transfer(
-                        &property.owner_id,
+                        &crate::fraction::nft_custodian(contract, property_id),
                         &bid.bidder,
                         &property_id.to_string(),
                         None,
@@ -1594,6 +1774,8 @@ pub fn release_escrow_callback(contract: &mut ShedaContract, property_id: u64, b
                     // as owner, which leaves the property unusable by either
                     // party.
                     transfer_property_ownership(contract, property_id, &bid.bidder);
+                    crate::joint::record_co_owners(contract, &bid);
+                    crate::royalty::pay(contract, &bid);
                 }
                 Action::Lease => {
                     // The Lease record was already created in
@@ -1602,18 +1784,24 @@ pub fn release_escrow_callback(contract: &mut ShedaContract, property_id: u64, b
                     // Creating a second Lease here would orphan that first
                     // record (it stays active/untracked in `leases`) and would
                     // never be indexed in `lease_per_tenant`, so all we do at
-                    // this stage is hand over the NFT.
+                    // this stage is hand over the NFT, and hold the damage
+                    // deposit against that lease now that it's paid for.
                     contract.tokens.internal_transfer(
-                        &property.owner_id,
+                        &crate::fraction::nft_custodian(contract, property_id),
                         &bid.bidder,
                         &property_id.to_string(),
                         None,
                         None,
                     );
+                    if let Some(lease_id) = bid.lease_id {
+                        crate::deposit::hold_for_lease(contract, property_id, bid_id, lease_id);
+                    }
                 }
             }
         }
         PromiseResult::Failed => {
+            crate::fees::drop_bid(contract, bid_id);
+            crate::royalty::drop_pending(contract, bid_id);
             let bid = {
                 let bids: &Vec<Bid> = contract.bids.get(&property_id).expect("Bid does not exist");
                 get_bid_from_list(bids, bid_id)
@@ -1623,9 +1811,10 @@ pub fn release_escrow_callback(contract: &mut ShedaContract, property_id: u64, b
                 .stable_coin_balances
                 .get(&bid.stablecoin_token)
                 .unwrap_or(&0);
+            let payment = bid.amount - crate::deposit::held_back(contract, bid_id);
             contract.stable_coin_balances.insert(
                 bid.stablecoin_token.clone(),
-                checked_add_u128(current_balance, bid.amount, "release_escrow revert"),
+                checked_add_u128(current_balance, payment, "release_escrow revert"),
             );
 
             if let Some(bids) = contract.bids.get_mut(&property_id) {
@@ -1643,23 +1832,30 @@ pub fn release_escrow_callback(contract: &mut ShedaContract, property_id: u64, b
     }
 }
 
+/// `caller` raises a dispute on an accepted bid, having posted
+/// `bond_amount` if a bond is required.
 pub fn internal_raise_bid_dispute(
     contract: &mut ShedaContract,
+    caller: AccountId,
     property_id: u64,
     bid_id: u64,
     reason: String,
+    bond_amount: u128,
 ) -> bool {
     let property = contract
         .properties
         .get(&property_id)
         .expect("Property does not exist");
+    let mut respondent = property.owner_id.clone();
 
     if let Some(bids) = contract.bids.get_mut(&property_id) {
         let _ = update_bid_in_list(bids, bid_id, |bid| {
-            let caller = env::predecessor_account_id();
             if caller != bid.bidder && caller != property.owner_id {
                 env::panic_str("Only buyer or seller can raise dispute");
             }
+            if caller == property.owner_id {
+                respondent = bid.bidder.clone();
+            }
 
             match bid.status {
                 BidStatus::Accepted | BidStatus::DocsReleased | BidStatus::DocsConfirmed => {}
@@ -1673,13 +1869,22 @@ pub fn internal_raise_bid_dispute(
     } else {
         env::panic_str("Bid does not exist");
     }
+    crate::evidence::open_case(
+        contract,
+        crate::models::DisputeCase::Bid {
+            property_id,
+            bid_id,
+        },
+        caller.clone(),
+        respondent,
+    );
 
     emit_event(
         "DisputeRaised",
         DisputeRaisedEvent {
             token_id: property_id,
-            tenant_id: env::predecessor_account_id(),
-            bond_amount: 0,
+            tenant_id: caller,
+            bond_amount,
         },
     );
 
@@ -1695,6 +1900,7 @@ pub fn internal_complete_transaction(
         .properties
         .get(&property_id)
         .expect("Property does not exist");
+    let lien_outstanding = contract.property_liens.get(&property_id) == Some(&bid_id);
 
     if let Some(bids) = contract.bids.get_mut(&property_id) {
         let _ = update_bid_in_list(bids, bid_id, |bid| {
@@ -1707,6 +1913,10 @@ pub fn internal_complete_transaction(
                 env::panic_str("Bid is not in a payment released state");
             }
 
+            if lien_outstanding {
+                env::panic_str("Instalments are still owed on this purchase");
+            }
+
             bid.status = BidStatus::Completed;
             bid.updated_at = env::block_timestamp();
         });
@@ -1790,6 +2000,9 @@ pub fn internal_refund_escrow_timeout(
         );
     }
 
+    // Co-bidders get back what they put in; the rest is the bidder's.
+    let bid = crate::joint::refund_co_bidders(contract, property_id, bid_id);
+
     let promise = ft_contract::ext(bid.stablecoin_token.clone())
         .with_attached_deposit(NearToken::from_yoctonear(1))
         .with_static_gas(Gas::from_tgas(30))
@@ -1887,6 +2100,7 @@ pub fn internal_delist_property(contract: &mut ShedaContract, property_id: u64)
     // escrow against it, leaving their bid unresolvable through the normal
     // flow. Same guard, same reasoning.
     assert_no_blocking_bids(contract, property_id, "delisted");
+    crate::auction::assert_no_open_auction(contract, property_id);
 
     // Set the property as not for sale
     property.is_for_sale = false;
@@ -1991,6 +2205,13 @@ pub fn transfer_property_ownership(
         return;
     }
 
+    // Whoever co-owned it with the previous owner doesn't any more, and the
+    // previous owner's payout split and broker go with them.
+    contract.property_co_owners.remove(&property_id);
+    contract.payout_splits.remove(&property_id);
+    contract.listing_brokers.remove(&property_id);
+    contract.external_listings.remove(&property_id);
+
     // Drop it from the previous owner's index, removing the key outright when
     // that was their last property (same cleanup internal_delete_property does).
     let mut previous_owner_properties = contract
@@ -2048,11 +2269,21 @@ pub fn internal_delete_property(contract: &mut ShedaContract, property_id: u64)
     assert!(property.sold.is_none(), "Cannot delete a sold property");
 
     assert_no_blocking_bids(contract, property_id, "deleted");
+    crate::auction::assert_no_open_auction(contract, property_id);
+    crate::building::on_property_deleted(contract, property_id);
+    require!(
+        crate::fraction::nft_custodian(contract, property_id) == property.owner_id,
+        "Cannot delete a property whose NFT the contract holds"
+    );
 
     burn_nft(contract, property_id.to_string());
 
     // Remove the property from storage
     contract.properties.remove(&property_id);
+    contract.payout_splits.remove(&property_id);
+    contract.listing_brokers.remove(&property_id);
+    contract.external_listings.remove(&property_id);
+    contract.property_royalties.remove(&property_id);
 
     let mut owner_properties = contract
         .property_per_owner
@@ -2077,28 +2308,35 @@ pub fn internal_delete_property(contract: &mut ShedaContract, property_id: u64)
     );
 }
 
-pub fn internal_raise_dispute(contract: &mut ShedaContract, lease_id: u64, reason: String) {
+/// `caller` raises a dispute on their lease, having posted `bond_amount` if
+/// a bond is required.
+pub fn internal_raise_dispute(
+    contract: &mut ShedaContract,
+    caller: AccountId,
+    lease_id: u64,
+    reason: String,
+    bond_amount: u128,
+) {
     let mut lease = contract
         .leases
         .get(&lease_id)
         .cloned()
         .expect("Lease not found");
 
-    let caller = env::predecessor_account_id();
     assert_eq!(
         lease.tenant_id, caller,
-        "Only the tenant can raise a dispute"
+        "Only the tenant can raise a dispute; owners use raise_owner_lease_dispute"
     );
 
-    assert_eq!(
-        lease.dispute_status,
-        crate::models::DisputeStatus::None,
+    // A dispute settled earlier in the tenancy doesn't stop a new one.
+    require!(
+        !lease.has_open_dispute(),
         "Dispute already raised for this lease"
     );
 
     lease.dispute_status = crate::models::DisputeStatus::Raised;
     lease.dispute = Some(crate::models::DisputeInfo {
-        raised_by: caller,
+        raised_by: caller.clone(),
         raised_at: env::block_timestamp(),
         reason,
         votes_for_tenant: 0,
@@ -2112,68 +2350,246 @@ pub fn internal_raise_dispute(contract: &mut ShedaContract, lease_id: u64, reaso
 
     let property_id = lease.property_id;
     contract.leases.insert(lease_id, lease);
+    crate::evidence::open_lease_case(contract, lease_id, caller.clone());
 
     emit_event(
         "DisputeRaised",
         DisputeRaisedEvent {
             token_id: property_id,
-            tenant_id: env::predecessor_account_id(),
-            bond_amount: 0,
+            tenant_id: caller,
+            bond_amount,
         },
     );
 }
 
-pub fn internal_expire_lease(contract: &mut ShedaContract, lease_id: u64) {
+/// The landlord raises a dispute on a lease — damage, unpaid rent — claiming
+/// `claim`, which may be nothing, having posted `bond_amount` if a bond is
+/// required. An upheld claim is paid from the damage deposit as far as it
+/// goes; rent arrears stay owed on the rent schedule. The tenant has the dispute response period to contest it, which
+/// takes it to the admins; if they stay silent the claim is paid to the owner.
+pub fn internal_raise_owner_dispute(
+    contract: &mut ShedaContract,
+    caller: AccountId,
+    lease_id: u64,
+    reason: String,
+    claim: u128,
+    bond_amount: u128,
+) {
     let mut lease = contract
         .leases
         .get(&lease_id)
         .cloned()
         .expect("Lease not found");
 
-    let current_time = env::block_timestamp();
+    assert_eq!(
+        crate::sublease::landlord_of(contract, &lease),
+        caller,
+        "Only the landlord can raise an owner dispute"
+    );
+    // A dispute settled earlier in the tenancy doesn't stop this one.
+    require!(
+        !lease.has_open_dispute(),
+        "A dispute is already open on this lease"
+    );
+    // A dispute needn't claim anything. A claim is backed by the damage
+    // deposit still held and by rent in arrears; only the deposit part can be
+    // paid out, since the lease payment is already the owner's.
+    let backing = checked_add_u128(
+        crate::deposit::claimable(contract, lease_id),
+        crate::rent::arrears(contract, lease_id),
+        "owner claim",
+    );
+    require!(
+        claim <= backing,
+        "Can't claim more than the damage deposit held and the rent in arrears"
+    );
 
-    // Check if lease has expired
-    require!(lease.end_time <= current_time, "Lease has not expired yet");
+    lease.dispute_status = crate::models::DisputeStatus::PendingTenantResponse;
+    lease.dispute = Some(crate::models::DisputeInfo {
+        raised_by: caller.clone(),
+        raised_at: env::block_timestamp(),
+        reason,
+        votes_for_tenant: 0,
+        votes_for_owner: 0,
+        oracle_result: None,
+        oracle_request_id: None,
+        oracle_updated_at: None,
+        resolved_by: None,
+        resolved_at: None,
+    });
+    let property_id = lease.property_id;
+    let tenant_id = lease.tenant_id.clone();
+    contract.leases.insert(lease_id, lease);
+    contract.lease_dispute_claims.insert(lease_id, claim);
 
-    require!(lease.active, "Lease is already inactive");
+    let case = crate::models::DisputeCase::Lease { lease_id };
+    crate::evidence::open_case(contract, case.clone(), caller.clone(), tenant_id);
+    let response_deadline = contract
+        .dispute_case_files
+        .get(&case)
+        .expect("Case file not found")
+        .response_deadline;
 
-    // Mark lease as inactive
-    lease.active = false;
-    contract.leases.insert(lease_id, lease.clone());
+    emit_event(
+        "OwnerDisputeRaised",
+        OwnerDisputeRaisedEvent {
+            token_id: property_id,
+            lease_id,
+            owner_id: caller,
+            claim,
+            response_deadline,
+            bond_amount,
+        },
+    );
+}
 
-    log!("Lease {} has ended and is now inactive", lease_id);
+/// When the tenant's time to answer an owner's dispute runs out.
+fn tenant_response_deadline(contract: &ShedaContract, lease_id: u64) -> u64 {
+    contract
+        .dispute_case_files
+        .get(&crate::models::DisputeCase::Lease { lease_id })
+        .expect("Case file not found")
+        .response_deadline
+}
 
-    // Transfer NFT back to owner
-    let property = contract
-        .properties
-        .get(&lease.property_id)
-        .expect("Property not found");
+fn pending_owner_dispute(contract: &ShedaContract, lease_id: u64) -> crate::models::Lease {
+    let lease = contract
+        .leases
+        .get(&lease_id)
+        .cloned()
+        .expect("Lease not found");
+    require!(
+        lease.dispute_status == crate::models::DisputeStatus::PendingTenantResponse,
+        "No owner dispute is waiting on the tenant"
+    );
+    lease
+}
 
-    contract.tokens.internal_transfer(
-        &lease.tenant_id,
-        &property.owner_id,
-        &lease.property_id.to_string(),
-        None,
-        None,
+/// The tenant contests an owner's dispute within the response period, and
+/// it goes to the admins like any other.
+pub fn internal_contest_owner_dispute(contract: &mut ShedaContract, lease_id: u64) {
+    let mut lease = pending_owner_dispute(contract, lease_id);
+    let caller = env::predecessor_account_id();
+    assert_eq!(
+        lease.tenant_id, caller,
+        "Only the tenant can contest the dispute"
+    );
+    require!(
+        env::block_timestamp() <= tenant_response_deadline(contract, lease_id),
+        "The response period has ended"
     );
 
-    // Update property to remove active lease
-    let mut updated_property = property.clone();
-    updated_property.active_lease = None;
+    lease.dispute_status = crate::models::DisputeStatus::Raised;
+    let property_id = lease.property_id;
+    contract.leases.insert(lease_id, lease);
+
+    emit_event(
+        "DisputeContested",
+        DisputeContestedEvent {
+            token_id: property_id,
+            lease_id,
+            tenant_id: caller,
+        },
+    );
+}
+
+/// The tenant let the response period pass without contesting an owner's
+/// dispute: anyone can settle it, and the claim is paid to the owner.
+pub fn internal_settle_uncontested_dispute(contract: &mut ShedaContract, lease_id: u64) -> U128 {
+    let mut lease = pending_owner_dispute(contract, lease_id);
+    require!(
+        env::block_timestamp() > tenant_response_deadline(contract, lease_id),
+        "The tenant can still contest the dispute"
+    );
+    let claim = *contract
+        .lease_dispute_claims
+        .get(&lease_id)
+        .expect("Dispute claim not found");
+
+    // Settled through the same payout as an admin ruling for the owner.
+    lease.dispute_status = crate::models::DisputeStatus::Raised;
+    contract.leases.insert(lease_id, lease);
     contract
-        .properties
-        .insert(lease.property_id, updated_property);
+        .resolve_dispute_payout(lease_id, crate::models::DisputeWinner::Owner, U128(claim))
+        .unwrap_or_else(|error| env::panic_str(error.as_ref()));
+    log!(
+        "Uncontested dispute on lease {} settled for the owner",
+        lease_id
+    );
+    U128(claim)
+}
+
+pub fn internal_expire_lease(contract: &mut ShedaContract, lease_id: u64) {
+    let lease = contract
+        .leases
+        .get(&lease_id)
+        .cloned()
+        .expect("Lease not found");
+
+    let current_time = env::block_timestamp();
+
+    // Check if lease has expired
+    require!(lease.end_time <= current_time, "Lease has not expired yet");
+
+    require!(lease.active, "Lease is already inactive");
+
+    let escrow_returned = close_lease(contract, lease_id);
+
+    log!("Lease {} has ended and is now inactive", lease_id);
 
     emit_event(
         "LeaseExpired",
         LeaseExpiredEvent {
             token_id: lease.property_id,
             tenant_id: lease.tenant_id,
-            escrow_returned: 0,
+            escrow_returned,
         },
     );
 }
 
+/// End an active lease: mark it inactive, end any sublease under it, hand the
+/// NFT back and free the property. Returns the damage deposit refunded on the
+/// spot.
+///
+/// Shared by expiry and early termination; callers check the timing.
+pub(crate) fn close_lease(contract: &mut ShedaContract, lease_id: u64) -> u128 {
+    let mut lease = contract
+        .leases
+        .get(&lease_id)
+        .cloned()
+        .expect("Lease not found");
+
+    // A sublease let from this one ends with it.
+    crate::sublease::close_subleases(contract, lease_id);
+
+    // Mark lease as inactive
+    lease.active = false;
+    contract.leases.insert(lease_id, lease.clone());
+
+    // Hand the NFT back: to the primary tenant when a sublease ends, to the
+    // owner otherwise. It may be with a subtenant rather than the tenant.
+    let token_id = lease.property_id.to_string();
+    let return_to = crate::sublease::return_nft_to(contract, &lease);
+    let holder = contract.tokens.owner_by_id.get(&token_id);
+    if let Some(holder) = holder.filter(|holder| *holder != return_to) {
+        contract
+            .tokens
+            .internal_transfer(&holder, &return_to, &token_id, None, None);
+    }
+
+    // Update property to remove active lease
+    if crate::sublease::parent_of(contract, lease_id).is_none() {
+        if let Some(property) = contract.properties.get_mut(&lease.property_id) {
+            property.active_lease = None;
+        }
+    }
+
+    // The damage deposit is normally settled later, once the owner has had
+    // the chance to file deductions; it only comes back here when there's no
+    // window for them.
+    crate::deposit::on_lease_expired(contract, lease_id)
+}
+
 /// Settle a bid stuck in `Disputed`.
 ///
 /// `internal_raise_bid_dispute` freezes a bid, and nothing else in the
@@ -2211,14 +2627,36 @@ pub fn internal_admin_resolve_bid_dispute(
             bid_id, bid.status
         )
     );
+    crate::jury::assert_no_panel(
+        contract,
+        &crate::models::DisputeCase::Bid {
+            property_id,
+            bid_id,
+        },
+    );
+
+    let winner_id = match resolution {
+        DisputeResolution::BuyerWins => Some(&bid.bidder),
+        DisputeResolution::SellerWins => Some(&property.owner_id),
+        DisputeResolution::Split => None,
+    };
+    crate::bond::release(
+        contract,
+        &crate::models::DisputeCase::Bid {
+            property_id,
+            bid_id,
+        },
+        winner_id,
+    );
 
     // The buyer keeps the odd unit on a split: it is their money sitting in
     // the contract, so rounding should not quietly move in the seller's favour.
     let (buyer_refund, seller_payout) = match resolution {
         DisputeResolution::BuyerWins => (bid.amount, 0u128),
         DisputeResolution::SellerWins => (0u128, 0u128),
+        // A damage deposit isn't the seller's to share; it all goes back.
         DisputeResolution::Split => {
-            let seller_half = bid.amount / 2;
+            let seller_half = (bid.amount - crate::deposit::held_back(contract, bid_id)) / 2;
             (bid.amount.saturating_sub(seller_half), seller_half)
         }
     };
@@ -2268,19 +2706,15 @@ pub fn internal_admin_resolve_bid_dispute(
         checked_sub_u128(current_balance, total_out, "dispute settlement"),
     );
 
-    let mut promise = ft_contract::ext(bid.stablecoin_token.clone())
-        .with_attached_deposit(NearToken::from_yoctonear(1))
-        .with_static_gas(Gas::from_tgas(30))
-        .ft_transfer(bid.bidder.clone(), U128(buyer_refund));
-
-    if seller_payout > 0 {
-        promise = promise.then(
-            ft_contract::ext(bid.stablecoin_token.clone())
-                .with_attached_deposit(NearToken::from_yoctonear(1))
-                .with_static_gas(Gas::from_tgas(30))
-                .ft_transfer(property.owner_id.clone(), U128(seller_payout)),
-        );
-    }
+    let mut payouts = vec![(bid.bidder.clone(), buyer_refund)];
+    payouts.extend(crate::payout::owner_payouts(
+        contract,
+        property_id,
+        &bid.stablecoin_token,
+        property.owner_id.clone(),
+        seller_payout,
+    ));
+    let promise = crate::payout::send_payouts(&bid.stablecoin_token, payouts);
 
     if let Some(bids) = contract.bids.get_mut(&property_id) {
         let _ = update_bid_in_list(bids, bid_id, |b| {
@@ -2314,5 +2748,5 @@ pub fn internal_admin_resolve_bid_dispute(
         },
     );
 
-    Some(promise)
+    promise
 }
@@ -0,0 +1,444 @@
+use near_sdk::{
+    assert_one_yocto, env, json_types::U128, log, near_bindgen, require, AccountId, Gas, NearToken,
+};
+
+use crate::{
+    events::{emit_event, BidCoFundedEvent, CoBiddersRefundedEvent},
+    ext::ft_contract,
+    internal::{
+        checked_add_u128, checked_sub_u128, credit_escrow, debit_escrow, update_bid_in_list,
+    },
+    models::{Bid, BidStatus, CoParty, JointBid},
+    views::{CoPartyView, JointBidView},
+    ShedaContract, ShedaContractExt,
+};
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+fn bid_on(contract: &ShedaContract, property_id: u64, bid_id: u64) -> Bid {
+    contract
+        .bids
+        .get(&property_id)
+        .and_then(|bids| bids.iter().find(|b| b.id == bid_id).cloned())
+        .expect("Bid not found for the property")
+}
+
+/// What the co-bidders still have in the bid.
+fn co_funded(joint: &JointBid) -> u128 {
+    joint
+        .co_bidders
+        .iter()
+        .filter(|party| !party.refunded)
+        .map(|party| party.contributed)
+        .sum()
+}
+
+/// The bidder's own part of `bid`, as opposed to what co-bidders put in.
+pub(crate) fn own_contribution(contract: &ShedaContract, bid: &Bid) -> u128 {
+    let co_funded = contract.co_bidders.get(&bid.id).map(co_funded).unwrap_or(0);
+    bid.amount.saturating_sub(co_funded)
+}
+
+/// Money handed back to the bidder alone must come out of their own part.
+pub(crate) fn assert_within_own_contribution(contract: &ShedaContract, bid: &Bid, refund: u128) {
+    require!(
+        refund <= own_contribution(contract, bid),
+        "That would hand back money your co-bidders put in"
+    );
+}
+
+/// Everyone with money in `bid`, the bidder first, each with their share of
+/// it. `None` when nobody else funded it.
+fn parties_with_shares(contract: &ShedaContract, bid: &Bid) -> Option<Vec<CoParty>> {
+    let joint = contract.co_bidders.get(&bid.id)?;
+    let funded: Vec<&CoParty> = joint
+        .co_bidders
+        .iter()
+        .filter(|party| !party.refunded && party.contributed > 0)
+        .collect();
+    if funded.is_empty() || bid.amount == 0 {
+        return None;
+    }
+
+    let mut parties: Vec<CoParty> = funded
+        .into_iter()
+        .map(|party| CoParty {
+            share_bps: (party.contributed * BPS_DENOMINATOR / bid.amount) as u16,
+            ..party.clone()
+        })
+        .collect();
+    // Rounding goes the bidder's way, so the shares always add up.
+    let others: u16 = parties.iter().map(|party| party.share_bps).sum();
+    parties.insert(
+        0,
+        CoParty {
+            account_id: bid.bidder.clone(),
+            contributed: own_contribution(contract, bid),
+            share_bps: BPS_DENOMINATOR as u16 - others,
+            refunded: false,
+        },
+    );
+    Some(parties)
+}
+
+/// Put an invited co-bidder's money into a pending bid with an
+/// `ft_on_transfer` `CoFund`. Their part grows with every transfer.
+pub(crate) fn co_fund(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    bid_id: u64,
+    sender_id: AccountId,
+    amount: u128,
+) {
+    let bid = bid_on(contract, property_id, bid_id);
+    require!(
+        bid.status == BidStatus::Pending,
+        "Only a pending bid can be co-funded"
+    );
+    require!(
+        bid.stablecoin_token == env::predecessor_account_id(),
+        "Co-funding must be in the bid's stablecoin"
+    );
+    require!(amount > 0, "Amount must be greater than zero");
+
+    let mut joint = contract
+        .co_bidders
+        .get(&bid_id)
+        .cloned()
+        .expect("Nobody has been invited onto this bid");
+    let party = joint
+        .co_bidders
+        .iter_mut()
+        .find(|party| party.account_id == sender_id)
+        .expect("You haven't been invited onto this bid");
+    require!(!party.refunded, "You have already pulled out of this bid");
+    party.contributed = checked_add_u128(party.contributed, amount, "co-funding");
+    contract.co_bidders.insert(bid_id, joint);
+
+    let new_amount = checked_add_u128(bid.amount, amount, "co-funding");
+    if let Some(bids) = contract.bids.get_mut(&property_id) {
+        let _ = update_bid_in_list(bids, bid_id, |b| {
+            b.amount = new_amount;
+            b.updated_at = env::block_timestamp();
+        });
+    }
+    credit_escrow(contract, &bid.stablecoin_token, amount, "co-funding");
+
+    emit_event(
+        "BidCoFunded",
+        BidCoFundedEvent {
+            token_id: property_id,
+            bid_id,
+            bidder_id: bid.bidder,
+            co_bidder_id: sender_id,
+            amount,
+            new_amount,
+        },
+    );
+}
+
+/// Pay co-bidders back what they put in and take it off the bid; `only`
+/// narrows it to one of them. Returns the bid as it now stands.
+fn pay_back(contract: &mut ShedaContract, mut bid: Bid, only: Option<&AccountId>) -> Bid {
+    let Some(mut joint) = contract.co_bidders.get(&bid.id).cloned() else {
+        return bid;
+    };
+    let mut refunds: Vec<(AccountId, u128)> = Vec::new();
+    for party in joint.co_bidders.iter_mut() {
+        if party.refunded || only.is_some_and(|account| *account != party.account_id) {
+            continue;
+        }
+        party.refunded = true;
+        if party.contributed > 0 {
+            refunds.push((party.account_id.clone(), party.contributed));
+        }
+    }
+    contract.co_bidders.insert(bid.id, joint);
+
+    let total: u128 = refunds.iter().map(|(_, amount)| amount).sum();
+    if total == 0 {
+        return bid;
+    }
+    bid.amount = checked_sub_u128(bid.amount, total, "co-bidder refund");
+    if let Some(bids) = contract.bids.get_mut(&bid.property_id) {
+        let _ = update_bid_in_list(bids, bid.id, |b| {
+            b.amount = bid.amount;
+            b.updated_at = env::block_timestamp();
+        });
+    }
+    debit_escrow(contract, &bid.stablecoin_token, total, "co-bidder refund");
+
+    emit_event(
+        "CoBiddersRefunded",
+        CoBiddersRefundedEvent {
+            token_id: bid.property_id,
+            bid_id: bid.id,
+            refunds: refunds.clone(),
+        },
+    );
+
+    let payouts: Vec<(AccountId, U128)> = refunds
+        .into_iter()
+        .map(|(account, amount)| (account, U128(amount)))
+        .collect();
+    let transfer = |(account, amount): &(AccountId, U128)| {
+        ft_contract::ext(bid.stablecoin_token.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(Gas::from_tgas(30))
+            .ft_transfer(account.clone(), *amount)
+    };
+    let mut transfers = payouts.iter().map(transfer);
+    if let Some(first) = transfers.next() {
+        #[allow(unused_must_use)]
+        transfers.fold(first, |all, next| all.and(next)).then(
+            ShedaContract::ext(env::current_account_id())
+                .with_static_gas(Gas::from_tgas(20))
+                .co_bidder_refund_callback(bid.stablecoin_token.clone(), payouts.clone()),
+        );
+    }
+
+    bid
+}
+
+/// A bid is being refunded: co-bidders get back exactly what they put in,
+/// and the bid is left holding the bidder's own part, which the caller
+/// refunds as it always has.
+pub(crate) fn refund_co_bidders(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    bid_id: u64,
+) -> Bid {
+    let bid = bid_on(contract, property_id, bid_id);
+    pay_back(contract, bid, None)
+}
+
+/// `refund_co_bidders` for every pending bid on the property but `keep`,
+/// ahead of the losing bids being refunded.
+pub(crate) fn refund_pending_co_bidders(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    keep: Option<u64>,
+) {
+    let pending: Vec<u64> = contract
+        .bids
+        .get(&property_id)
+        .map(|bids| {
+            bids.iter()
+                .filter(|bid| bid.status == BidStatus::Pending && Some(bid.id) != keep)
+                .filter(|bid| contract.co_bidders.contains_key(&bid.id))
+                .map(|bid| bid.id)
+                .collect()
+        })
+        .unwrap_or_default();
+    for bid_id in pending {
+        refund_co_bidders(contract, property_id, bid_id);
+    }
+}
+
+/// List everyone who funded `bid` on the lease it became, and index the lease
+/// under each of them.
+pub(crate) fn record_co_tenants(contract: &mut ShedaContract, bid: &Bid, lease_id: u64) {
+    let Some(parties) = parties_with_shares(contract, bid) else {
+        return;
+    };
+    for party in parties.iter().skip(1) {
+        let mut leases = contract
+            .lease_per_tenant
+            .get(&party.account_id)
+            .cloned()
+            .unwrap_or_default();
+        if !leases.contains(&lease_id) {
+            leases.push(lease_id);
+        }
+        contract
+            .lease_per_tenant
+            .insert(party.account_id.clone(), leases);
+    }
+    contract.lease_co_tenants.insert(lease_id, parties);
+}
+
+/// List everyone who funded `bid` as co-owners of the property it bought.
+/// Called once ownership has passed to the bidder.
+pub(crate) fn record_co_owners(contract: &mut ShedaContract, bid: &Bid) {
+    if let Some(parties) = parties_with_shares(contract, bid) {
+        contract.property_co_owners.insert(bid.property_id, parties);
+    }
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Bidder invites others to fund a pending bid with them, as co-tenants
+    /// on the lease or co-owners of the property. Each pays in with an
+    /// `ft_on_transfer` `CoFund` and their share is fixed by what they've put
+    /// in when the bid is accepted. If the bid is cancelled, rejected or
+    /// outbid, everyone gets back what they put in.
+    ///
+    /// The bidder still acts for the bid — accepting counter-offers,
+    /// cancelling it, and afterwards holding the NFT.
+    #[payable]
+    pub fn invite_co_bidders(&mut self, property_id: u64, bid_id: u64, co_bidders: Vec<AccountId>) {
+        assert_one_yocto();
+
+        let bid = bid_on(self, property_id, bid_id);
+        assert_eq!(
+            bid.bidder,
+            env::predecessor_account_id(),
+            "Only the bidder can invite co-bidders"
+        );
+        require!(
+            bid.status == BidStatus::Pending,
+            "Only a pending bid can be co-funded"
+        );
+        // The deposit/balance split and the instalment schedule belong to
+        // the bidder alone.
+        crate::earnest::assert_not_earnest(self, bid_id, "An earnest-money bid can't be co-funded");
+        crate::instalment::assert_not_instalment(
+            self,
+            bid_id,
+            "An instalment bid can't be co-funded",
+        );
+        require!(!co_bidders.is_empty(), "Invite at least one co-bidder");
+
+        let mut joint = self.co_bidders.get(&bid_id).cloned().unwrap_or(JointBid {
+            property_id,
+            co_bidders: Vec::new(),
+        });
+        for account_id in co_bidders {
+            require!(account_id != bid.bidder, "You're already on the bid");
+            require!(
+                !joint
+                    .co_bidders
+                    .iter()
+                    .any(|party| party.account_id == account_id),
+                format!("{} is already invited", account_id)
+            );
+            joint.co_bidders.push(CoParty {
+                account_id,
+                contributed: 0,
+                share_bps: 0,
+                refunded: false,
+            });
+        }
+        self.co_bidders.insert(bid_id, joint);
+    }
+
+    /// Co-bidder pulls out of a bid still pending and is paid back.
+    #[payable]
+    pub fn leave_joint_bid(&mut self, property_id: u64, bid_id: u64) {
+        assert_one_yocto();
+
+        let bid = bid_on(self, property_id, bid_id);
+        require!(
+            bid.status == BidStatus::Pending,
+            "The bid is no longer pending"
+        );
+        let caller = env::predecessor_account_id();
+        require!(
+            self.co_bidders.get(&bid_id).is_some_and(|joint| joint
+                .co_bidders
+                .iter()
+                .any(|party| party.account_id == caller && !party.refunded)),
+            "You aren't a co-bidder on this bid"
+        );
+
+        let bid = pay_back(self, bid, Some(&caller));
+        crate::deposit::assert_covers_deposit(self, bid_id, bid.amount);
+        log!("{} left bid {}", caller, bid_id);
+    }
+
+    #[private]
+    pub fn co_bidder_refund_callback(
+        &mut self,
+        stablecoin_token: AccountId,
+        payouts: Vec<(AccountId, U128)>,
+    ) {
+        for (index, (account, amount)) in payouts.into_iter().enumerate() {
+            if crate::internal::promise_failed(index as u64) {
+                // The co-bidder is off the bid either way; they can claim it.
+                crate::payout::hold_for_claim(self, &account, &stablecoin_token, amount.0);
+                log!(
+                    "Co-bidder refund of {} to {} failed, held for them to claim",
+                    amount.0,
+                    account
+                );
+            }
+        }
+    }
+
+    /// Everyone on a bid, the bidder first, with what each has put in. Shares
+    /// are as they'd be if the bid were accepted now.
+    pub fn get_co_bidders(&self, property_id: u64, bid_id: u64) -> Vec<CoPartyView> {
+        let bid = bid_on(self, property_id, bid_id);
+        let Some(joint) = self.co_bidders.get(&bid_id) else {
+            return Vec::new();
+        };
+        let shares = parties_with_shares(self, &bid).unwrap_or_default();
+        let bidder = CoParty {
+            account_id: bid.bidder.clone(),
+            contributed: own_contribution(self, &bid),
+            share_bps: shares
+                .first()
+                .map_or(BPS_DENOMINATOR as u16, |p| p.share_bps),
+            refunded: false,
+        };
+        std::iter::once(&bidder)
+            .chain(joint.co_bidders.iter())
+            .map(|party| {
+                let share_bps = shares
+                    .iter()
+                    .find(|p| p.account_id == party.account_id)
+                    .map_or(0, |p| p.share_bps);
+                CoPartyView {
+                    share_bps,
+                    ..party.into()
+                }
+            })
+            .collect()
+    }
+
+    /// Everyone on a jointly funded lease, the tenant first.
+    pub fn get_lease_co_tenants(&self, lease_id: u64) -> Vec<CoPartyView> {
+        self.lease_co_tenants
+            .get(&lease_id)
+            .map(|parties| parties.iter().map(|party| party.into()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Everyone a property was jointly bought by, the owner first.
+    pub fn get_property_co_owners(&self, property_id: u64) -> Vec<CoPartyView> {
+        self.property_co_owners
+            .get(&property_id)
+            .map(|parties| parties.iter().map(|party| party.into()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Every joint bid an account is on, as the bidder or a co-bidder.
+    pub fn get_joint_bids_by_account(&self, account_id: AccountId) -> Vec<JointBidView> {
+        self.co_bidders
+            .iter()
+            .filter_map(|(bid_id, joint)| {
+                let bid = self
+                    .bids
+                    .get(&joint.property_id)?
+                    .iter()
+                    .find(|bid| bid.id == *bid_id)?;
+                let (contributed, refunded) = if bid.bidder == account_id {
+                    (own_contribution(self, bid), false)
+                } else {
+                    let party = joint
+                        .co_bidders
+                        .iter()
+                        .find(|party| party.account_id == account_id)?;
+                    (party.contributed, party.refunded)
+                };
+                Some(JointBidView {
+                    bid_id: *bid_id,
+                    property_id: joint.property_id,
+                    bidder_id: bid.bidder.to_string(),
+                    contributed: contributed.to_string(),
+                    refunded,
+                })
+            })
+            .collect()
+    }
+}
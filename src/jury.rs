@@ -0,0 +1,537 @@
+use near_sdk::{assert_one_yocto, env, json_types::U128, log, near_bindgen, require, AccountId};
+
+use crate::{
+    events::{emit_event, ArbitrationDecidedEvent, ArbitrationRequestedEvent},
+    internal::{checked_add_u128, credit_escrow, debit_escrow},
+    models::{
+        Arbitration, ArbitrationMsg, BidStatus, DamageDepositStatus, DisputeCase,
+        DisputeResolution, DisputeStatus, DisputeWinner, Juror, JuryBallot, JuryTerms,
+    },
+    views::{ArbitrationView, JurorView},
+    ShedaContract, ShedaContractExt,
+};
+
+const MAX_PANEL_SIZE: u32 = 21;
+
+fn terms(contract: &ShedaContract) -> JuryTerms {
+    contract
+        .jury_terms
+        .clone()
+        .expect("Arbitration is not set up")
+}
+
+/// The commitment a juror's vote has to match on reveal. Binding the juror
+/// into the preimage stops one panellist from copying another's hash.
+pub(crate) fn jury_vote_commitment(juror: &AccountId, for_raiser: bool, salt: &str) -> String {
+    env::sha256(format!("{}:{}:{}", juror, for_raiser, salt).as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Admins stand aside while a panel sits on a dispute. Called wherever an
+/// admin, the admins' vote or the oracle would resolve it.
+pub(crate) fn assert_no_panel(contract: &ShedaContract, case: &DisputeCase) {
+    require!(
+        !contract.arbitrations.contains_key(case),
+        "A juror panel is deciding this dispute"
+    );
+}
+
+/// Whether the dispute is raised and waiting on a decision — not still
+/// waiting on the tenant to answer an owner's dispute.
+fn is_raised(contract: &ShedaContract, case: &DisputeCase) -> bool {
+    match case {
+        DisputeCase::Lease { lease_id } => contract
+            .leases
+            .get(lease_id)
+            .is_some_and(|lease| lease.dispute_status == DisputeStatus::Raised),
+        DisputeCase::Bid {
+            property_id,
+            bid_id,
+        } => contract
+            .bids
+            .get(property_id)
+            .and_then(|bids| bids.iter().find(|bid| bid.id == *bid_id))
+            .is_some_and(|bid| bid.status == BidStatus::Disputed),
+    }
+}
+
+/// Handle an `ft_transfer_call` carrying an `ArbitrationMsg`. Returns the
+/// part of the transfer to hand back.
+pub(crate) fn on_transfer(
+    contract: &mut ShedaContract,
+    sender_id: AccountId,
+    amount: u128,
+    msg: ArbitrationMsg,
+) -> u128 {
+    let terms = terms(contract);
+    let token = env::predecessor_account_id();
+    require!(
+        token == terms.stablecoin_token,
+        "Arbitration is paid in another stablecoin"
+    );
+
+    match msg {
+        ArbitrationMsg::StakeJuror => {
+            require!(amount > 0, "Nothing to stake");
+            let juror = contract.jurors.entry(sender_id.clone()).or_insert(Juror {
+                stake: 0,
+                panels: 0,
+            });
+            juror.stake = checked_add_u128(juror.stake, amount, "juror stake");
+            credit_escrow(contract, &token, amount, "juror stake");
+            log!("{} staked {} as a juror", sender_id, amount);
+            0
+        }
+        ArbitrationMsg::RequestArbitration { case } => {
+            let fee = terms.arbitration_fee.0;
+            require!(amount >= fee, "Not enough to cover the arbitration fee");
+            request(contract, case, sender_id, &terms);
+            credit_escrow(contract, &token, fee, "arbitration fee");
+            amount - fee
+        }
+    }
+}
+
+/// Put `case` before a freshly drawn panel.
+fn request(
+    contract: &mut ShedaContract,
+    case: DisputeCase,
+    requested_by: AccountId,
+    terms: &JuryTerms,
+) {
+    require!(is_raised(contract, &case), "The dispute is not raised");
+    assert_no_panel(contract, &case);
+    let file = contract
+        .dispute_case_files
+        .get(&case)
+        .cloned()
+        .expect("No case file for this dispute");
+    require!(
+        requested_by == file.raised_by || requested_by == file.respondent,
+        "Only a party to the dispute can ask for arbitration"
+    );
+
+    let panel = draw_panel(
+        contract,
+        terms,
+        &[file.raised_by.clone(), file.respondent.clone()],
+    );
+    for juror_id in &panel {
+        if let Some(juror) = contract.jurors.get_mut(juror_id) {
+            juror.panels += 1;
+        }
+    }
+
+    let now = env::block_timestamp();
+    let commit_deadline = now.saturating_add(terms.commit_period_ns);
+    contract.arbitrations.insert(
+        case.clone(),
+        Arbitration {
+            requested_by: requested_by.clone(),
+            stablecoin_token: terms.stablecoin_token.clone(),
+            fee: terms.arbitration_fee.0,
+            slash_bps: terms.slash_bps,
+            panel: panel
+                .iter()
+                .map(|juror_id| JuryBallot {
+                    juror_id: juror_id.clone(),
+                    commitment: None,
+                    for_raiser: None,
+                })
+                .collect(),
+            commit_deadline,
+            reveal_deadline: commit_deadline.saturating_add(terms.reveal_period_ns),
+        },
+    );
+
+    emit_event(
+        "ArbitrationRequested",
+        ArbitrationRequestedEvent {
+            case,
+            requested_by,
+            panel,
+        },
+    );
+}
+
+/// Draw `terms.panel_size` jurors with enough at stake, leaving out the
+/// parties. Each seat is picked by a hash of the block's random seed and the
+/// seat number.
+fn draw_panel(
+    contract: &ShedaContract,
+    terms: &JuryTerms,
+    parties: &[AccountId],
+) -> Vec<AccountId> {
+    let mut pool: Vec<AccountId> = contract
+        .jurors
+        .iter()
+        .filter(|(account_id, juror)| {
+            juror.stake >= terms.min_stake.0 && !parties.contains(account_id)
+        })
+        .map(|(account_id, _)| account_id.clone())
+        .collect();
+    require!(
+        pool.len() >= terms.panel_size as usize,
+        "Not enough jurors to draw a panel"
+    );
+
+    let seed = env::random_seed();
+    (0..terms.panel_size)
+        .map(|seat| {
+            let mut preimage = seed.clone();
+            preimage.extend_from_slice(&seat.to_le_bytes());
+            let hash = env::sha256(&preimage);
+            let mut draw = [0u8; 8];
+            draw.copy_from_slice(&hash[..8]);
+            let index = (u64::from_le_bytes(draw) % pool.len() as u64) as usize;
+            pool.swap_remove(index)
+        })
+        .collect()
+}
+
+fn ballot_mut<'a>(arbitration: &'a mut Arbitration, juror_id: &AccountId) -> &'a mut JuryBallot {
+    arbitration
+        .panel
+        .iter_mut()
+        .find(|ballot| ballot.juror_id == *juror_id)
+        .unwrap_or_else(|| env::panic_str("You are not on this panel"))
+}
+
+/// What the winner of a lease dispute is paid. An owner's claim is paid, up
+/// to the damage deposit still held, if they win and not at all if they
+/// lose. Otherwise a dispute over
+/// deposit deductions goes wholly to the winner, and over the lease payment
+/// a winning tenant is refunded while a winning owner keeps what they had.
+fn lease_award(contract: &ShedaContract, lease_id: u64, winner: &DisputeWinner) -> u128 {
+    if let Some(claim) = contract.lease_dispute_claims.get(&lease_id) {
+        return match winner {
+            DisputeWinner::Owner => *claim,
+            DisputeWinner::Tenant => 0,
+        };
+    }
+    let deposit_disputed = contract
+        .damage_deposits
+        .get(&lease_id)
+        .is_some_and(|deposit| deposit.status == DamageDepositStatus::Disputed);
+    match winner {
+        // Capped at the deposit by `resolve_dispute_payout`.
+        _ if deposit_disputed => u128::MAX,
+        DisputeWinner::Tenant => contract
+            .leases
+            .get(&lease_id)
+            .map(|lease| lease.escrow_held)
+            .unwrap_or(0),
+        DisputeWinner::Owner => 0,
+    }
+}
+
+/// Carry out the panel's verdict through the same path an admin's ruling
+/// takes.
+fn enforce(contract: &mut ShedaContract, case: &DisputeCase, raiser_won: bool) {
+    let file = contract
+        .dispute_case_files
+        .get(case)
+        .cloned()
+        .expect("No case file for this dispute");
+    let winner_id = if raiser_won {
+        file.raised_by
+    } else {
+        file.respondent
+    };
+
+    match case {
+        DisputeCase::Lease { lease_id } => {
+            let tenant_id = contract
+                .leases
+                .get(lease_id)
+                .map(|lease| lease.tenant_id.clone())
+                .expect("Lease not found");
+            let winner = if winner_id == tenant_id {
+                DisputeWinner::Tenant
+            } else {
+                DisputeWinner::Owner
+            };
+            let award = lease_award(contract, *lease_id, &winner);
+            contract
+                .resolve_dispute_payout(*lease_id, winner, U128(award))
+                .unwrap_or_else(|error| env::panic_str(error.as_ref()));
+        }
+        DisputeCase::Bid {
+            property_id,
+            bid_id,
+        } => {
+            let bidder = contract
+                .bids
+                .get(property_id)
+                .and_then(|bids| bids.iter().find(|bid| bid.id == *bid_id))
+                .map(|bid| bid.bidder.clone())
+                .expect("Bid not found");
+            let resolution = if winner_id == bidder {
+                DisputeResolution::BuyerWins
+            } else {
+                DisputeResolution::SellerWins
+            };
+            #[allow(unused_must_use)]
+            crate::internal::internal_admin_resolve_bid_dispute(
+                contract,
+                *property_id,
+                *bid_id,
+                resolution,
+            );
+        }
+    }
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Contract owner sets up, or changes, arbitration by juror panels.
+    /// Panels already sitting keep their fee and deadlines.
+    pub fn set_jury_terms(&mut self, terms: JuryTerms) {
+        self.assert_owner();
+        require!(
+            self.accepted_stablecoin.contains(&terms.stablecoin_token),
+            "Stablecoin not accepted"
+        );
+        require!(
+            self.jurors.is_empty()
+                || self
+                    .jury_terms
+                    .as_ref()
+                    .is_some_and(|current| current.stablecoin_token == terms.stablecoin_token),
+            "Jurors have stakes in the current stablecoin"
+        );
+        require!(
+            terms.min_stake.0 > 0,
+            "The minimum stake must be above zero"
+        );
+        require!(
+            terms.panel_size % 2 == 1 && terms.panel_size <= MAX_PANEL_SIZE,
+            format!(
+                "A panel must have an odd number of jurors, at most {}",
+                MAX_PANEL_SIZE
+            )
+        );
+        require!(terms.slash_bps <= 10_000, "Basis points can't exceed 10000");
+        require!(
+            terms.commit_period_ns > 0 && terms.reveal_period_ns > 0,
+            "The commit and reveal periods must be greater than zero"
+        );
+        self.jury_terms = Some(terms);
+    }
+
+    /// Juror takes back some or all of their stake. Not while they sit on a
+    /// panel.
+    #[payable]
+    pub fn withdraw_juror_stake(&mut self, amount: Option<U128>) -> U128 {
+        assert_one_yocto();
+
+        let juror_id = env::predecessor_account_id();
+        let juror = self.jurors.get(&juror_id).cloned().expect("Not a juror");
+        require!(juror.panels == 0, "You are sitting on a panel");
+        let amount = amount.map(|amount| amount.0).unwrap_or(juror.stake);
+        require!(amount > 0, "Nothing to withdraw");
+        require!(amount <= juror.stake, "Not that much staked");
+
+        if amount == juror.stake {
+            self.jurors.remove(&juror_id);
+        } else if let Some(juror) = self.jurors.get_mut(&juror_id) {
+            juror.stake -= amount;
+        }
+        let token = terms(self).stablecoin_token;
+        debit_escrow(self, &token, amount, "juror stake withdrawal");
+        #[allow(unused_must_use)]
+        crate::payout::send_payouts(&token, vec![(juror_id, amount)]);
+        U128(amount)
+    }
+
+    /// Panellist commits to a vote: the hex sha256 of
+    /// `"{juror}:{for_raiser}:{salt}"`, where `for_raiser` is whether the side
+    /// that raised the dispute should win. Can be replaced until the commit
+    /// period ends.
+    pub fn commit_jury_vote(&mut self, case: DisputeCase, commitment: String) {
+        require!(
+            commitment.len() == 64 && commitment.chars().all(|c| c.is_ascii_hexdigit()),
+            "Commitment must be a hex-encoded sha256 hash"
+        );
+        let juror_id = env::predecessor_account_id();
+        let arbitration = self
+            .arbitrations
+            .get_mut(&case)
+            .expect("No panel is sitting on this dispute");
+        require!(
+            env::block_timestamp() <= arbitration.commit_deadline,
+            "The commit period has ended"
+        );
+        ballot_mut(arbitration, &juror_id).commitment = Some(commitment);
+    }
+
+    /// Panellist reveals their vote, once every panellist has committed or
+    /// the commit period is over.
+    pub fn reveal_jury_vote(&mut self, case: DisputeCase, for_raiser: bool, salt: String) {
+        let juror_id = env::predecessor_account_id();
+        let arbitration = self
+            .arbitrations
+            .get_mut(&case)
+            .expect("No panel is sitting on this dispute");
+        let now = env::block_timestamp();
+        require!(
+            now > arbitration.commit_deadline
+                || arbitration
+                    .panel
+                    .iter()
+                    .all(|ballot| ballot.commitment.is_some()),
+            "Panellists are still committing"
+        );
+        require!(
+            now <= arbitration.reveal_deadline,
+            "The reveal period has ended"
+        );
+        let ballot = ballot_mut(arbitration, &juror_id);
+        require!(ballot.for_raiser.is_none(), "Already revealed");
+        require!(
+            ballot.commitment.as_deref()
+                == Some(jury_vote_commitment(&juror_id, for_raiser, &salt).as_str()),
+            "The vote doesn't match the commitment"
+        );
+        ballot.for_raiser = Some(for_raiser);
+    }
+
+    /// Anyone closes a panel once every panellist has revealed or the reveal
+    /// period is over. The majority splits the fee and the stakes slashed
+    /// from the minority and from those who didn't reveal, and the verdict
+    /// resolves the dispute. A panel without a majority hands the fee back
+    /// and leaves the dispute to be decided again.
+    pub fn finalize_arbitration(&mut self, case: DisputeCase) {
+        let arbitration = self
+            .arbitrations
+            .get(&case)
+            .cloned()
+            .expect("No panel is sitting on this dispute");
+        require!(
+            env::block_timestamp() > arbitration.reveal_deadline
+                || arbitration
+                    .panel
+                    .iter()
+                    .all(|ballot| ballot.for_raiser.is_some()),
+            "Panellists are still voting"
+        );
+        self.arbitrations.remove(&case);
+
+        let for_raiser = arbitration
+            .panel
+            .iter()
+            .filter(|ballot| ballot.for_raiser == Some(true))
+            .count();
+        let against = arbitration
+            .panel
+            .iter()
+            .filter(|ballot| ballot.for_raiser == Some(false))
+            .count();
+        // A dispute settled some other way meanwhile has nothing to decide.
+        let raiser_won =
+            (for_raiser != against && is_raised(self, &case)).then_some(for_raiser > against);
+
+        let mut slashed = Vec::new();
+        let mut majority = Vec::new();
+        for ballot in &arbitration.panel {
+            let Some(juror) = self.jurors.get_mut(&ballot.juror_id) else {
+                continue;
+            };
+            juror.panels = juror.panels.saturating_sub(1);
+            match (raiser_won, ballot.for_raiser) {
+                (Some(verdict), Some(vote)) if vote == verdict => {
+                    majority.push(ballot.juror_id.clone());
+                }
+                // Voting against a verdict that didn't come costs nothing.
+                (None, Some(_)) => {}
+                _ => {
+                    let cut = juror.stake * u128::from(arbitration.slash_bps) / 10_000;
+                    juror.stake -= cut;
+                    if cut > 0 {
+                        slashed.push((ballot.juror_id.clone(), cut));
+                    }
+                }
+            }
+        }
+        let total_slashed = slashed.iter().fold(0, |total, (_, cut)| {
+            checked_add_u128(total, *cut, "slashed stakes")
+        });
+
+        let token = arbitration.stablecoin_token.clone();
+        let mut rewards = Vec::new();
+        if majority.is_empty() {
+            // No verdict: the requester has their fee back and slashed stakes
+            // go to the platform.
+            debit_escrow(
+                self,
+                &token,
+                checked_add_u128(arbitration.fee, total_slashed, "arbitration refund"),
+                "arbitration refund",
+            );
+            if total_slashed > 0 {
+                let accrued = self.accrued_fees.entry(token.clone()).or_insert(0);
+                *accrued = checked_add_u128(*accrued, total_slashed, "accrued fees");
+            }
+            #[allow(unused_must_use)]
+            crate::payout::send_payouts(
+                &token,
+                vec![(arbitration.requested_by.clone(), arbitration.fee)],
+            );
+        } else {
+            // The pool stays staked, shared out among the majority; the first
+            // juror takes the rounding dust.
+            let pool = checked_add_u128(arbitration.fee, total_slashed, "jury rewards");
+            let share = pool / majority.len() as u128;
+            for (index, juror_id) in majority.iter().enumerate() {
+                let reward = if index == 0 {
+                    pool - share * (majority.len() as u128 - 1)
+                } else {
+                    share
+                };
+                if let Some(juror) = self.jurors.get_mut(juror_id) {
+                    juror.stake = checked_add_u128(juror.stake, reward, "jury reward");
+                }
+                rewards.push((juror_id.clone(), reward));
+            }
+        }
+
+        emit_event(
+            "ArbitrationDecided",
+            ArbitrationDecidedEvent {
+                case: case.clone(),
+                raiser_won,
+                rewards,
+                slashed,
+            },
+        );
+        if let Some(raiser_won) = raiser_won {
+            enforce(self, &case, raiser_won);
+        }
+    }
+
+    pub fn get_jury_terms(&self) -> Option<JuryTerms> {
+        self.jury_terms.clone()
+    }
+
+    pub fn get_juror(&self, account_id: AccountId) -> Option<JurorView> {
+        self.jurors.get(&account_id).map(|juror| JurorView {
+            account_id: account_id.to_string(),
+            stake: juror.stake.to_string(),
+            panels: juror.panels,
+        })
+    }
+
+    pub fn get_arbitration(&self, case: DisputeCase) -> Option<ArbitrationView> {
+        self.arbitrations
+            .get(&case)
+            .map(|arbitration| arbitration.into())
+    }
+
+    /// The commitment to send for a jury vote. Like `hash_sealed_bid`, a
+    /// convenience: the RPC node answering sees the vote.
+    pub fn hash_jury_vote(&self, juror: AccountId, for_raiser: bool, salt: String) -> String {
+        jury_vote_commitment(&juror, for_raiser, &salt)
+    }
+}
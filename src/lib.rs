@@ -1,8 +1,28 @@
 // Find all our documentation at https://docs.near.org
 pub mod admin;
+pub mod auction;
+pub mod bond;
+pub mod booking;
+pub mod building;
+pub mod deposit;
+pub mod earnest;
 pub mod events;
+pub mod evidence;
+pub mod fees;
+pub mod fraction;
+pub mod instalment;
 pub mod internal;
+pub mod joint;
+pub mod jury;
+pub mod listing;
 pub mod models;
+pub mod negotiation;
+pub mod payout;
+pub mod renewal;
+pub mod rent;
+pub mod royalty;
+pub mod sublease;
+pub mod termination;
 pub mod views;
 
 pub mod ext;
@@ -17,7 +37,7 @@ use crate::{internal::*, models::Action};
 #[allow(unused_imports)]
 use near_contract_standards::non_fungible_token::{
     approval::NonFungibleTokenApproval,
-    core::NonFungibleTokenCore,
+    core::{NonFungibleTokenCore, NonFungibleTokenResolver},
     enumeration::NonFungibleTokenEnumeration,
     metadata::NonFungibleTokenMetadataProvider,
     metadata::{NFTContractMetadata, TokenMetadata},
@@ -61,6 +81,36 @@ pub const DEFAULT_STALLED_DEAL_TIMEOUT_NS: u64 = 7 * 24 * NS_PER_HOUR;
 pub const DEFAULT_DISPUTE_RESOLUTION_TIMELOCK_NS: u64 = 72 * NS_PER_HOUR;
 pub const DEFAULT_LEASE_EARLY_TERMINATION_WINDOW_NS: u64 = 7 * 24 * NS_PER_HOUR;
 
+/// Defaults for earnest-money purchases: at least 10% down, the whole deposit
+/// forfeited if the balance isn't paid, and 30 days to pay it.
+pub const DEFAULT_EARNEST_MIN_DEPOSIT_BPS: u16 = 1_000;
+pub const DEFAULT_EARNEST_FORFEIT_BPS: u16 = 10_000;
+pub const DEFAULT_BALANCE_DUE_WINDOW_NS: u64 = 30 * 24 * NS_PER_HOUR;
+
+/// A missed instalment can be made good for a week before the seller may
+/// foreclose.
+pub const DEFAULT_INSTALMENT_GRACE_PERIOD_NS: u64 = 7 * 24 * NS_PER_HOUR;
+
+/// Two weeks for the owner to file deductions once a lease ends, and two
+/// weeks for the tenant to answer them.
+pub const DEFAULT_DEPOSIT_CLAIM_WINDOW_NS: u64 = 14 * 24 * NS_PER_HOUR;
+
+/// Ending a lease early costs whoever gives notice 10% of the lease payment.
+pub const DEFAULT_EARLY_TERMINATION_PENALTY_BPS: u16 = 1_000;
+
+/// A week for the other side of a dispute to put in their evidence.
+pub const DEFAULT_DISPUTE_RESPONSE_PERIOD_NS: u64 = 7 * 24 * NS_PER_HOUR;
+
+/// A lease dispute resolves itself once three admins have voted and one
+/// side has a simple majority, or when voting has been open for a week.
+pub const DEFAULT_DISPUTE_QUORUM: u32 = 3;
+pub const DEFAULT_DISPUTE_MAJORITY_BPS: u16 = 5_001;
+pub const DEFAULT_DISPUTE_VOTING_PERIOD_NS: u64 = 7 * 24 * NS_PER_HOUR;
+
+/// Storage prefixes for the collections v5 introduces.
+///
+/// All the same length and none a prefix of another, or of any v2-era prefix,
+/// so no two maps can ever read each other's keys.
 /// Storage prefix for the bids map rebuilt by the v4 migration.
 ///
 /// Deliberately *not* the original `b"b"`. `IterableMap` keeps its entries in
@@ -70,6 +120,49 @@ pub const DEFAULT_LEASE_EARLY_TERMINATION_WINDOW_NS: u64 = 7 * 24 * NS_PER_HOUR;
 /// got corrupted in the first place. A new prefix guarantees a clean slate.
 const BIDS_V4_PREFIX: &[u8] = b"b4";
 
+const AUCTIONS_PREFIX: &[u8] = b"v5_auc";
+const SEALED_COMMITS_PREFIX: &[u8] = b"v5_sld";
+const NEGOTIATIONS_PREFIX: &[u8] = b"v5_neg";
+const EARNEST_DEPOSITS_PREFIX: &[u8] = b"v5_ern";
+const INSTALMENT_PLANS_PREFIX: &[u8] = b"v5_ins";
+const PROPERTY_LIENS_PREFIX: &[u8] = b"v5_lie";
+const RENT_TERMS_PREFIX: &[u8] = b"v5_rtt";
+const RENT_SCHEDULES_PREFIX: &[u8] = b"v5_rnt";
+const DAMAGE_DEPOSIT_TERMS_PREFIX: &[u8] = b"v5_ddt";
+const BID_DAMAGE_DEPOSITS_PREFIX: &[u8] = b"v5_ddb";
+const DAMAGE_DEPOSITS_PREFIX: &[u8] = b"v5_dmg";
+const LEASE_TERMINATIONS_PREFIX: &[u8] = b"v5_trm";
+const LEASE_ASSIGNMENTS_PREFIX: &[u8] = b"v5_asg";
+const SUBLEASE_OFFERS_PREFIX: &[u8] = b"v5_sbo";
+const SUBLEASES_PREFIX: &[u8] = b"v5_sbl";
+const CO_BIDDERS_PREFIX: &[u8] = b"v5_cob";
+const LEASE_CO_TENANTS_PREFIX: &[u8] = b"v5_cot";
+const PROPERTY_CO_OWNERS_PREFIX: &[u8] = b"v5_coo";
+const AUTO_RENEWALS_PREFIX: &[u8] = b"v5_arn";
+const BOOKING_TERMS_PREFIX: &[u8] = b"v5_bkt";
+const BOOKINGS_PREFIX: &[u8] = b"v5_bkg";
+const PROPERTY_BOOKINGS_PREFIX: &[u8] = b"v5_bkp";
+const BUILDING_UNITS_PREFIX: &[u8] = b"v5_bld";
+const UNIT_BUILDING_PREFIX: &[u8] = b"v5_unb";
+const FRACTIONAL_PROPERTIES_PREFIX: &[u8] = b"v5_frc";
+const SHARE_HOLDINGS_PREFIX: &[u8] = b"v5_shr";
+const PAYOUT_SPLITS_PREFIX: &[u8] = b"v5_pys";
+const CLAIMABLE_BALANCES_PREFIX: &[u8] = b"v5_clm";
+const PLATFORM_FEES_PREFIX: &[u8] = b"v5_pfe";
+const LISTING_BROKERS_PREFIX: &[u8] = b"v5_lbr";
+const ACCRUED_FEES_PREFIX: &[u8] = b"v5_acf";
+const PENDING_FEES_PREFIX: &[u8] = b"v5_pfc";
+const PROPERTY_ROYALTIES_PREFIX: &[u8] = b"v5_roy";
+const PENDING_ROYALTIES_PREFIX: &[u8] = b"v5_pry";
+const EXTERNAL_LISTINGS_PREFIX: &[u8] = b"v5_xls";
+const DISPUTE_CASE_FILES_PREFIX: &[u8] = b"v5_dcf";
+const LEASE_DISPUTE_CLAIMS_PREFIX: &[u8] = b"v5_ldc";
+const DISPUTE_VOTES_PREFIX: &[u8] = b"v5_dvt";
+const JURORS_PREFIX: &[u8] = b"v5_jur";
+const ARBITRATIONS_PREFIX: &[u8] = b"v5_arb";
+const DISPUTE_BONDS_PREFIX: &[u8] = b"v5_dbd";
+const SUBLEASES_PER_LEASE_PREFIX: &[u8] = b"v5_sbi";
+
 #[near(contract_state)]
 #[derive(PanicOnDefault)]
 pub struct ShedaContract {
@@ -147,6 +240,133 @@ pub struct ShedaContract {
     pub pending_upgrade_at: Option<u64>,
 
     pub version: u32,
+
+    // Auctions, keyed by property id. A property has at most one, and a
+    // settled auction stays here as a record until the next one replaces it.
+    pub auctions: IterableMap<u64, models::Auction>,
+    // Sealed-bid commitments, keyed by property id. Cleared when the next
+    // sealed auction on the property starts — by which point every deposit
+    // has been refunded.
+    pub sealed_commits: IterableMap<u64, Vec<models::SealedCommit>>,
+    // Counter-offer rounds, keyed by bid id (bid ids are unique across
+    // properties). Only the last round can still be open.
+    pub negotiations: IterableMap<u64, Vec<models::CounterOffer>>,
+    // Earnest-money terms, keyed by the bid id of a deposit-only purchase bid.
+    pub earnest_deposits: IterableMap<u64, models::EarnestMoney>,
+    // Earnest-money policy: the smallest deposit accepted, as basis points
+    // of the offer price; how much of it the buyer forfeits for missing the
+    // balance deadline; and how long after confirming the documents they have.
+    pub earnest_min_deposit_bps: u16,
+    pub earnest_forfeit_bps: u16,
+    pub balance_due_window_ns: u64,
+    // Instalment schedules, keyed by the bid id of an instalment purchase bid.
+    pub instalment_plans: IterableMap<u64, models::InstalmentPlan>,
+    // Property id to the instalment bid holding a lien on it. Present only
+    // while the buyer holds the NFT and still owes instalments.
+    pub property_liens: IterableMap<u64, u64>,
+    // How long past a missed instalment the seller must wait to foreclose.
+    pub instalment_grace_period_ns: u64,
+    // Rent terms a property is let on, keyed by property id. Copied into a
+    // schedule for every lease that starts while they're set.
+    pub rent_terms: IterableMap<u64, models::RentTerms>,
+    // Rent schedules and their payment history, keyed by lease id.
+    pub rent_schedules: IterableMap<u64, models::RentSchedule>,
+    // Damage deposit a property's tenants must lodge, keyed by property id.
+    pub damage_deposit_terms: IterableMap<u64, u128>,
+    // The part of a lease bid that is damage deposit, keyed by bid id. Held
+    // back from the owner when the bid is paid out.
+    pub bid_damage_deposits: IterableMap<u64, u128>,
+    // Damage deposits held against running or ended leases, keyed by lease id.
+    pub damage_deposits: IterableMap<u64, models::DamageDeposit>,
+    // How long the owner has after a lease ends to file deductions against
+    // the deposit, and the tenant then has to answer them.
+    pub deposit_claim_window_ns: u64,
+    // Early-termination notices, keyed by lease id.
+    pub lease_terminations: IterableMap<u64, models::LeaseTermination>,
+    // What ending a lease early costs the side that gives notice, as basis
+    // points of the lease payment.
+    pub early_termination_penalty_bps: u16,
+    // Pending hand-overs of a lease to a new tenant, keyed by lease id.
+    pub lease_assignments: IterableMap<u64, models::LeaseAssignment>,
+    // Offers to sublet, keyed by the primary tenant's lease id.
+    pub sublease_offers: IterableMap<u64, models::SubleaseOffer>,
+    // Sublease lease id to the lease it was sublet from.
+    pub subleases: IterableMap<u64, u64>,
+    // Accounts invited to help fund a bid, keyed by bid id.
+    pub co_bidders: IterableMap<u64, models::JointBid>,
+    // Everyone on a jointly funded lease, the tenant first, with their shares.
+    pub lease_co_tenants: IterableMap<u64, Vec<models::CoParty>>,
+    // Everyone a property was jointly bought by, the owner first, with their
+    // shares. Cleared when it changes hands again.
+    pub property_co_owners: IterableMap<u64, Vec<models::CoParty>>,
+    // Automatic renewal terms, keyed by lease id.
+    pub auto_renewals: IterableMap<u64, models::AutoRenewal>,
+    // Short-stay booking terms, keyed by property id.
+    pub booking_terms: IterableMap<u64, models::BookingTerms>,
+    // Short-stay bookings, keyed by booking id.
+    pub bookings: IterableMap<u64, models::Booking>,
+    // Booking ids per property, in the order they were made.
+    pub property_bookings: IterableMap<u64, Vec<u64>>,
+    pub booking_counter: u64,
+    // Units of each building, keyed by the building's property id.
+    pub building_units: IterableMap<u64, Vec<u64>>,
+    // The building each unit belongs to, keyed by the unit's property id.
+    pub unit_building: IterableMap<u64, u64>,
+    // Fractionalised properties, keyed by property id. Kept after a buyout
+    // until every shareholder has claimed.
+    pub fractional_properties: IterableMap<u64, models::Fractional>,
+    // Share ledger of each fractionalised property, keyed by property id.
+    pub share_holdings: IterableMap<u64, Vec<models::ShareHolding>>,
+    // How each property's owner-bound payments are split, keyed by property id.
+    pub payout_splits: IterableMap<u64, Vec<models::PayoutShare>>,
+    // Payouts whose transfer failed, held for the recipient to claim.
+    pub claimable_balances: IterableMap<AccountId, Vec<models::ClaimableBalance>>,
+    // Platform fee per action, keyed by stablecoin.
+    pub platform_fees: IterableMap<AccountId, Vec<models::PlatformFee>>,
+    // Broker commission attached to a listing, keyed by property id.
+    pub listing_brokers: IterableMap<u64, models::BrokerCommission>,
+    // Platform fees collected and not yet withdrawn, per stablecoin. Kept
+    // apart from the escrow in stable_coin_balances.
+    pub accrued_fees: IterableMap<AccountId, u128>,
+    // Fees on a bid payout still waiting on its transfer, keyed by bid id.
+    pub pending_fees: IterableMap<u64, models::FeeCharge>,
+    // Royalties on every sale of a property, keyed by property id.
+    pub property_royalties: IterableMap<u64, Vec<models::PayoutShare>>,
+    // Royalties held back from a sale until it completes, keyed by bid id.
+    pub pending_royalties: IterableMap<u64, Vec<(AccountId, u128)>>,
+    // Properties listed through nft_transfer_call or nft_approve, keyed by
+    // property id.
+    pub external_listings: IterableMap<u64, models::ExternalListing>,
+    // Evidence logs for lease and bid disputes.
+    pub dispute_case_files: IterableMap<models::DisputeCase, models::CaseFile>,
+    // How long the other side of a dispute has to answer it with evidence.
+    pub dispute_response_period_ns: u64,
+    // What an owner raising a lease dispute claims from its damage deposit,
+    // keyed by lease id.
+    pub lease_dispute_claims: IterableMap<u64, u128>,
+    // Admin votes on lease disputes, keyed by lease id.
+    pub dispute_votes: IterableMap<u64, models::DisputeVoting>,
+    // How many admins must vote before a lease dispute resolves itself, the
+    // share of those votes a side needs, and how long voting stays open.
+    pub dispute_quorum: u32,
+    pub dispute_majority_bps: u16,
+    pub dispute_voting_period_ns: u64,
+    // How juror panels are staked, paid and run; arbitration is off until
+    // the contract owner sets them.
+    pub jury_terms: Option<models::JuryTerms>,
+    // Staked jurors, keyed by account.
+    pub jurors: IterableMap<AccountId, models::Juror>,
+    // Juror panels sitting on disputes.
+    pub arbitrations: IterableMap<models::DisputeCase, models::Arbitration>,
+    // Bond a tenant or bidder posts to raise a dispute, in the lease's or bid's
+    // stablecoin; zero means disputes are raised without one.
+    pub dispute_bond: u128,
+    // Whether a lost bond goes to the treasury rather than the other side.
+    pub dispute_bond_to_treasury: bool,
+    // Bonds held against open disputes.
+    pub dispute_bonds: IterableMap<models::DisputeCase, models::DisputeBond>,
+    // Subleases let from each lease, keyed by the primary lease id.
+    pub subleases_per_lease: IterableMap<u64, Vec<u64>>,
 }
 
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -245,6 +465,9 @@ impl NonFungibleTokenCore for ShedaContract {
                 env::panic_str("Cannot transfer property while it is on an active lease");
             }
         }
+        instalment::assert_no_lien(self, property_id);
+        building::assert_units_vacant(self, property_id);
+        listing::assert_not_in_custody(self, property_id);
         self.tokens
             .nft_transfer(receiver_id, token_id, approval_id, memo);
     }
@@ -268,6 +491,9 @@ impl NonFungibleTokenCore for ShedaContract {
                 env::panic_str("Cannot transfer property while it is on an active lease");
             }
         }
+        instalment::assert_no_lien(self, property_id);
+        building::assert_units_vacant(self, property_id);
+        listing::assert_not_in_custody(self, property_id);
         self.tokens
             .nft_transfer_call(receiver_id, token_id, approval_id, memo, msg)
     }
@@ -291,11 +517,17 @@ impl NonFungibleTokenApproval for ShedaContract {
 
     #[payable]
     fn nft_revoke(&mut self, token_id: TokenId, account_id: AccountId) {
+        if account_id == env::current_account_id() {
+            let property_id = token_id.parse::<u64>().expect("Invalid token ID");
+            listing::on_approval_revoked(self, property_id);
+        }
         self.tokens.nft_revoke(token_id, account_id)
     }
 
     #[payable]
     fn nft_revoke_all(&mut self, token_id: TokenId) {
+        let property_id = token_id.parse::<u64>().expect("Invalid token ID");
+        listing::on_approval_revoked(self, property_id);
         self.tokens.nft_revoke_all(token_id)
     }
 
@@ -310,6 +542,26 @@ impl NonFungibleTokenApproval for ShedaContract {
     }
 }
 
+// Returns a token sent with nft_transfer_call that the receiver didn't keep.
+#[near]
+impl NonFungibleTokenResolver for ShedaContract {
+    #[private]
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: Option<std::collections::HashMap<AccountId, u64>>,
+    ) -> bool {
+        self.tokens.nft_resolve_transfer(
+            previous_owner_id,
+            receiver_id,
+            token_id,
+            approved_account_ids,
+        )
+    }
+}
+
 #[near]
 impl NonFungibleTokenEnumeration for ShedaContract {
     fn nft_total_supply(&self) -> U128 {
@@ -437,7 +689,63 @@ impl ShedaContract {
             upgrade_delay_ns: 0,
             pending_upgrade_code: None,
             pending_upgrade_at: None,
-            version: 4,
+            version: 5,
+            auctions: IterableMap::new(AUCTIONS_PREFIX.to_vec()),
+            sealed_commits: IterableMap::new(SEALED_COMMITS_PREFIX.to_vec()),
+            negotiations: IterableMap::new(NEGOTIATIONS_PREFIX.to_vec()),
+            earnest_deposits: IterableMap::new(EARNEST_DEPOSITS_PREFIX.to_vec()),
+            earnest_min_deposit_bps: DEFAULT_EARNEST_MIN_DEPOSIT_BPS,
+            earnest_forfeit_bps: DEFAULT_EARNEST_FORFEIT_BPS,
+            balance_due_window_ns: DEFAULT_BALANCE_DUE_WINDOW_NS,
+            instalment_plans: IterableMap::new(INSTALMENT_PLANS_PREFIX.to_vec()),
+            property_liens: IterableMap::new(PROPERTY_LIENS_PREFIX.to_vec()),
+            instalment_grace_period_ns: DEFAULT_INSTALMENT_GRACE_PERIOD_NS,
+            rent_terms: IterableMap::new(RENT_TERMS_PREFIX.to_vec()),
+            rent_schedules: IterableMap::new(RENT_SCHEDULES_PREFIX.to_vec()),
+            damage_deposit_terms: IterableMap::new(DAMAGE_DEPOSIT_TERMS_PREFIX.to_vec()),
+            bid_damage_deposits: IterableMap::new(BID_DAMAGE_DEPOSITS_PREFIX.to_vec()),
+            damage_deposits: IterableMap::new(DAMAGE_DEPOSITS_PREFIX.to_vec()),
+            deposit_claim_window_ns: DEFAULT_DEPOSIT_CLAIM_WINDOW_NS,
+            lease_terminations: IterableMap::new(LEASE_TERMINATIONS_PREFIX.to_vec()),
+            early_termination_penalty_bps: DEFAULT_EARLY_TERMINATION_PENALTY_BPS,
+            lease_assignments: IterableMap::new(LEASE_ASSIGNMENTS_PREFIX.to_vec()),
+            sublease_offers: IterableMap::new(SUBLEASE_OFFERS_PREFIX.to_vec()),
+            subleases: IterableMap::new(SUBLEASES_PREFIX.to_vec()),
+            co_bidders: IterableMap::new(CO_BIDDERS_PREFIX.to_vec()),
+            lease_co_tenants: IterableMap::new(LEASE_CO_TENANTS_PREFIX.to_vec()),
+            property_co_owners: IterableMap::new(PROPERTY_CO_OWNERS_PREFIX.to_vec()),
+            auto_renewals: IterableMap::new(AUTO_RENEWALS_PREFIX.to_vec()),
+            booking_terms: IterableMap::new(BOOKING_TERMS_PREFIX.to_vec()),
+            bookings: IterableMap::new(BOOKINGS_PREFIX.to_vec()),
+            property_bookings: IterableMap::new(PROPERTY_BOOKINGS_PREFIX.to_vec()),
+            booking_counter: 0,
+            building_units: IterableMap::new(BUILDING_UNITS_PREFIX.to_vec()),
+            unit_building: IterableMap::new(UNIT_BUILDING_PREFIX.to_vec()),
+            fractional_properties: IterableMap::new(FRACTIONAL_PROPERTIES_PREFIX.to_vec()),
+            share_holdings: IterableMap::new(SHARE_HOLDINGS_PREFIX.to_vec()),
+            payout_splits: IterableMap::new(PAYOUT_SPLITS_PREFIX.to_vec()),
+            claimable_balances: IterableMap::new(CLAIMABLE_BALANCES_PREFIX.to_vec()),
+            platform_fees: IterableMap::new(PLATFORM_FEES_PREFIX.to_vec()),
+            listing_brokers: IterableMap::new(LISTING_BROKERS_PREFIX.to_vec()),
+            accrued_fees: IterableMap::new(ACCRUED_FEES_PREFIX.to_vec()),
+            pending_fees: IterableMap::new(PENDING_FEES_PREFIX.to_vec()),
+            property_royalties: IterableMap::new(PROPERTY_ROYALTIES_PREFIX.to_vec()),
+            pending_royalties: IterableMap::new(PENDING_ROYALTIES_PREFIX.to_vec()),
+            external_listings: IterableMap::new(EXTERNAL_LISTINGS_PREFIX.to_vec()),
+            dispute_case_files: IterableMap::new(DISPUTE_CASE_FILES_PREFIX.to_vec()),
+            dispute_response_period_ns: DEFAULT_DISPUTE_RESPONSE_PERIOD_NS,
+            lease_dispute_claims: IterableMap::new(LEASE_DISPUTE_CLAIMS_PREFIX.to_vec()),
+            dispute_votes: IterableMap::new(DISPUTE_VOTES_PREFIX.to_vec()),
+            dispute_quorum: DEFAULT_DISPUTE_QUORUM,
+            dispute_majority_bps: DEFAULT_DISPUTE_MAJORITY_BPS,
+            dispute_voting_period_ns: DEFAULT_DISPUTE_VOTING_PERIOD_NS,
+            jury_terms: None,
+            jurors: IterableMap::new(JURORS_PREFIX.to_vec()),
+            arbitrations: IterableMap::new(ARBITRATIONS_PREFIX.to_vec()),
+            dispute_bond: 0,
+            dispute_bond_to_treasury: false,
+            dispute_bonds: IterableMap::new(DISPUTE_BONDS_PREFIX.to_vec()),
+            subleases_per_lease: IterableMap::new(SUBLEASES_PER_LEASE_PREFIX.to_vec()),
         };
         this.admins.insert(owner_id);
         for stablecoin in supported_stablecoins {
@@ -447,33 +755,62 @@ impl ShedaContract {
         this
     }
 
-    /// Upgrade hook to migrate state to v4.
+    /// Upgrade hook to migrate state to v5.
     ///
-    /// v4 adds the buyer-cancellation window fields and starts the bids map
-    /// clean under a new storage prefix.
+    /// v5 only appends collections to the contract struct; nothing v4 stored
+    /// changes shape. `Property`, `Bid` and `Lease` are deliberately left
+    /// alone — new per-property, per-bid and per-lease data lives in maps of
+    /// its own keyed by id — because changing a struct that already sits
+    /// inside a map is exactly the Borsh drift that forced the v4 migration to
+    /// abandon every stored bid. Here everything carries over.
     ///
-    /// # Why bids reset instead of carrying over
+    /// # From v3
     ///
-    /// The previous `migrate()` could never run. It iterated `old.bids`, and
-    /// `IterableMap::iter()` deserializes each value eagerly — so it panicked
-    /// with `Cannot deserialize element` on the first record written before
-    /// `Bid` gained `document_image_uri`/`document_description`. Borsh is
-    /// positional, so those bytes can't be read as the current `Bid`. The
-    /// migration meant to repair the corruption was blocked by the corruption.
+    /// State still on v3 is taken through the v4 migration first: the
+    /// buyer-cancellation window fields get their defaults and the bids map
+    /// starts clean under a new storage prefix.
     ///
-    /// Any migration that tries to preserve those records has to decide, per
-    /// entry, whether it is readable — and guessing wrong destroys live bids
-    /// and the escrow behind them. On testnet, where the existing records are
-    /// already written off, that risk buys nothing. So this reads none of them:
-    /// it never touches `old.bids` at all, which is the only approach that
-    /// cannot hit the panic. Bidders re-bid against a contract that works.
+    /// The v3 bids can't be carried over. Iterating `old.bids` deserializes
+    /// each value eagerly, and records written before `Bid` gained
+    /// `document_image_uri`/`document_description` can't be read as the
+    /// current `Bid` — Borsh is positional — so the iteration panics with
+    /// `Cannot deserialize element`. Deciding per entry which records are
+    /// readable risks destroying live bids and the escrow behind them, so the
+    /// migration reads none of them and never touches `old.bids` at all.
     ///
-    /// # This abandons escrow
+    /// This abandons escrow: dropped bids leave their stablecoins in the
+    /// contract with no record to refund against. `stable_coin_balances` still
+    /// counts them, so the owner can recover them via `emergency_withdraw`, but
+    /// bidders can't be paid out through the normal path. Reconcile those
+    /// balances after migrating.
     ///
-    /// Dropped bids leave their stablecoins in the contract with no record to
-    /// refund against. `stable_coin_balances` still counts them, so the owner
-    /// can recover them via `emergency_withdraw`, but bidders can't be paid out
-    /// through the normal path. Reconcile those balances after migrating.
+    /// New in v5:
+    /// - `auctions`
+    /// - `sealed_commits`
+    /// - `negotiations`
+    /// - `earnest_deposits`
+    /// - `earnest_min_deposit_bps`, `earnest_forfeit_bps`, `balance_due_window_ns`
+    /// - `instalment_plans`, `property_liens`, `instalment_grace_period_ns`
+    /// - `rent_terms`, `rent_schedules`
+    /// - `damage_deposit_terms`, `bid_damage_deposits`, `damage_deposits`,
+    ///   `deposit_claim_window_ns`
+    /// - `lease_terminations`, `early_termination_penalty_bps`
+    /// - `lease_assignments`, `sublease_offers`, `subleases`, `subleases_per_lease`
+    /// - `co_bidders`, `lease_co_tenants`, `property_co_owners`
+    /// - `auto_renewals`
+    /// - `booking_terms`, `bookings`, `property_bookings`, `booking_counter`
+    /// - `building_units`, `unit_building`
+    /// - `fractional_properties`, `share_holdings`
+    /// - `payout_splits`, `claimable_balances`
+    /// - `platform_fees`, `listing_brokers`, `accrued_fees`, `pending_fees`
+    /// - `property_royalties`, `pending_royalties`
+    /// - `external_listings`
+    /// - `dispute_case_files`, `dispute_response_period_ns`
+    /// - `lease_dispute_claims`
+    /// - `dispute_votes`, `dispute_quorum`, `dispute_majority_bps`,
+    ///   `dispute_voting_period_ns`
+    /// - `jury_terms`, `jurors`, `arbitrations`
+    /// - `dispute_bond`, `dispute_bond_to_treasury`, `dispute_bonds`
     #[init(ignore_state)]
     #[private]
     pub fn migrate() -> Self {
@@ -484,6 +821,8 @@ impl ShedaContract {
             pub tokens: NonFungibleToken,
             pub metadata: LazyOption<NFTContractMetadata>,
             pub properties: IterableMap<u64, Property>,
+            // Never read; see below.
+            #[allow(dead_code)]
             pub bids: IterableMap<u64, Vec<Bid>>,
             pub leases: IterableMap<u64, Lease>,
             pub property_counter: u64,
@@ -510,35 +849,120 @@ impl ShedaContract {
             pub version: u32,
         }
 
-        let old: OldStateV3 = env::state_read().expect("Old state does not exist");
-
-        // `old.bids` is never read — not iterated, not cleared. Reading it is
-        // the operation that panics, and `IterableMap::clear()` walks entries
-        // to drop them, so even discarding it properly would hit the same
-        // failure. It stays in storage, unreferenced, at the old prefix.
-        let migrated_bids = IterableMap::new(BIDS_V4_PREFIX.to_vec());
-        log!("migrate v4: bids reset under a new storage prefix");
-
-        let mut bid_expiry_ns = old.bid_expiry_ns;
-        if bid_expiry_ns == 0 {
-            bid_expiry_ns = 7 * 24 * 60 * 60 * 1_000_000_000;
-        }
-
-        let mut escrow_release_delay_ns = old.escrow_release_delay_ns;
-        if escrow_release_delay_ns == 0 {
-            escrow_release_delay_ns = 24 * 60 * 60 * 1_000_000_000;
+        // Mirrors the v4 layout exactly.
+        #[derive(BorshDeserialize)]
+        struct OldStateV4 {
+            pub tokens: NonFungibleToken,
+            pub metadata: LazyOption<NFTContractMetadata>,
+            pub properties: IterableMap<u64, Property>,
+            pub bids: IterableMap<u64, Vec<Bid>>,
+            pub leases: IterableMap<u64, Lease>,
+            pub property_counter: u64,
+            pub bid_counter: u64,
+            pub lease_counter: u64,
+            pub property_per_owner: IterableMap<AccountId, Vec<u64>>,
+            pub lease_per_tenant: IterableMap<AccountId, Vec<u64>>,
+            pub admins: IterableSet<AccountId>,
+            pub owner_id: AccountId,
+            pub accepted_stablecoin: Vec<AccountId>,
+            pub stable_coin_balances: IterableMap<AccountId, u128>,
+            pub reentrancy_locks: IterableSet<String>,
+            pub mock_transfers_enabled: bool,
+            pub bid_expiry_ns: u64,
+            pub escrow_release_delay_ns: u64,
+            pub lost_bid_claim_delay_ns: u64,
+            pub path_a_cancellation_window_ns: u64,
+            pub path_b_stage1_window_ns: u64,
+            pub path_b_stage2_window_ns: u64,
+            pub stalled_deal_timeout_ns: u64,
+            pub dispute_resolution_timelock_ns: u64,
+            pub lease_early_termination_window_ns: u64,
+            pub global_contract_code: Option<Vec<u8>>,
+            pub property_instances: IterableMap<u64, AccountId>,
+            pub oracle_account_id: Option<AccountId>,
+            pub oracle_request_nonce: u64,
+            pub upgrade_delay_ns: u64,
+            pub pending_upgrade_code: Option<Vec<u8>>,
+            pub pending_upgrade_at: Option<u64>,
+            pub version: u32,
         }
 
-        let mut lost_bid_claim_delay_ns = old.lost_bid_claim_delay_ns;
-        if lost_bid_claim_delay_ns == 0 {
-            lost_bid_claim_delay_ns = 24 * 60 * 60 * 1_000_000_000;
-        }
+        let state = env::storage_read(b"STATE").expect("Old state does not exist");
+        // Every layout ends with `version`, so it can be read before choosing
+        // which layout to decode.
+        let version = state
+            .len()
+            .checked_sub(4)
+            .and_then(|at| state[at..].try_into().ok())
+            .map(u32::from_le_bytes)
+            .unwrap_or_else(|| env::panic_str("Old state is too short"));
+
+        let old = if version < 4 {
+            let old = OldStateV3::try_from_slice(&state)
+                .unwrap_or_else(|_| env::panic_str("Cannot deserialize the v3 state"));
+
+            // `old.bids` is never read — not iterated, not cleared. Reading it
+            // is the operation that panics, and `IterableMap::clear()` walks
+            // entries to drop them, so even discarding it properly would hit
+            // the same failure. It stays in storage, unreferenced, at the old
+            // prefix.
+            log!(
+                "migrate v4: v{} bids reset under a new storage prefix",
+                old.version
+            );
+            let or_day = |value: u64| if value == 0 { 24 * NS_PER_HOUR } else { value };
+
+            OldStateV4 {
+                tokens: old.tokens,
+                metadata: old.metadata,
+                properties: old.properties,
+                bids: IterableMap::new(BIDS_V4_PREFIX.to_vec()),
+                leases: old.leases,
+                property_counter: old.property_counter,
+                bid_counter: old.bid_counter,
+                lease_counter: old.lease_counter,
+                property_per_owner: old.property_per_owner,
+                lease_per_tenant: old.lease_per_tenant,
+                admins: old.admins,
+                owner_id: old.owner_id,
+                accepted_stablecoin: old.accepted_stablecoin,
+                stable_coin_balances: old.stable_coin_balances,
+                reentrancy_locks: old.reentrancy_locks,
+                mock_transfers_enabled: old.mock_transfers_enabled,
+                bid_expiry_ns: if old.bid_expiry_ns == 0 {
+                    7 * 24 * NS_PER_HOUR
+                } else {
+                    old.bid_expiry_ns
+                },
+                escrow_release_delay_ns: or_day(old.escrow_release_delay_ns),
+                lost_bid_claim_delay_ns: or_day(old.lost_bid_claim_delay_ns),
+                // New in v4 — no prior value to carry over.
+                path_a_cancellation_window_ns: DEFAULT_PATH_A_CANCELLATION_WINDOW_NS,
+                path_b_stage1_window_ns: DEFAULT_PATH_B_STAGE1_WINDOW_NS,
+                path_b_stage2_window_ns: DEFAULT_PATH_B_STAGE2_WINDOW_NS,
+                stalled_deal_timeout_ns: DEFAULT_STALLED_DEAL_TIMEOUT_NS,
+                dispute_resolution_timelock_ns: DEFAULT_DISPUTE_RESOLUTION_TIMELOCK_NS,
+                lease_early_termination_window_ns: DEFAULT_LEASE_EARLY_TERMINATION_WINDOW_NS,
+                global_contract_code: old.global_contract_code,
+                property_instances: old.property_instances,
+                oracle_account_id: old.oracle_account_id,
+                oracle_request_nonce: old.oracle_request_nonce,
+                upgrade_delay_ns: old.upgrade_delay_ns,
+                pending_upgrade_code: old.pending_upgrade_code,
+                pending_upgrade_at: old.pending_upgrade_at,
+                version: 4,
+            }
+        } else {
+            OldStateV4::try_from_slice(&state)
+                .unwrap_or_else(|_| env::panic_str("Cannot deserialize the v4 state"))
+        };
+        log!("migrate v5: v{} state carried over", old.version);
 
         Self {
             tokens: old.tokens,
             metadata: old.metadata,
             properties: old.properties,
-            bids: migrated_bids,
+            bids: old.bids,
             leases: old.leases,
             property_counter: old.property_counter,
             bid_counter: old.bid_counter,
@@ -551,16 +975,15 @@ impl ShedaContract {
             stable_coin_balances: old.stable_coin_balances,
             reentrancy_locks: old.reentrancy_locks,
             mock_transfers_enabled: old.mock_transfers_enabled,
-            bid_expiry_ns,
-            escrow_release_delay_ns,
-            lost_bid_claim_delay_ns,
-            // New in v4 — no prior value to carry over.
-            path_a_cancellation_window_ns: DEFAULT_PATH_A_CANCELLATION_WINDOW_NS,
-            path_b_stage1_window_ns: DEFAULT_PATH_B_STAGE1_WINDOW_NS,
-            path_b_stage2_window_ns: DEFAULT_PATH_B_STAGE2_WINDOW_NS,
-            stalled_deal_timeout_ns: DEFAULT_STALLED_DEAL_TIMEOUT_NS,
-            dispute_resolution_timelock_ns: DEFAULT_DISPUTE_RESOLUTION_TIMELOCK_NS,
-            lease_early_termination_window_ns: DEFAULT_LEASE_EARLY_TERMINATION_WINDOW_NS,
+            bid_expiry_ns: old.bid_expiry_ns,
+            escrow_release_delay_ns: old.escrow_release_delay_ns,
+            lost_bid_claim_delay_ns: old.lost_bid_claim_delay_ns,
+            path_a_cancellation_window_ns: old.path_a_cancellation_window_ns,
+            path_b_stage1_window_ns: old.path_b_stage1_window_ns,
+            path_b_stage2_window_ns: old.path_b_stage2_window_ns,
+            stalled_deal_timeout_ns: old.stalled_deal_timeout_ns,
+            dispute_resolution_timelock_ns: old.dispute_resolution_timelock_ns,
+            lease_early_termination_window_ns: old.lease_early_termination_window_ns,
             global_contract_code: old.global_contract_code,
             property_instances: old.property_instances,
             oracle_account_id: old.oracle_account_id,
@@ -568,7 +991,64 @@ impl ShedaContract {
             upgrade_delay_ns: old.upgrade_delay_ns,
             pending_upgrade_code: old.pending_upgrade_code,
             pending_upgrade_at: old.pending_upgrade_at,
-            version: 4,
+            version: 5,
+            // New in v5 — start empty.
+            auctions: IterableMap::new(AUCTIONS_PREFIX.to_vec()),
+            sealed_commits: IterableMap::new(SEALED_COMMITS_PREFIX.to_vec()),
+            negotiations: IterableMap::new(NEGOTIATIONS_PREFIX.to_vec()),
+            earnest_deposits: IterableMap::new(EARNEST_DEPOSITS_PREFIX.to_vec()),
+            earnest_min_deposit_bps: DEFAULT_EARNEST_MIN_DEPOSIT_BPS,
+            earnest_forfeit_bps: DEFAULT_EARNEST_FORFEIT_BPS,
+            balance_due_window_ns: DEFAULT_BALANCE_DUE_WINDOW_NS,
+            instalment_plans: IterableMap::new(INSTALMENT_PLANS_PREFIX.to_vec()),
+            property_liens: IterableMap::new(PROPERTY_LIENS_PREFIX.to_vec()),
+            instalment_grace_period_ns: DEFAULT_INSTALMENT_GRACE_PERIOD_NS,
+            rent_terms: IterableMap::new(RENT_TERMS_PREFIX.to_vec()),
+            rent_schedules: IterableMap::new(RENT_SCHEDULES_PREFIX.to_vec()),
+            damage_deposit_terms: IterableMap::new(DAMAGE_DEPOSIT_TERMS_PREFIX.to_vec()),
+            bid_damage_deposits: IterableMap::new(BID_DAMAGE_DEPOSITS_PREFIX.to_vec()),
+            damage_deposits: IterableMap::new(DAMAGE_DEPOSITS_PREFIX.to_vec()),
+            deposit_claim_window_ns: DEFAULT_DEPOSIT_CLAIM_WINDOW_NS,
+            lease_terminations: IterableMap::new(LEASE_TERMINATIONS_PREFIX.to_vec()),
+            early_termination_penalty_bps: DEFAULT_EARLY_TERMINATION_PENALTY_BPS,
+            lease_assignments: IterableMap::new(LEASE_ASSIGNMENTS_PREFIX.to_vec()),
+            sublease_offers: IterableMap::new(SUBLEASE_OFFERS_PREFIX.to_vec()),
+            subleases: IterableMap::new(SUBLEASES_PREFIX.to_vec()),
+            co_bidders: IterableMap::new(CO_BIDDERS_PREFIX.to_vec()),
+            lease_co_tenants: IterableMap::new(LEASE_CO_TENANTS_PREFIX.to_vec()),
+            property_co_owners: IterableMap::new(PROPERTY_CO_OWNERS_PREFIX.to_vec()),
+            auto_renewals: IterableMap::new(AUTO_RENEWALS_PREFIX.to_vec()),
+            booking_terms: IterableMap::new(BOOKING_TERMS_PREFIX.to_vec()),
+            bookings: IterableMap::new(BOOKINGS_PREFIX.to_vec()),
+            property_bookings: IterableMap::new(PROPERTY_BOOKINGS_PREFIX.to_vec()),
+            booking_counter: 0,
+            building_units: IterableMap::new(BUILDING_UNITS_PREFIX.to_vec()),
+            unit_building: IterableMap::new(UNIT_BUILDING_PREFIX.to_vec()),
+            fractional_properties: IterableMap::new(FRACTIONAL_PROPERTIES_PREFIX.to_vec()),
+            share_holdings: IterableMap::new(SHARE_HOLDINGS_PREFIX.to_vec()),
+            payout_splits: IterableMap::new(PAYOUT_SPLITS_PREFIX.to_vec()),
+            claimable_balances: IterableMap::new(CLAIMABLE_BALANCES_PREFIX.to_vec()),
+            platform_fees: IterableMap::new(PLATFORM_FEES_PREFIX.to_vec()),
+            listing_brokers: IterableMap::new(LISTING_BROKERS_PREFIX.to_vec()),
+            accrued_fees: IterableMap::new(ACCRUED_FEES_PREFIX.to_vec()),
+            pending_fees: IterableMap::new(PENDING_FEES_PREFIX.to_vec()),
+            property_royalties: IterableMap::new(PROPERTY_ROYALTIES_PREFIX.to_vec()),
+            pending_royalties: IterableMap::new(PENDING_ROYALTIES_PREFIX.to_vec()),
+            external_listings: IterableMap::new(EXTERNAL_LISTINGS_PREFIX.to_vec()),
+            dispute_case_files: IterableMap::new(DISPUTE_CASE_FILES_PREFIX.to_vec()),
+            dispute_response_period_ns: DEFAULT_DISPUTE_RESPONSE_PERIOD_NS,
+            lease_dispute_claims: IterableMap::new(LEASE_DISPUTE_CLAIMS_PREFIX.to_vec()),
+            dispute_votes: IterableMap::new(DISPUTE_VOTES_PREFIX.to_vec()),
+            dispute_quorum: DEFAULT_DISPUTE_QUORUM,
+            dispute_majority_bps: DEFAULT_DISPUTE_MAJORITY_BPS,
+            dispute_voting_period_ns: DEFAULT_DISPUTE_VOTING_PERIOD_NS,
+            jury_terms: None,
+            jurors: IterableMap::new(JURORS_PREFIX.to_vec()),
+            arbitrations: IterableMap::new(ARBITRATIONS_PREFIX.to_vec()),
+            dispute_bond: 0,
+            dispute_bond_to_treasury: false,
+            dispute_bonds: IterableMap::new(DISPUTE_BONDS_PREFIX.to_vec()),
+            subleases_per_lease: IterableMap::new(SUBLEASES_PER_LEASE_PREFIX.to_vec()),
         }
     }
 
@@ -618,6 +1098,12 @@ impl ShedaContract {
             bid_expiry_ns: 7 * 24 * 60 * 60 * 1_000_000_000,
             escrow_release_delay_ns: 24 * 60 * 60 * 1_000_000_000,
             lost_bid_claim_delay_ns: 24 * 60 * 60 * 1_000_000_000,
+            path_a_cancellation_window_ns: DEFAULT_PATH_A_CANCELLATION_WINDOW_NS,
+            path_b_stage1_window_ns: DEFAULT_PATH_B_STAGE1_WINDOW_NS,
+            path_b_stage2_window_ns: DEFAULT_PATH_B_STAGE2_WINDOW_NS,
+            stalled_deal_timeout_ns: DEFAULT_STALLED_DEAL_TIMEOUT_NS,
+            dispute_resolution_timelock_ns: DEFAULT_DISPUTE_RESOLUTION_TIMELOCK_NS,
+            lease_early_termination_window_ns: DEFAULT_LEASE_EARLY_TERMINATION_WINDOW_NS,
             global_contract_code: None,
             property_instances: IterableMap::new(b"v2_pi".to_vec()),
             oracle_account_id: Some(owner_id.clone()),
@@ -625,7 +1111,63 @@ impl ShedaContract {
             upgrade_delay_ns: 0,
             pending_upgrade_code: None,
             pending_upgrade_at: None,
-            version: 4,
+            version: 5,
+            auctions: IterableMap::new(AUCTIONS_PREFIX.to_vec()),
+            sealed_commits: IterableMap::new(SEALED_COMMITS_PREFIX.to_vec()),
+            negotiations: IterableMap::new(NEGOTIATIONS_PREFIX.to_vec()),
+            earnest_deposits: IterableMap::new(EARNEST_DEPOSITS_PREFIX.to_vec()),
+            earnest_min_deposit_bps: DEFAULT_EARNEST_MIN_DEPOSIT_BPS,
+            earnest_forfeit_bps: DEFAULT_EARNEST_FORFEIT_BPS,
+            balance_due_window_ns: DEFAULT_BALANCE_DUE_WINDOW_NS,
+            instalment_plans: IterableMap::new(INSTALMENT_PLANS_PREFIX.to_vec()),
+            property_liens: IterableMap::new(PROPERTY_LIENS_PREFIX.to_vec()),
+            instalment_grace_period_ns: DEFAULT_INSTALMENT_GRACE_PERIOD_NS,
+            rent_terms: IterableMap::new(RENT_TERMS_PREFIX.to_vec()),
+            rent_schedules: IterableMap::new(RENT_SCHEDULES_PREFIX.to_vec()),
+            damage_deposit_terms: IterableMap::new(DAMAGE_DEPOSIT_TERMS_PREFIX.to_vec()),
+            bid_damage_deposits: IterableMap::new(BID_DAMAGE_DEPOSITS_PREFIX.to_vec()),
+            damage_deposits: IterableMap::new(DAMAGE_DEPOSITS_PREFIX.to_vec()),
+            deposit_claim_window_ns: DEFAULT_DEPOSIT_CLAIM_WINDOW_NS,
+            lease_terminations: IterableMap::new(LEASE_TERMINATIONS_PREFIX.to_vec()),
+            early_termination_penalty_bps: DEFAULT_EARLY_TERMINATION_PENALTY_BPS,
+            lease_assignments: IterableMap::new(LEASE_ASSIGNMENTS_PREFIX.to_vec()),
+            sublease_offers: IterableMap::new(SUBLEASE_OFFERS_PREFIX.to_vec()),
+            subleases: IterableMap::new(SUBLEASES_PREFIX.to_vec()),
+            co_bidders: IterableMap::new(CO_BIDDERS_PREFIX.to_vec()),
+            lease_co_tenants: IterableMap::new(LEASE_CO_TENANTS_PREFIX.to_vec()),
+            property_co_owners: IterableMap::new(PROPERTY_CO_OWNERS_PREFIX.to_vec()),
+            auto_renewals: IterableMap::new(AUTO_RENEWALS_PREFIX.to_vec()),
+            booking_terms: IterableMap::new(BOOKING_TERMS_PREFIX.to_vec()),
+            bookings: IterableMap::new(BOOKINGS_PREFIX.to_vec()),
+            property_bookings: IterableMap::new(PROPERTY_BOOKINGS_PREFIX.to_vec()),
+            booking_counter: 0,
+            building_units: IterableMap::new(BUILDING_UNITS_PREFIX.to_vec()),
+            unit_building: IterableMap::new(UNIT_BUILDING_PREFIX.to_vec()),
+            fractional_properties: IterableMap::new(FRACTIONAL_PROPERTIES_PREFIX.to_vec()),
+            share_holdings: IterableMap::new(SHARE_HOLDINGS_PREFIX.to_vec()),
+            payout_splits: IterableMap::new(PAYOUT_SPLITS_PREFIX.to_vec()),
+            claimable_balances: IterableMap::new(CLAIMABLE_BALANCES_PREFIX.to_vec()),
+            platform_fees: IterableMap::new(PLATFORM_FEES_PREFIX.to_vec()),
+            listing_brokers: IterableMap::new(LISTING_BROKERS_PREFIX.to_vec()),
+            accrued_fees: IterableMap::new(ACCRUED_FEES_PREFIX.to_vec()),
+            pending_fees: IterableMap::new(PENDING_FEES_PREFIX.to_vec()),
+            property_royalties: IterableMap::new(PROPERTY_ROYALTIES_PREFIX.to_vec()),
+            pending_royalties: IterableMap::new(PENDING_ROYALTIES_PREFIX.to_vec()),
+            external_listings: IterableMap::new(EXTERNAL_LISTINGS_PREFIX.to_vec()),
+            dispute_case_files: IterableMap::new(DISPUTE_CASE_FILES_PREFIX.to_vec()),
+            dispute_response_period_ns: DEFAULT_DISPUTE_RESPONSE_PERIOD_NS,
+            lease_dispute_claims: IterableMap::new(LEASE_DISPUTE_CLAIMS_PREFIX.to_vec()),
+            dispute_votes: IterableMap::new(DISPUTE_VOTES_PREFIX.to_vec()),
+            dispute_quorum: DEFAULT_DISPUTE_QUORUM,
+            dispute_majority_bps: DEFAULT_DISPUTE_MAJORITY_BPS,
+            dispute_voting_period_ns: DEFAULT_DISPUTE_VOTING_PERIOD_NS,
+            jury_terms: None,
+            jurors: IterableMap::new(JURORS_PREFIX.to_vec()),
+            arbitrations: IterableMap::new(ARBITRATIONS_PREFIX.to_vec()),
+            dispute_bond: 0,
+            dispute_bond_to_treasury: false,
+            dispute_bonds: IterableMap::new(DISPUTE_BONDS_PREFIX.to_vec()),
+            subleases_per_lease: IterableMap::new(SUBLEASES_PER_LEASE_PREFIX.to_vec()),
         };
         this.admins.insert(owner_id);
         for stablecoin in supported_stablecoins {
@@ -858,6 +1400,14 @@ impl ShedaContract {
 
     //NOTE Placing a Bid
     pub fn ft_on_transfer(&mut self, sender_id: AccountId, amount: U128, msg: String) -> U128 {
+        // Dispute bonds, juror stakes and arbitration fees aren't bids on a
+        // property.
+        if let Ok(dispute) = serde_json::from_str::<models::DisputeMsg>(&msg) {
+            return U128(bond::on_transfer(self, sender_id, amount.0, dispute));
+        }
+        if let Ok(arbitration) = serde_json::from_str::<models::ArbitrationMsg>(&msg) {
+            return U128(jury::on_transfer(self, sender_id, amount.0, arbitration));
+        }
         let bid_action: models::BidAction =
             serde_json::from_str::<models::BidAction>(&msg).expect("Invalid BidAction");
         let property_id = bid_action.property_id;
@@ -884,16 +1434,139 @@ impl ShedaContract {
         //assert the property is fo sale if action is sales and for lease if action is lease
         match bid_action.action {
             Action::Purchase => {
-                assert!(property.is_for_sale, "Property is not for sale");
+                // A buyout goes by the shares' reserve price, not a listing,
+                // and an instalment is owed on a sale already made — taking
+                // possession delists the property; the plan is checked instead.
+                assert!(
+                    property.is_for_sale
+                        || matches!(
+                            bid_action.kind,
+                            models::BidKind::BuyOutShares | models::BidKind::PayInstalment { .. }
+                        ),
+                    "Property is not for sale"
+                );
             }
             Action::Lease => {
+                // Short stays go by the booking terms, not the lease term.
                 assert!(
-                    property.lease_duration_months.is_some(),
+                    property.lease_duration_months.is_some()
+                        || matches!(bid_action.kind, models::BidKind::Book { .. }),
                     "Property is not for lease"
                 );
             }
         }
 
+        // A sealed commitment isn't a bid yet — it only becomes one if it wins
+        // the reveal.
+        if let models::BidKind::SealedCommit { commitment } = bid_action.kind {
+            require!(
+                matches!(bid_action.action, Action::Purchase),
+                "Sealed bids can only be placed on a purchase"
+            );
+            internal::lock_ft_on_transfer(self, property_id, &sender_id_guard);
+            auction::record_sealed_commit(self, property_id, sender_id, amount.0, commitment);
+            internal::unlock_ft_on_transfer(self, property_id, &sender_id_guard);
+            return U128(0);
+        }
+
+        if let models::BidKind::AcceptCounterOffer { bid_id } = bid_action.kind {
+            internal::lock_ft_on_transfer(self, property_id, &sender_id_guard);
+            let refund =
+                negotiation::accept_with_top_up(self, property_id, bid_id, sender_id, amount.0);
+            internal::unlock_ft_on_transfer(self, property_id, &sender_id_guard);
+            return U128(refund);
+        }
+
+        if let models::BidKind::TopUp { bid_id } = bid_action.kind {
+            internal::lock_ft_on_transfer(self, property_id, &sender_id_guard);
+            negotiation::top_up_bid(self, property_id, bid_id, sender_id, amount.0);
+            internal::unlock_ft_on_transfer(self, property_id, &sender_id_guard);
+            return U128(0);
+        }
+
+        if let models::BidKind::BuyOutShares = bid_action.kind {
+            internal::lock_ft_on_transfer(self, property_id, &sender_id_guard);
+            let refund = fraction::buy_out(self, property_id, sender_id, amount.0);
+            internal::unlock_ft_on_transfer(self, property_id, &sender_id_guard);
+            return U128(refund);
+        }
+
+        if let models::BidKind::CoFund { bid_id } = bid_action.kind {
+            internal::lock_ft_on_transfer(self, property_id, &sender_id_guard);
+            joint::co_fund(self, property_id, bid_id, sender_id, amount.0);
+            internal::unlock_ft_on_transfer(self, property_id, &sender_id_guard);
+            return U128(0);
+        }
+
+        if let models::BidKind::Book { check_in, nights } = bid_action.kind {
+            internal::lock_ft_on_transfer(self, property_id, &sender_id_guard);
+            let refund = booking::book(self, property_id, check_in, nights, sender_id, amount.0);
+            internal::unlock_ft_on_transfer(self, property_id, &sender_id_guard);
+            return U128(refund);
+        }
+
+        if let models::BidKind::PayAutoRenewal { lease_id } = bid_action.kind {
+            internal::lock_ft_on_transfer(self, property_id, &sender_id_guard);
+            let refund =
+                renewal::pay_auto_renewal(self, property_id, lease_id, sender_id, amount.0);
+            internal::unlock_ft_on_transfer(self, property_id, &sender_id_guard);
+            return U128(refund);
+        }
+
+        if let models::BidKind::PayBalance { bid_id } = bid_action.kind {
+            internal::lock_ft_on_transfer(self, property_id, &sender_id_guard);
+            let refund = earnest::pay_balance(self, property_id, bid_id, sender_id, amount.0);
+            internal::unlock_ft_on_transfer(self, property_id, &sender_id_guard);
+            return U128(refund);
+        }
+
+        if let models::BidKind::PayInstalment { bid_id } = bid_action.kind {
+            internal::lock_ft_on_transfer(self, property_id, &sender_id_guard);
+            let refund = instalment::pay_instalment(self, property_id, bid_id, sender_id, amount.0);
+            internal::unlock_ft_on_transfer(self, property_id, &sender_id_guard);
+            return U128(refund);
+        }
+
+        if let models::BidKind::PayRent { lease_id } = bid_action.kind {
+            internal::lock_ft_on_transfer(self, property_id, &sender_id_guard);
+            let refund = rent::pay_rent(self, property_id, lease_id, sender_id, amount.0);
+            internal::unlock_ft_on_transfer(self, property_id, &sender_id_guard);
+            return U128(refund);
+        }
+
+        if let models::BidKind::PayTerminationRefund { lease_id } = bid_action.kind {
+            internal::lock_ft_on_transfer(self, property_id, &sender_id_guard);
+            let refund = termination::pay_termination_refund(
+                self,
+                property_id,
+                lease_id,
+                sender_id,
+                amount.0,
+            );
+            internal::unlock_ft_on_transfer(self, property_id, &sender_id_guard);
+            return U128(refund);
+        }
+
+        // The buyer under a lien holds the NFT; nobody else can be sold it.
+        instalment::assert_no_lien(self, property_id);
+
+        let earnest_offer = match bid_action.kind {
+            models::BidKind::EarnestMoney { offer_price } => Some(offer_price.0),
+            _ => None,
+        };
+        let instalment_terms = match bid_action.kind {
+            models::BidKind::Instalments {
+                price,
+                instalment_count,
+                interval_ns,
+            } => Some((price.0, instalment_count, interval_ns)),
+            _ => None,
+        };
+        let damage_deposit = match bid_action.kind {
+            models::BidKind::LeaseWithDeposit { damage_deposit } => Some(damage_deposit.0),
+            _ => None,
+        };
+
         // Amount matches, create the bid
         let bid_id = self.bid_counter;
         self.bid_counter = Self::checked_add_u64(self.bid_counter, 1, "bid_counter");
@@ -952,9 +1625,30 @@ impl ShedaContract {
             Self::checked_add_u128(current_balance, amount.0, "bid deposit"),
         );
 
-        // Returning 0 means: keep all tokens, no refund
+        if let Some(offer_price) = earnest_offer {
+            earnest::record_earnest_bid(self, property_id, bid_id, offer_price);
+        }
+        if let Some((price, instalment_count, interval_ns)) = instalment_terms {
+            instalment::record_instalment_bid(
+                self,
+                property_id,
+                bid_id,
+                price,
+                instalment_count,
+                interval_ns,
+            );
+        }
+        if let Some(damage_deposit) = damage_deposit {
+            deposit::record_deposit_bid(self, property_id, bid_id, damage_deposit);
+        }
+
+        // If the property is under auction, hold the bid to the auction's rules
+        // and refund whoever it outbid. A Dutch purchase may hand some back.
+        let refund = auction::on_bid_placed(self, property_id, bid_id);
+
+        // Whatever is returned goes back to the sender; 0 keeps everything
         internal::unlock_ft_on_transfer(self, property_id, &sender_id_guard);
-        U128(0)
+        U128(refund)
     }
 
     #[payable]
@@ -1083,34 +1777,16 @@ impl ShedaContract {
     ) {
         assert_one_yocto();
 
-        let mut property = self
-            .properties
-            .get(&property_id)
-            .expect("Property not found")
-            .clone();
-
-        assert_eq!(
-            property.owner_id,
-            env::predecessor_account_id(),
-            "Only the property owner can update the listing"
-        );
-
-        // An occupied property isn't the owner's to re-offer until the lease
-        // ends — same guard delist/delete already apply.
-        assert!(
-            property.active_lease.is_none(),
-            "Cannot update the listing while the property has an active lease"
-        );
-
-        assert!(
-            !is_for_sale || price.0 > 0,
-            "A listed property must have a price above zero"
+        listing::list(
+            self,
+            property_id,
+            &env::predecessor_account_id(),
+            &models::ListingMsg {
+                price,
+                is_for_sale,
+                lease_duration_months,
+            },
         );
-
-        property.price = price.0;
-        property.is_for_sale = is_for_sale;
-        property.lease_duration_months = lease_duration_months;
-        self.properties.insert(property_id, property);
     }
 
     #[payable]
@@ -1119,15 +1795,61 @@ impl ShedaContract {
     }
 
     pub fn raise_lease_dispute(&mut self, lease_id: u64) {
-        internal_raise_dispute(self, lease_id, "".to_string());
+        self.raise_lease_dispute_with_reason(lease_id, "".to_string());
     }
 
+    /// Tenant disputes their lease. While a dispute bond is required the
+    /// dispute is raised through `ft_transfer_call` instead, with a
+    /// `DisputeMsg::RaiseLeaseDispute` message.
     pub fn raise_lease_dispute_with_reason(&mut self, lease_id: u64, reason: String) {
-        internal_raise_dispute(self, lease_id, reason);
+        bond::assert_no_bond_required(self);
+        internal_raise_dispute(self, env::predecessor_account_id(), lease_id, reason, 0);
     }
 
+    /// Landlord raises a dispute on a lease, claiming `claim` — nothing, or
+    /// up to the damage deposit held plus rent in arrears; only the deposit
+    /// is paid out. It waits on the tenant: see `contest_lease_dispute` and
+    /// `settle_uncontested_lease_dispute`. While a dispute bond is required it
+    /// is raised with a `DisputeMsg::RaiseOwnerLeaseDispute` instead.
+    pub fn raise_owner_lease_dispute(&mut self, lease_id: u64, reason: String, claim: U128) {
+        bond::assert_no_bond_required(self);
+        internal::internal_raise_owner_dispute(
+            self,
+            env::predecessor_account_id(),
+            lease_id,
+            reason,
+            claim.0,
+            0,
+        );
+    }
+
+    pub fn contest_lease_dispute(&mut self, lease_id: u64) {
+        internal::internal_contest_owner_dispute(self, lease_id);
+    }
+
+    pub fn settle_uncontested_lease_dispute(&mut self, lease_id: u64) -> U128 {
+        internal::internal_settle_uncontested_dispute(self, lease_id)
+    }
+
+    pub fn get_lease_dispute_claim(&self, lease_id: u64) -> Option<U128> {
+        self.lease_dispute_claims
+            .get(&lease_id)
+            .map(|claim| U128(*claim))
+    }
+
+    /// Bidder or owner disputes an accepted bid. While a dispute bond is
+    /// required the dispute is raised through `ft_transfer_call` instead,
+    /// with a `DisputeMsg::RaiseBidDispute` message.
     pub fn raise_dispute(&mut self, bid_id: u64, property_id: u64, reason: String) -> bool {
-        internal::internal_raise_bid_dispute(self, property_id, bid_id, reason)
+        bond::assert_no_bond_required(self);
+        internal::internal_raise_bid_dispute(
+            self,
+            env::predecessor_account_id(),
+            property_id,
+            bid_id,
+            reason,
+            0,
+        )
     }
 
     pub fn expire_lease(&mut self, lease_id: u64) {
@@ -1213,6 +1935,11 @@ impl ShedaContract {
             .collect();
 
         for lease_id in expired_ids {
+            // Ending a lease ends any sublease under it, which may be later
+            // in the list.
+            if !self.leases.get(&lease_id).is_some_and(|lease| lease.active) {
+                continue;
+            }
             internal::internal_expire_lease(self, lease_id);
         }
 
@@ -1304,6 +2031,9 @@ impl ShedaContract {
 
         internal::lock_bid(self, property_id, bid_id);
 
+        // Co-bidders get back what they put in; the rest is the bidder's.
+        let bid = crate::joint::refund_co_bidders(self, property_id, bid_id);
+
         // Refund the bid amount
         let promise = crate::ext::ft_contract::ext(bid.stablecoin_token.clone())
             .with_attached_deposit(near_sdk::NearToken::from_yoctonear(1))
@@ -0,0 +1,247 @@
+use near_contract_standards::non_fungible_token::{
+    approval::NonFungibleTokenApprovalReceiver, core::NonFungibleTokenReceiver,
+};
+use near_sdk::{
+    assert_one_yocto, env, log, near, near_bindgen, require, AccountId, PromiseOrValue,
+};
+
+use crate::{
+    events::{emit_event, PropertyListedEvent},
+    internal::assert_no_blocking_bids,
+    models::{ExternalListing, ListingMsg},
+    ShedaContract, ShedaContractExt, TokenId,
+};
+
+/// Whether the contract holds the property's NFT for a listing its owner
+/// transferred in.
+pub(crate) fn in_custody(contract: &ShedaContract, property_id: u64) -> bool {
+    matches!(
+        contract.external_listings.get(&property_id),
+        Some(ExternalListing::Custody)
+    )
+}
+
+/// Called wherever the owner is about to move the NFT themselves.
+pub(crate) fn assert_not_in_custody(contract: &ShedaContract, property_id: u64) {
+    require!(
+        !in_custody(contract, property_id),
+        "The contract holds this property's NFT; reclaim it from the listing first"
+    );
+}
+
+/// List `property_id` on `terms` for `owner_id`, who must own it. Shared by
+/// `update_listing` and the NFT receivers.
+pub(crate) fn list(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    owner_id: &AccountId,
+    terms: &ListingMsg,
+) {
+    let property = contract
+        .properties
+        .get(&property_id)
+        .expect("Property not found");
+
+    assert_eq!(
+        property.owner_id, *owner_id,
+        "Only the property owner can update the listing"
+    );
+
+    // An occupied property isn't the owner's to re-offer until the lease
+    // ends — same guard delist/delete already apply.
+    assert!(
+        property.active_lease.is_none(),
+        "Cannot update the listing while the property has an active lease"
+    );
+
+    // The auction's reserve and bids were made against this listing.
+    crate::auction::assert_no_open_auction(contract, property_id);
+    crate::instalment::assert_no_lien(contract, property_id);
+
+    assert!(
+        !terms.is_for_sale || terms.price.0 > 0,
+        "A listed property must have a price above zero"
+    );
+
+    if let Some(property) = contract.properties.get_mut(&property_id) {
+        property.price = terms.price.0;
+        property.is_for_sale = terms.is_for_sale;
+        property.lease_duration_months = terms.lease_duration_months;
+    }
+}
+
+/// Check a receiver call came from this contract's own NFT, for a property
+/// `owner_id` owns, and parse its listing terms.
+fn listing_from_token(
+    contract: &ShedaContract,
+    token_id: &TokenId,
+    owner_id: &AccountId,
+    msg: &str,
+) -> (u64, ListingMsg) {
+    require!(
+        env::predecessor_account_id() == env::current_account_id(),
+        "Only this contract's property NFTs can be listed"
+    );
+    let property_id = token_id.parse::<u64>().expect("Invalid token ID");
+    let property = contract
+        .properties
+        .get(&property_id)
+        .expect("Property not found");
+    require!(
+        property.owner_id == *owner_id,
+        "Only the property owner can list it"
+    );
+    require!(
+        !crate::fraction::is_fractionalised(contract, property_id),
+        "A fractionalised property is listed by its shareholders"
+    );
+    let terms = near_sdk::serde_json::from_str::<ListingMsg>(msg).expect("Invalid ListingMsg");
+    (property_id, terms)
+}
+
+fn record(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    owner_id: AccountId,
+    terms: ListingMsg,
+    listing: ExternalListing,
+) {
+    list(contract, property_id, &owner_id, &terms);
+    emit_event(
+        "PropertyListed",
+        PropertyListedEvent {
+            token_id: property_id,
+            owner_id,
+            price: terms.price.0,
+            is_for_sale: terms.is_for_sale,
+            lease_duration_months: terms.lease_duration_months,
+            custody: listing == ExternalListing::Custody,
+        },
+    );
+    contract.external_listings.insert(property_id, listing);
+}
+
+/// The owner withdrew the contract's approval: a listing made by approving
+/// the contract comes down with it. Called from `nft_revoke` and
+/// `nft_revoke_all`.
+pub(crate) fn on_approval_revoked(contract: &mut ShedaContract, property_id: u64) {
+    if !matches!(
+        contract.external_listings.get(&property_id),
+        Some(ExternalListing::Approval { .. })
+    ) {
+        return;
+    }
+    assert_no_blocking_bids(contract, property_id, "revoke the listing's approval");
+    contract.external_listings.remove(&property_id);
+    if let Some(property) = contract.properties.get_mut(&property_id) {
+        property.is_for_sale = false;
+    }
+    log!(
+        "Listing of property {} withdrawn with the contract's approval",
+        property_id
+    );
+}
+
+/// NEP-171: an owner transferring their property NFT here with a
+/// `ListingMsg` lists it, and the contract holds the NFT until it's sold or
+/// reclaimed.
+#[near]
+impl NonFungibleTokenReceiver for ShedaContract {
+    fn nft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_id: AccountId,
+        token_id: TokenId,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        let (property_id, terms) = listing_from_token(self, &token_id, &previous_owner_id, &msg);
+        record(
+            self,
+            property_id,
+            previous_owner_id,
+            terms,
+            ExternalListing::Custody,
+        );
+        log!(
+            "Property {} transferred in by {} and listed",
+            property_id,
+            sender_id
+        );
+        PromiseOrValue::Value(false)
+    }
+}
+
+/// NEP-178: an owner approving the contract with a `ListingMsg` lists the
+/// property and keeps the NFT; the contract transfers it on their behalf
+/// when a bid goes through.
+#[near]
+impl NonFungibleTokenApprovalReceiver for ShedaContract {
+    fn nft_on_approve(
+        &mut self,
+        token_id: TokenId,
+        owner_id: AccountId,
+        approval_id: u64,
+        msg: String,
+    ) -> PromiseOrValue<String> {
+        let (property_id, terms) = listing_from_token(self, &token_id, &owner_id, &msg);
+        record(
+            self,
+            property_id,
+            owner_id,
+            terms,
+            ExternalListing::Approval { approval_id },
+        );
+        PromiseOrValue::Value("listed".to_string())
+    }
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Owner takes back an NFT they transferred in to list, and the listing
+    /// comes down with it.
+    #[payable]
+    pub fn reclaim_listed_nft(&mut self, property_id: u64) {
+        assert_one_yocto();
+
+        let owner_id = self
+            .properties
+            .get(&property_id)
+            .expect("Property not found")
+            .owner_id
+            .clone();
+        assert_eq!(
+            owner_id,
+            env::predecessor_account_id(),
+            "Only the property owner can reclaim the NFT"
+        );
+        require!(
+            in_custody(self, property_id),
+            "The contract doesn't hold this property's NFT"
+        );
+        require!(
+            self.properties
+                .get(&property_id)
+                .is_some_and(|property| property.active_lease.is_none()),
+            "Cannot reclaim the NFT while the property has an active lease"
+        );
+        assert_no_blocking_bids(self, property_id, "reclaim the NFT");
+        crate::auction::assert_no_open_auction(self, property_id);
+
+        self.tokens.internal_transfer(
+            &env::current_account_id(),
+            &owner_id,
+            &property_id.to_string(),
+            None,
+            None,
+        );
+        self.external_listings.remove(&property_id);
+        if let Some(property) = self.properties.get_mut(&property_id) {
+            property.is_for_sale = false;
+        }
+    }
+
+    /// How the property was listed from outside, if it was.
+    pub fn get_external_listing(&self, property_id: u64) -> Option<ExternalListing> {
+        self.external_listings.get(&property_id).cloned()
+    }
+}
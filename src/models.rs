@@ -1,5 +1,6 @@
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
     serde::{Deserialize, Serialize},
     AccountId, Timestamp,
 };
@@ -50,6 +51,8 @@ pub struct DisputeInfoView {
     pub oracle_updated_at: Option<Timestamp>,
     pub resolved_by: Option<String>,
     pub resolved_at: Option<Timestamp>,
+    /// The bond still held against the dispute, in the lease's stablecoin.
+    pub bond: Option<String>, // u128 as string for JSON
 }
 
 #[derive(
@@ -124,9 +127,100 @@ pub struct BidAction {
     pub property_id: u64,
     pub action: Action,
     pub stablecoin_token: AccountId,
+    /// What the transfer funds. Omitted by every client that predates it,
+    /// which is why it defaults to an ordinary bid.
+    #[serde(default)]
+    pub kind: BidKind,
 }
 
-#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, JsonSchema)]
+/// The different things an `ft_transfer_call` into the marketplace can pay for.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Debug, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub enum BidKind {
+    /// A plain, visible bid.
+    #[default]
+    Place,
+    /// A sealed-bid commitment: the deposit is the transferred amount, and
+    /// `commitment` is the hex sha256 of `"{bidder}:{amount}:{salt}"`.
+    SealedCommit { commitment: String },
+    /// Tops up bid `bid_id` to the seller's open counter-offer and accepts it.
+    AcceptCounterOffer { bid_id: u64 },
+    /// Adds the transferred amount to the sender's pending bid `bid_id`.
+    TopUp { bid_id: u64 },
+    /// A purchase bid that only carries earnest money: the transferred amount
+    /// is the deposit, `offer_price` what the buyer will pay in total.
+    EarnestMoney { offer_price: U128 },
+    /// Pays the balance still due on earnest-money bid `bid_id`.
+    PayBalance { bid_id: u64 },
+    /// A purchase bid paid over time: the transferred amount is the down
+    /// payment, the rest of `price` falls due in `instalment_count` equal
+    /// instalments, one every `interval_ns`.
+    Instalments {
+        price: U128,
+        instalment_count: u32,
+        interval_ns: u64,
+    },
+    /// Pays the next instalment on instalment bid `bid_id`.
+    PayInstalment { bid_id: u64 },
+    /// Pays rent on lease `lease_id`, oldest unpaid period first.
+    PayRent { lease_id: u64 },
+    /// A lease bid that lodges a damage deposit: `damage_deposit` of the
+    /// transferred amount is held against the lease, the rest goes to the
+    /// owner as usual.
+    LeaseWithDeposit { damage_deposit: U128 },
+    /// The owner pays the refund due on an early termination of `lease_id`.
+    PayTerminationRefund { lease_id: u64 },
+    /// Adds the transferred amount to bid `bid_id` as an invited co-bidder.
+    CoFund { bid_id: u64 },
+    /// Pays for the next term of auto-renewing lease `lease_id`.
+    PayAutoRenewal { lease_id: u64 },
+    /// Books a short stay of `nights` nights from `check_in`.
+    Book { check_in: Timestamp, nights: u32 },
+    /// Buys out every other shareholder of a fractionalised property.
+    BuyOutShares,
+}
+
+/// The closing terms of an earnest-money bid.
+///
+/// The `Bid` itself holds whatever has actually been paid in — the deposit
+/// until the balance arrives, the full price after.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct EarnestMoney {
+    pub offer_price: u128,
+    pub deposit: u128,
+    /// Set when the buyer confirms the documents; the balance must be in by then.
+    pub balance_due_by: Option<Timestamp>,
+    pub balance_paid: bool,
+}
+
+/// One round of back-and-forth over a pending bid.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct CounterOffer {
+    pub round: u32,
+    pub proposer: AccountId,
+    pub amount: u128,
+    pub terms: Option<String>,
+    pub created_at: Timestamp,
+    pub status: OfferStatus,
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Debug, Clone, JsonSchema,
+)]
+pub enum OfferStatus {
+    /// Waiting on the other side.
+    Open,
+    /// Answered with a newer round.
+    Countered,
+    /// The bidder took the seller's offer; the bid moved into escrow at this amount.
+    Accepted,
+    /// The bidder turned the offer down; the bid stands at its original amount.
+    Declined,
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Debug, Clone, JsonSchema,
+)]
 #[serde(crate = "near_sdk::serde")]
 pub enum Action {
     Purchase,
@@ -160,6 +254,68 @@ pub enum DisputeResolution {
     Split,
 }
 
+/// A timed sale the contract runs on the seller's behalf.
+///
+/// While an auction is open the seller can't pick a bid by hand — the
+/// auction's rules decide the winner, and anyone can settle it once it ends.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct Auction {
+    pub property_id: u64,
+    pub seller_id: AccountId,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub mode: AuctionMode,
+    pub settled: bool,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug)]
+pub enum AuctionMode {
+    /// Ascending open bids. Each new bid must beat the current highest by at
+    /// least `min_increment`, and the bid it beats is refunded on the spot.
+    /// The sale only goes through if the winning bid reaches `reserve_price`.
+    English {
+        reserve_price: u128,
+        min_increment: u128,
+        highest_bid_id: Option<u64>,
+    },
+    /// Commit–reveal tender. Until `end_time` bidders only commit a hash and
+    /// a deposit; between `end_time` and `reveal_end` they reveal amount and
+    /// salt. The highest valid reveal at or above `reserve_price` wins.
+    Sealed {
+        reserve_price: u128,
+        reveal_end: Timestamp,
+        leading_commit_id: Option<u64>,
+    },
+    /// Descending price. The asking price falls from `start_price` to
+    /// `floor_price` between `start_time` and `end_time`, and the first bid
+    /// covering it wins. `use_escrow` sends the sale through the escrow flow
+    /// rather than paying the seller on the spot.
+    Dutch {
+        start_price: u128,
+        floor_price: u128,
+        use_escrow: bool,
+    },
+}
+
+/// One sealed bid: a hash and the deposit backing it.
+///
+/// The deposit is visible on chain, so it only bounds the bid from above —
+/// a bidder who wants to hide their number can over-fund and get the
+/// difference back. `id` comes from `bid_counter`, and the winning commit's
+/// `Bid` reuses it.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct SealedCommit {
+    pub id: u64,
+    pub property_id: u64,
+    pub bidder: AccountId,
+    pub commitment: String,
+    pub deposit: u128,
+    pub stablecoin_token: AccountId,
+    pub created_at: Timestamp,
+    pub revealed_amount: Option<u128>,
+    pub refunded: bool,
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
 pub struct Lease {
     pub id: u64,
@@ -300,6 +456,15 @@ impl Property {
 }
 
 impl Lease {
+    /// Whether a dispute on the lease is still waiting on the tenant or an
+    /// admin.
+    pub fn has_open_dispute(&self) -> bool {
+        matches!(
+            self.dispute_status,
+            DisputeStatus::Raised | DisputeStatus::PendingTenantResponse
+        )
+    }
+
     pub fn to_view(&self) -> LeaseView {
         LeaseView {
             id: self.id,
@@ -329,6 +494,7 @@ impl From<&DisputeInfo> for DisputeInfoView {
             oracle_updated_at: info.oracle_updated_at,
             resolved_by: info.resolved_by.as_ref().map(|id| id.to_string()),
             resolved_at: info.resolved_at,
+            bond: None,
         }
     }
 }
@@ -377,3 +543,568 @@ impl Bid {
         }
     }
 }
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Debug, Clone, JsonSchema,
+)]
+pub enum InstalmentStatus {
+    /// Bid placed or in escrow; nothing owed yet.
+    Pending,
+    /// Down payment released, buyer holds the NFT under a lien.
+    Active,
+    Completed,
+    Defaulted,
+}
+
+/// The schedule of an instalment purchase.
+///
+/// The down payment goes through the ordinary escrow path. Releasing it hands
+/// the buyer the NFT but not the title: `Property.owner_id` stays the seller
+/// and the token can't be transferred on until the last instalment is in.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct InstalmentPlan {
+    pub price: u128,
+    pub down_payment: u128,
+    pub instalment_count: u32,
+    pub interval_ns: u64,
+    pub paid_instalments: u32,
+    pub next_due_at: Option<Timestamp>,
+    pub status: InstalmentStatus,
+}
+
+impl InstalmentPlan {
+    /// What the instalment at `index` (0-based) comes to. The last one takes
+    /// the rounding remainder.
+    pub fn instalment_amount(&self, index: u32) -> u128 {
+        let financed = self.price - self.down_payment;
+        let each = financed / u128::from(self.instalment_count);
+        if index + 1 == self.instalment_count {
+            financed - each * u128::from(self.instalment_count - 1)
+        } else {
+            each
+        }
+    }
+
+    /// Everything paid so far, down payment included.
+    pub fn amount_paid(&self) -> u128 {
+        (0..self.paid_instalments).fold(self.down_payment, |total, index| {
+            total + self.instalment_amount(index)
+        })
+    }
+}
+
+/// What a property is let for, set by the owner before it's leased.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct RentTerms {
+    pub amount: u128,
+    pub period_ns: u64,
+    /// Charged on a period paid more than `grace_period_ns` after it fell due,
+    /// as basis points of `amount`.
+    pub late_fee_bps: u16,
+    pub grace_period_ns: u64,
+}
+
+/// One rent payment, as recorded against its period.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct RentPayment {
+    pub period: u32,
+    pub due_at: Timestamp,
+    pub paid_at: Timestamp,
+    pub amount: u128,
+    pub late_fee: u128,
+}
+
+/// The rent owed on a lease. The payment made when the lease bid was accepted
+/// covers the opening period, so period 0 falls due one period after the
+/// lease starts; the schedule runs for as long as the lease does, renewals
+/// included.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct RentSchedule {
+    pub property_id: u64,
+    pub terms: RentTerms,
+    pub first_due_at: Timestamp,
+    pub payments: Vec<RentPayment>,
+}
+
+impl RentSchedule {
+    pub fn due_at(&self, period: u32) -> Timestamp {
+        self.first_due_at + self.terms.period_ns * u64::from(period)
+    }
+
+    /// The first period not paid yet.
+    pub fn next_period(&self) -> u32 {
+        self.payments.len() as u32
+    }
+
+    /// The late fee period `period` carries if it's paid at `paid_at`.
+    pub fn late_fee(&self, period: u32, paid_at: Timestamp) -> u128 {
+        if paid_at > self.due_at(period) + self.terms.grace_period_ns {
+            self.terms.amount * u128::from(self.terms.late_fee_bps) / 10_000
+        } else {
+            0
+        }
+    }
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Debug, Clone, JsonSchema,
+)]
+pub enum DamageDepositStatus {
+    /// The lease is running.
+    Held,
+    /// The lease has ended; the owner can file deductions until the deadline.
+    AwaitingDeductions,
+    /// Deductions are filed; the tenant can accept or dispute them until the
+    /// deadline, after which they stand.
+    DeductionsFiled,
+    /// The tenant disputed the deductions; the lease dispute decides them.
+    Disputed,
+    /// Paid out.
+    Settled,
+}
+
+/// One item the owner takes out of a damage deposit.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct DepositDeduction {
+    pub description: String,
+    pub amount: U128,
+}
+
+/// A tenant's damage deposit, held by the contract for the length of the
+/// lease and settled once it ends.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct DamageDeposit {
+    pub property_id: u64,
+    pub bid_id: u64,
+    pub tenant_id: AccountId,
+    pub stablecoin_token: AccountId,
+    pub amount: u128,
+    pub deductions: Vec<DepositDeduction>,
+    /// Closes whichever step the status is waiting on.
+    pub deadline: Option<Timestamp>,
+    pub status: DamageDepositStatus,
+    /// What went back to the tenant, once settled.
+    pub refunded: u128,
+}
+
+impl DamageDeposit {
+    pub fn total_deductions(&self) -> u128 {
+        self.deductions.iter().map(|d| d.amount.0).sum()
+    }
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Debug, Clone, JsonSchema,
+)]
+pub enum TerminationStatus {
+    /// Notice given; the lease ends at `effective_at`.
+    Noticed,
+    Completed,
+    Withdrawn,
+}
+
+/// Notice to end a lease before its term is up, and what it settles to.
+///
+/// The lease payment went to the owner when the lease started, so any refund
+/// is the owner's to pay: the unused share of the payment, less the penalty
+/// when the tenant gives notice and plus it when the owner does.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct LeaseTermination {
+    pub initiated_by: AccountId,
+    pub noticed_at: Timestamp,
+    pub effective_at: Timestamp,
+    /// The share of the lease payment covering the time after `effective_at`.
+    pub unused: u128,
+    pub penalty: u128,
+    pub refund_due: u128,
+    pub refund_paid: bool,
+    pub status: TerminationStatus,
+}
+
+/// A tenant handing the rest of their lease to someone else. Takes effect once
+/// the owner has consented and the new tenant has accepted.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct LeaseAssignment {
+    pub assignee: AccountId,
+    pub proposed_at: Timestamp,
+    pub owner_consented: bool,
+}
+
+/// A primary tenant's offer to sublet, waiting on the subtenant. The sublease
+/// runs from acceptance to `end_time`, inside the primary lease, and its rent
+/// (if any) is paid to the primary tenant.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct SubleaseOffer {
+    pub subtenant_id: AccountId,
+    pub end_time: Timestamp,
+    pub rent: Option<RentTerms>,
+    pub offered_at: Timestamp,
+}
+
+/// One party to a jointly funded bid, and to the lease or purchase it becomes.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct CoParty {
+    pub account_id: AccountId,
+    pub contributed: u128,
+    /// Their share of the lease or property, in basis points of everything
+    /// paid in. Fixed when the bid is accepted; zero until then.
+    pub share_bps: u16,
+    /// Paid back after the bid was cancelled, rejected or outbid, or after
+    /// they pulled out.
+    pub refunded: bool,
+}
+
+/// The co-bidders invited onto a bid. The bidder isn't among them; their part
+/// is whatever of the bid the others didn't put in.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct JointBid {
+    pub property_id: u64,
+    pub co_bidders: Vec<CoParty>,
+}
+
+/// How the price of an automatically renewed lease rises each term.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Escalation {
+    /// Basis points of the previous term's price.
+    Percentage { bps: u16 },
+    /// A flat amount on top of the previous term's price.
+    FixedAmount { amount: U128 },
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Debug, Clone, JsonSchema,
+)]
+pub enum AutoRenewalStatus {
+    /// The owner has set terms; waiting on the tenant to agree.
+    Offered,
+    /// The lease renews each term once the tenant pays, unless someone opts
+    /// out before the notice deadline.
+    Active,
+    OptedOut,
+}
+
+/// Automatic renewal terms on a lease.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct AutoRenewal {
+    pub escalation: Escalation,
+    /// How long before the lease ends either side must opt out by.
+    pub notice_period_ns: u64,
+    /// What the current term cost; the next costs this escalated.
+    pub term_price: u128,
+    pub status: AutoRenewalStatus,
+    pub opted_out_by: Option<AccountId>,
+    pub renewals: u32,
+    /// The renewal bid paid in and not yet settled, if any.
+    pub pending_bid_id: Option<u64>,
+}
+
+/// How much of a short-stay booking a guest gets back for cancelling. The
+/// owner cancelling always refunds in full.
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Debug, Clone, JsonSchema,
+)]
+pub enum CancellationPolicy {
+    /// Full refund up to a day before check-in, nothing after.
+    Flexible,
+    /// Full refund up to five days before check-in, half after.
+    Moderate,
+    /// Half refunded up to a week before check-in, nothing after.
+    Strict,
+}
+
+/// An owner's terms for short stays on a property.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct BookingTerms {
+    pub nightly_rate: u128,
+    /// Charged per full seven nights instead of the nightly rate, if set.
+    pub weekly_rate: Option<u128>,
+    pub min_nights: u32,
+    pub max_nights: u32,
+    pub cancellation_policy: CancellationPolicy,
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Debug, Clone, JsonSchema,
+)]
+pub enum BookingStatus {
+    /// Paid for; the payment is held until check-in.
+    Confirmed,
+    Cancelled,
+    /// Checked in, and the owner has been paid.
+    Completed,
+}
+
+/// A short stay. The nights run from `check_in` to `check_out`; the calendar
+/// never holds two live bookings that overlap.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct Booking {
+    pub property_id: u64,
+    pub guest_id: AccountId,
+    pub check_in: Timestamp,
+    pub check_out: Timestamp,
+    pub nights: u32,
+    pub amount: u128,
+    pub stablecoin_token: AccountId,
+    /// The policy in force when it was booked.
+    pub cancellation_policy: CancellationPolicy,
+    pub status: BookingStatus,
+    pub refunded: u128,
+    pub created_at: Timestamp,
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, PartialEq, Debug, Clone, JsonSchema,
+)]
+pub enum FractionalStatus {
+    /// The NFT is locked in the contract and its lease income is shared out.
+    Active,
+    /// Bought out or recombined; the NFT is back with a single owner.
+    Recombined,
+}
+
+/// A property split into a fixed supply of ownership shares. The owner who
+/// fractionalised it stays on as its manager, letting it as before.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct Fractional {
+    pub manager_id: AccountId,
+    pub total_shares: u128,
+    /// What buying out every share comes to; a buyout pays the others their
+    /// part of it.
+    pub reserve_price: u128,
+    /// Leases on the property are paid in this, and so is a buyout.
+    pub income_token: AccountId,
+    /// Lease income per share ever received, scaled by the contract's share
+    /// precision.
+    pub income_per_share: u128,
+    pub total_income: u128,
+    pub status: FractionalStatus,
+    pub recombined_by: Option<AccountId>,
+    pub created_at: Timestamp,
+}
+
+/// One shareholder's stake. Income accrues against `income_snapshot` and is
+/// moved into `unclaimed` whenever the stake changes.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct ShareHolding {
+    pub account_id: AccountId,
+    pub shares: u128,
+    pub income_snapshot: u128,
+    /// Income and buyout proceeds waiting to be claimed.
+    pub unclaimed: u128,
+}
+
+/// One recipient's cut of the payments owed to a property's owner.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct PayoutShare {
+    pub account_id: AccountId,
+    pub bps: u16,
+}
+
+/// Stablecoins an account is owed after a payout to them failed.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct ClaimableBalance {
+    pub token: AccountId,
+    pub amount: u128,
+}
+
+/// What the platform takes of a payment to a property's owner for one kind
+/// of deal: `bps` of the payment plus a `flat` amount.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct PlatformFee {
+    pub action: Action,
+    pub bps: u16,
+    pub flat: u128,
+}
+
+/// A broker's commission on a listing, as bps of each payment to the owner.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct BrokerCommission {
+    pub broker_id: AccountId,
+    pub bps: u16,
+}
+
+/// The fees taken out of one payment.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct FeeCharge {
+    pub property_id: u64,
+    pub stablecoin_token: AccountId,
+    pub platform_fee: u128,
+    pub broker_id: Option<AccountId>,
+    pub commission: u128,
+}
+
+/// The `msg` a property NFT's owner sends with `nft_transfer_call` or
+/// `nft_approve` to list it: the same terms `update_listing` takes.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ListingMsg {
+    pub price: U128,
+    pub is_for_sale: bool,
+    pub lease_duration_months: Option<u64>,
+}
+
+/// How a property listed from outside came to the contract.
+#[derive(
+    BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, PartialEq, Debug, JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ExternalListing {
+    /// The owner transferred the NFT in; the contract holds it until it's
+    /// sold or reclaimed.
+    Custody,
+    /// The owner approved the contract to sell it and kept the NFT.
+    Approval { approval_id: u64 },
+}
+
+/// A dispute with a case file: a lease's, or a bid's.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Deserialize,
+    Serialize,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Debug,
+    JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum DisputeCase {
+    Lease { lease_id: u64 },
+    Bid { property_id: u64, bid_id: u64 },
+}
+
+/// One piece of evidence: a document kept off-chain, pinned by its hash.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct EvidenceEntry {
+    pub submitted_by: AccountId,
+    pub uri: String,
+    pub content_hash: String,
+    pub description: String,
+    pub submitted_at: Timestamp,
+}
+
+/// Everything put in on one dispute. The side that raised it and the side
+/// answering it can add evidence until `response_deadline`; admins can add
+/// notes until it's resolved. Kept after resolution as the record.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct CaseFile {
+    pub raised_by: AccountId,
+    pub respondent: AccountId,
+    pub opened_at: Timestamp,
+    pub response_deadline: Timestamp,
+    pub entries: Vec<EvidenceEntry>,
+}
+
+/// One admin's vote on a lease dispute: who should win, and what they
+/// should be paid.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct DisputeVote {
+    pub admin_id: AccountId,
+    pub for_tenant: bool,
+    pub payout: u128,
+    pub voted_at: Timestamp,
+}
+
+/// The admins' vote on a lease dispute, opened by the first vote. Each admin
+/// has one vote and can change it until voting closes.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct DisputeVoting {
+    pub opened_at: Timestamp,
+    pub closes_at: Timestamp,
+    pub votes: Vec<DisputeVote>,
+}
+
+/// How arbitration by a panel of staked jurors runs. Jurors stake
+/// `stablecoin_token`, and a party pays `arbitration_fee` in it to have a
+/// dispute decided by `panel_size` of them.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JuryTerms {
+    pub stablecoin_token: AccountId,
+    /// Least a juror must have staked to be drawn.
+    pub min_stake: U128,
+    pub arbitration_fee: U128,
+    /// Odd, so a panel that all reveals can't tie.
+    pub panel_size: u32,
+    /// Share of their stake a juror loses for voting with the minority or
+    /// not revealing.
+    pub slash_bps: u16,
+    pub commit_period_ns: u64,
+    pub reveal_period_ns: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct Juror {
+    pub stake: u128,
+    /// Panels the juror is sitting on; their stake can't be withdrawn
+    /// until they're all decided.
+    pub panels: u32,
+}
+
+/// A panellist's vote: first the commitment, then what it hid.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct JuryBallot {
+    pub juror_id: AccountId,
+    pub commitment: Option<String>,
+    pub for_raiser: Option<bool>,
+}
+
+/// A juror panel sitting on a dispute. Jurors vote for or against the side
+/// that raised it.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct Arbitration {
+    pub requested_by: AccountId,
+    pub stablecoin_token: AccountId,
+    pub fee: u128,
+    pub slash_bps: u16,
+    pub panel: Vec<JuryBallot>,
+    pub commit_deadline: Timestamp,
+    pub reveal_deadline: Timestamp,
+}
+
+/// `ft_transfer_call` messages that fund arbitration rather than a bid.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ArbitrationMsg {
+    /// Stakes the transferred amount as a juror.
+    StakeJuror,
+    /// Pays the arbitration fee to put `case` before a juror panel.
+    RequestArbitration { case: DisputeCase },
+}
+
+/// A bond posted to raise a dispute, held until it's decided.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone)]
+pub struct DisputeBond {
+    pub posted_by: AccountId,
+    pub stablecoin_token: AccountId,
+    pub amount: u128,
+    /// Where the bond goes if the dispute is lost, fixed when it's posted.
+    pub forfeit_to_treasury: bool,
+}
+
+/// `ft_transfer_call` messages that post a dispute bond and raise the
+/// dispute with it.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum DisputeMsg {
+    /// Tenant disputes their lease.
+    RaiseLeaseDispute { lease_id: u64, reason: String },
+    /// Landlord disputes a lease, claiming `claim` (which may be zero).
+    RaiseOwnerLeaseDispute {
+        lease_id: u64,
+        reason: String,
+        claim: U128,
+    },
+    /// Tenant contests the deductions filed against their damage deposit.
+    DisputeDepositDeductions { lease_id: u64, reason: String },
+    /// Bidder or owner disputes an accepted bid.
+    RaiseBidDispute {
+        property_id: u64,
+        bid_id: u64,
+        reason: String,
+    },
+}
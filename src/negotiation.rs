@@ -0,0 +1,466 @@
+use near_sdk::{
+    assert_one_yocto, env, json_types::U128, log, near_bindgen, require, AccountId, Gas, NearToken,
+};
+
+use crate::{
+    events::{
+        emit_event, BidAmendedEvent, CounterOfferAcceptedEvent, CounterOfferDeclinedEvent,
+        CounterOfferMadeEvent,
+    },
+    ext::ft_contract,
+    internal::{
+        checked_add_u128, checked_sub_u128, credit_escrow, debit_escrow,
+        execute_accept_bid_with_escrow, lock_bid, unlock_bid, update_bid_in_list,
+    },
+    models::{Bid, BidStatus, CounterOffer, OfferStatus},
+    views::CounterOfferView,
+    ShedaContract, ShedaContractExt,
+};
+
+fn pending_bid(contract: &ShedaContract, property_id: u64, bid_id: u64) -> Bid {
+    let bid = contract
+        .bids
+        .get(&property_id)
+        .and_then(|bids| bids.iter().find(|b| b.id == bid_id).cloned())
+        .expect("Bid not found for the property");
+    require!(
+        bid.status == BidStatus::Pending,
+        "Only a pending bid can be negotiated"
+    );
+    // The deposit/balance split and the instalment schedule were fixed
+    // against the bid's amount; moving it under them would leave them wrong.
+    crate::earnest::assert_not_earnest(
+        contract,
+        bid_id,
+        "An earnest-money bid can't be negotiated or amended",
+    );
+    crate::instalment::assert_not_instalment(
+        contract,
+        bid_id,
+        "An instalment bid can't be negotiated or amended",
+    );
+    bid
+}
+
+/// The round still waiting on an answer, if any.
+fn open_round(contract: &ShedaContract, bid_id: u64) -> Option<CounterOffer> {
+    contract
+        .negotiations
+        .get(&bid_id)
+        .and_then(|rounds| rounds.last())
+        .filter(|round| round.status == OfferStatus::Open)
+        .cloned()
+}
+
+/// The seller's offer the bidder is answering. Panics if the ball isn't in
+/// the bidder's court.
+fn open_seller_offer(contract: &ShedaContract, property_id: u64, bid_id: u64) -> CounterOffer {
+    let owner_id = contract
+        .properties
+        .get(&property_id)
+        .expect("Property not found")
+        .owner_id
+        .clone();
+    open_round(contract, bid_id)
+        .filter(|round| round.proposer == owner_id)
+        .expect("There is no open counter-offer from the seller on this bid")
+}
+
+fn set_round_status(contract: &mut ShedaContract, bid_id: u64, round: u32, status: OfferStatus) {
+    if let Some(entry) = contract
+        .negotiations
+        .get_mut(&bid_id)
+        .and_then(|rounds| rounds.iter_mut().find(|r| r.round == round))
+    {
+        entry.status = status;
+    }
+}
+
+/// Record a new round, closing whichever one it answers.
+fn push_round(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    bid_id: u64,
+    proposer: AccountId,
+    amount: u128,
+    terms: Option<String>,
+) {
+    require!(amount > 0, "Offer amount must be greater than zero");
+    crate::deposit::assert_covers_deposit(contract, bid_id, amount);
+
+    let rounds = contract.negotiations.entry(bid_id).or_default();
+    if let Some(last) = rounds.last_mut() {
+        if last.status == OfferStatus::Open {
+            last.status = OfferStatus::Countered;
+        }
+    }
+    let round = u32::try_from(rounds.len()).unwrap_or_else(|_| env::panic_str("Too many rounds"));
+    rounds.push(CounterOffer {
+        round,
+        proposer: proposer.clone(),
+        amount,
+        terms: terms.clone(),
+        created_at: env::block_timestamp(),
+        status: OfferStatus::Open,
+    });
+
+    emit_event(
+        "CounterOfferMade",
+        CounterOfferMadeEvent {
+            token_id: property_id,
+            bid_id,
+            round,
+            proposer_id: proposer,
+            amount,
+            terms,
+        },
+    );
+}
+
+/// Move the bid to the agreed amount and into escrow.
+///
+/// The seller committed to the number when they offered it, so accepting it
+/// is the acceptance — the bid goes through `execute_accept_bid_with_escrow`
+/// exactly as if the seller had called `accept_bid_with_escrow` at that price.
+fn conclude(contract: &mut ShedaContract, property_id: u64, bid: &Bid, offer: &CounterOffer) {
+    if let Some(bids) = contract.bids.get_mut(&property_id) {
+        let _ = update_bid_in_list(bids, bid.id, |b| {
+            b.amount = offer.amount;
+            b.updated_at = env::block_timestamp();
+        });
+    }
+    set_round_status(contract, bid.id, offer.round, OfferStatus::Accepted);
+
+    emit_event(
+        "CounterOfferAccepted",
+        CounterOfferAcceptedEvent {
+            token_id: property_id,
+            bid_id: bid.id,
+            round: offer.round,
+            bidder_id: bid.bidder.clone(),
+            previous_amount: bid.amount,
+            amount: offer.amount,
+        },
+    );
+
+    execute_accept_bid_with_escrow(contract, property_id, bid.id);
+}
+
+/// Accept a higher counter-offer by paying the difference through
+/// `ft_on_transfer`. Returns what was sent beyond the difference, for
+/// `ft_on_transfer` to hand back.
+pub(crate) fn accept_with_top_up(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    bid_id: u64,
+    sender_id: AccountId,
+    amount: u128,
+) -> u128 {
+    let bid = pending_bid(contract, property_id, bid_id);
+    require!(
+        bid.bidder == sender_id,
+        "Only the bidder can accept a counter-offer"
+    );
+    require!(
+        bid.stablecoin_token == env::predecessor_account_id(),
+        "Top-up must be in the bid's stablecoin"
+    );
+    crate::auction::assert_no_open_auction(contract, property_id);
+
+    let offer = open_seller_offer(contract, property_id, bid_id);
+    require!(
+        offer.amount > bid.amount,
+        "This counter-offer is below the bid — accept it with accept_counter_offer"
+    );
+    let top_up = offer.amount - bid.amount;
+    require!(
+        amount >= top_up,
+        format!("Accepting this counter-offer takes a top-up of {}", top_up)
+    );
+
+    credit_escrow(
+        contract,
+        &bid.stablecoin_token,
+        top_up,
+        "counter-offer top-up",
+    );
+    conclude(contract, property_id, &bid, &offer);
+
+    amount - top_up
+}
+
+/// Raise a pending bid in place with an `ft_on_transfer` `TopUp`.
+///
+/// The bid keeps its id, so it keeps its place — in an auction, and in any
+/// negotiation running on it.
+pub(crate) fn top_up_bid(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    bid_id: u64,
+    sender_id: AccountId,
+    amount: u128,
+) {
+    let bid = pending_bid(contract, property_id, bid_id);
+    require!(bid.bidder == sender_id, "Only the bidder can top up a bid");
+    require!(
+        bid.stablecoin_token == env::predecessor_account_id(),
+        "Top-up must be in the bid's stablecoin"
+    );
+    require!(amount > 0, "Top-up amount must be greater than zero");
+
+    let new_amount = checked_add_u128(bid.amount, amount, "bid top-up");
+    if let Some(bids) = contract.bids.get_mut(&property_id) {
+        let _ = update_bid_in_list(bids, bid_id, |b| {
+            b.amount = new_amount;
+            b.updated_at = env::block_timestamp();
+        });
+    }
+    credit_escrow(contract, &bid.stablecoin_token, amount, "bid top-up");
+
+    emit_event(
+        "BidAmended",
+        BidAmendedEvent {
+            token_id: property_id,
+            bid_id,
+            bidder_id: bid.bidder,
+            previous_amount: bid.amount,
+            new_amount,
+        },
+    );
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Seller proposes a different amount (and optionally terms) on a pending
+    /// bid instead of accepting or rejecting it.
+    ///
+    /// Also how the seller answers a bidder's counter — the bidder's round is
+    /// closed as `Countered`. Offering the bidder's own number back is how the
+    /// seller agrees to it, since only the bidder can put money in.
+    #[payable]
+    pub fn counter_offer(
+        &mut self,
+        property_id: u64,
+        bid_id: u64,
+        amount: U128,
+        terms: Option<String>,
+    ) {
+        assert_one_yocto();
+
+        let owner_id = self
+            .properties
+            .get(&property_id)
+            .expect("Property not found")
+            .owner_id
+            .clone();
+        assert_eq!(
+            owner_id,
+            env::predecessor_account_id(),
+            "Only the property owner can make a counter-offer"
+        );
+        crate::auction::assert_no_open_auction(self, property_id);
+        pending_bid(self, property_id, bid_id);
+
+        // While the two sides are talking, the bid shouldn't lapse from under
+        // them; the bidder can still walk away with cancel_bid at any point.
+        if let Some(bids) = self.bids.get_mut(&property_id) {
+            let _ = update_bid_in_list(bids, bid_id, |b| {
+                b.expires_at = None;
+            });
+        }
+
+        push_round(self, property_id, bid_id, owner_id, amount.0, terms);
+    }
+
+    /// Bidder answers the seller's counter-offer with one of their own.
+    pub fn counter_seller_offer(
+        &mut self,
+        property_id: u64,
+        bid_id: u64,
+        amount: U128,
+        terms: Option<String>,
+    ) {
+        let bid = pending_bid(self, property_id, bid_id);
+        assert_eq!(
+            bid.bidder,
+            env::predecessor_account_id(),
+            "Only the bidder can counter the seller's offer"
+        );
+        open_seller_offer(self, property_id, bid_id);
+
+        push_round(self, property_id, bid_id, bid.bidder, amount.0, terms);
+    }
+
+    /// Bidder accepts a counter-offer at or below their bid. The difference
+    /// is refunded and the bid moves into escrow at the offered amount.
+    ///
+    /// An offer above the bid is accepted by paying the difference instead:
+    /// `ft_transfer_call` with `"kind": {"AcceptCounterOffer": {"bid_id": ..}}`.
+    #[payable]
+    pub fn accept_counter_offer(&mut self, property_id: u64, bid_id: u64) {
+        assert_one_yocto();
+
+        let bid = pending_bid(self, property_id, bid_id);
+        assert_eq!(
+            bid.bidder,
+            env::predecessor_account_id(),
+            "Only the bidder can accept a counter-offer"
+        );
+        crate::auction::assert_no_open_auction(self, property_id);
+
+        let offer = open_seller_offer(self, property_id, bid_id);
+        require!(
+            offer.amount <= bid.amount,
+            "This counter-offer is above the bid — top up the difference with ft_transfer_call"
+        );
+        let refund = checked_sub_u128(bid.amount, offer.amount, "counter-offer refund");
+        crate::joint::assert_within_own_contribution(self, &bid, refund);
+
+        conclude(self, property_id, &bid, &offer);
+
+        if refund > 0 {
+            debit_escrow(self, &bid.stablecoin_token, refund, "counter-offer refund");
+
+            #[allow(unused_must_use)]
+            ft_contract::ext(bid.stablecoin_token.clone())
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(Gas::from_tgas(30))
+                .ft_transfer(bid.bidder.clone(), U128(refund))
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(Gas::from_tgas(10))
+                        .counter_offer_refund_callback(bid.bidder, bid.stablecoin_token, refund),
+                );
+        }
+    }
+
+    /// Bidder turns the seller's counter-offer down. The bid stays pending at
+    /// its original amount; to walk away entirely, use `cancel_bid`.
+    pub fn decline_counter_offer(&mut self, property_id: u64, bid_id: u64) {
+        let bid = pending_bid(self, property_id, bid_id);
+        assert_eq!(
+            bid.bidder,
+            env::predecessor_account_id(),
+            "Only the bidder can decline a counter-offer"
+        );
+
+        let offer = open_seller_offer(self, property_id, bid_id);
+        set_round_status(self, bid_id, offer.round, OfferStatus::Declined);
+
+        emit_event(
+            "CounterOfferDeclined",
+            CounterOfferDeclinedEvent {
+                token_id: property_id,
+                bid_id,
+                round: offer.round,
+                bidder_id: bid.bidder,
+            },
+        );
+    }
+
+    #[private]
+    pub fn counter_offer_refund_callback(
+        &mut self,
+        bidder_id: AccountId,
+        stablecoin_token: AccountId,
+        amount: u128,
+    ) {
+        if crate::internal::promise_failed(0) {
+            // The deal itself stands; only the change failed to go out. The
+            // bidder can claim it.
+            crate::payout::hold_for_claim(self, &bidder_id, &stablecoin_token, amount);
+            log!(
+                "Counter-offer refund of {} to {} failed, held for them to claim",
+                amount,
+                bidder_id
+            );
+        }
+    }
+
+    /// Withdraw part of a pending bid; the rest stays in place.
+    ///
+    /// Not available to the bid leading an auction — the other bidders had to
+    /// beat its full amount.
+    #[payable]
+    pub fn reduce_bid(&mut self, property_id: u64, bid_id: u64, amount: U128) {
+        assert_one_yocto();
+
+        let bid = pending_bid(self, property_id, bid_id);
+        assert_eq!(
+            bid.bidder,
+            env::predecessor_account_id(),
+            "Only the bidder can reduce their bid"
+        );
+        crate::auction::assert_not_leading_bid(self, property_id, bid_id);
+        require!(
+            amount.0 > 0 && amount.0 < bid.amount,
+            "Can only withdraw part of the bid — use cancel_bid to withdraw all of it"
+        );
+        crate::joint::assert_within_own_contribution(self, &bid, amount.0);
+        let new_amount = bid.amount - amount.0;
+        crate::deposit::assert_covers_deposit(self, bid_id, new_amount);
+
+        lock_bid(self, property_id, bid_id);
+
+        if let Some(bids) = self.bids.get_mut(&property_id) {
+            let _ = update_bid_in_list(bids, bid_id, |b| {
+                b.amount = new_amount;
+                b.updated_at = env::block_timestamp();
+            });
+        }
+        debit_escrow(self, &bid.stablecoin_token, amount.0, "reduce_bid");
+
+        emit_event(
+            "BidAmended",
+            BidAmendedEvent {
+                token_id: property_id,
+                bid_id,
+                bidder_id: bid.bidder.clone(),
+                previous_amount: bid.amount,
+                new_amount,
+            },
+        );
+
+        #[allow(unused_must_use)]
+        ft_contract::ext(bid.stablecoin_token.clone())
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(Gas::from_tgas(30))
+            .ft_transfer(bid.bidder, amount)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(Gas::from_tgas(20))
+                    .reduce_bid_callback(property_id, bid_id, bid.stablecoin_token, amount.0),
+            );
+    }
+
+    #[private]
+    pub fn reduce_bid_callback(
+        &mut self,
+        property_id: u64,
+        bid_id: u64,
+        stablecoin_token: AccountId,
+        amount: u128,
+    ) {
+        unlock_bid(self, property_id, bid_id);
+
+        if crate::internal::promise_failed(0) {
+            // The bid was locked for the whole round trip, so putting the
+            // amount back can't trample anything that happened in between.
+            if let Some(bids) = self.bids.get_mut(&property_id) {
+                let _ = update_bid_in_list(bids, bid_id, |b| {
+                    b.amount = checked_add_u128(b.amount, amount, "reduce_bid revert");
+                });
+            }
+            credit_escrow(self, &stablecoin_token, amount, "reduce_bid revert");
+            log!("reduce_bid transfer failed, bid {} restored", bid_id);
+        }
+    }
+
+    /// Every round of the negotiation on a bid, oldest first.
+    pub fn get_negotiation(&self, bid_id: u64) -> Vec<CounterOfferView> {
+        self.negotiations
+            .get(&bid_id)
+            .map(|rounds| rounds.iter().map(|round| round.into()).collect())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,283 @@
+use near_sdk::{
+    assert_one_yocto, env, json_types::U128, log, near_bindgen, require, AccountId, Gas, NearToken,
+    Promise,
+};
+
+use crate::{
+    events::{emit_event, PayoutHeldEvent, PayoutSplitSetEvent},
+    ext::ft_contract,
+    internal::{checked_add_u128, credit_escrow, debit_escrow},
+    models::{ClaimableBalance, PayoutShare},
+    views::{ClaimableBalanceView, PayoutShareView},
+    ShedaContract, ShedaContractExt,
+};
+
+const MAX_PAYOUT_RECIPIENTS: usize = 5;
+const TOTAL_BPS: u128 = 10_000;
+
+fn owner_of(contract: &ShedaContract, property_id: u64) -> AccountId {
+    contract
+        .properties
+        .get(&property_id)
+        .map(|property| property.owner_id.clone())
+        .expect("Property not found")
+}
+
+fn transfer(token: &AccountId, account_id: AccountId, amount: u128) -> Promise {
+    ft_contract::ext(token.clone())
+        .with_attached_deposit(NearToken::from_yoctonear(1))
+        .with_static_gas(Gas::from_tgas(30))
+        .ft_transfer(account_id, U128(amount))
+}
+
+/// Who gets what of `amount` paid to `payee` on a property's account. Only
+/// what's owed to the owner themselves is split: a fractionalised property's
+/// share goes to its shareholders (and nothing is returned), a property with
+/// a payout split is divided by basis points with any rounding dust going to
+/// the first recipient, and anyone else — a landlord collecting from a
+/// subtenant, a refunded tenant — is paid as is.
+pub(crate) fn owner_payouts(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    token: &AccountId,
+    payee: AccountId,
+    amount: u128,
+) -> Vec<(AccountId, u128)> {
+    if amount == 0 || payee != owner_of(contract, property_id) {
+        return vec![(payee, amount)];
+    }
+    if crate::fraction::is_fractionalised(contract, property_id) {
+        crate::fraction::share_income(contract, property_id, token, amount);
+        return Vec::new();
+    }
+    let Some(split) = contract.payout_splits.get(&property_id) else {
+        return vec![(payee, amount)];
+    };
+
+    let mut payouts: Vec<(AccountId, u128)> = split
+        .iter()
+        .map(|share| {
+            let cut = amount
+                .checked_mul(u128::from(share.bps))
+                .unwrap_or_else(|| env::panic_str("Overflow in payout split"))
+                / TOTAL_BPS;
+            (share.account_id.clone(), cut)
+        })
+        .collect();
+    let dust = amount - payouts.iter().map(|(_, cut)| cut).sum::<u128>();
+    payouts[0].1 += dust;
+    payouts
+}
+
+/// Send payouts out of the contract, skipping zeros. Each transfer stands on
+/// its own: one that fails is held for its recipient to claim and doesn't
+/// hold up the others. The caller has already taken the amounts off the
+/// escrow ledger.
+pub(crate) fn send_payouts(token: &AccountId, payouts: Vec<(AccountId, u128)>) -> Option<Promise> {
+    let payouts: Vec<(AccountId, U128)> = payouts
+        .into_iter()
+        .filter(|(_, amount)| *amount > 0)
+        .map(|(account, amount)| (account, U128(amount)))
+        .collect();
+    let mut transfers = payouts
+        .iter()
+        .map(|(account, amount)| transfer(token, account.clone(), amount.0));
+    let first = transfers.next()?;
+    Some(
+        transfers.fold(first, |all, next| all.and(next)).then(
+            ShedaContract::ext(env::current_account_id())
+                .with_static_gas(Gas::from_tgas(20))
+                .payout_callback(token.clone(), payouts.clone()),
+        ),
+    )
+}
+
+/// Pay what's owed to `payee` on a property, split as `owner_payouts` says.
+/// A single recipient is paid with a plain transfer so the caller's callback
+/// sees how it went; a split is sent on its own and the caller gets a no-op
+/// promise to chain on, failures being held for claim instead.
+pub(crate) fn pay_owner(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    token: &AccountId,
+    payee: AccountId,
+    amount: u128,
+) -> Promise {
+    let mut payouts = owner_payouts(contract, property_id, token, payee, amount);
+    if payouts.len() == 1 {
+        let (account_id, amount) = payouts.remove(0);
+        return transfer(token, account_id, amount);
+    }
+    #[allow(unused_must_use)]
+    send_payouts(token, payouts);
+    // Nothing leaves the contract; the same no-op the mock-transfer path uses.
+    Promise::new(env::current_account_id()).transfer(NearToken::from_yoctonear(0))
+}
+
+/// Keep a payout that couldn't be sent for its recipient to claim later.
+pub(crate) fn hold_for_claim(
+    contract: &mut ShedaContract,
+    account_id: &AccountId,
+    token: &AccountId,
+    amount: u128,
+) {
+    credit_escrow(contract, token, amount, "claimable balance");
+    let balances = contract
+        .claimable_balances
+        .entry(account_id.clone())
+        .or_default();
+    match balances.iter_mut().find(|balance| balance.token == *token) {
+        Some(balance) => {
+            balance.amount = checked_add_u128(balance.amount, amount, "claimable balance");
+        }
+        None => balances.push(ClaimableBalance {
+            token: token.clone(),
+            amount,
+        }),
+    }
+
+    emit_event(
+        "PayoutHeld",
+        PayoutHeldEvent {
+            account_id: account_id.clone(),
+            token: token.clone(),
+            amount,
+        },
+    );
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Owner splits what the property earns — sale and lease proceeds,
+    /// renewals, rent, stays, kept deposits and dispute awards — between up
+    /// to five accounts by basis points adding up to 10000. The split ends
+    /// when the property changes hands.
+    #[payable]
+    pub fn set_payout_split(&mut self, property_id: u64, recipients: Vec<PayoutShare>) {
+        assert_one_yocto();
+
+        let owner_id = owner_of(self, property_id);
+        assert_eq!(
+            owner_id,
+            env::predecessor_account_id(),
+            "Only the property owner can set a payout split"
+        );
+        require!(
+            !recipients.is_empty(),
+            "A split needs at least one recipient"
+        );
+        require!(
+            recipients.len() <= MAX_PAYOUT_RECIPIENTS,
+            format!(
+                "A split can have at most {} recipients",
+                MAX_PAYOUT_RECIPIENTS
+            )
+        );
+        require!(
+            recipients.iter().all(|share| share.bps > 0),
+            "Every recipient needs a share above zero"
+        );
+        require!(
+            recipients
+                .iter()
+                .map(|share| u128::from(share.bps))
+                .sum::<u128>()
+                == TOTAL_BPS,
+            "Shares must add up to 10000 basis points"
+        );
+        for (index, share) in recipients.iter().enumerate() {
+            require!(
+                recipients[..index]
+                    .iter()
+                    .all(|earlier| earlier.account_id != share.account_id),
+                "A recipient can only appear once"
+            );
+        }
+
+        emit_event(
+            "PayoutSplitSet",
+            PayoutSplitSetEvent {
+                token_id: property_id,
+                owner_id,
+                recipients: recipients
+                    .iter()
+                    .map(|share| (share.account_id.clone(), share.bps))
+                    .collect(),
+            },
+        );
+        self.payout_splits.insert(property_id, recipients);
+    }
+
+    /// Owner goes back to being paid in full.
+    #[payable]
+    pub fn clear_payout_split(&mut self, property_id: u64) {
+        assert_one_yocto();
+
+        assert_eq!(
+            owner_of(self, property_id),
+            env::predecessor_account_id(),
+            "Only the property owner can clear a payout split"
+        );
+        self.payout_splits.remove(&property_id);
+    }
+
+    #[private]
+    pub fn payout_callback(
+        &mut self,
+        stablecoin_token: AccountId,
+        payouts: Vec<(AccountId, U128)>,
+    ) {
+        for (index, (account, amount)) in payouts.into_iter().enumerate() {
+            if crate::internal::promise_failed(index as u64) {
+                hold_for_claim(self, &account, &stablecoin_token, amount.0);
+                log!(
+                    "Payout of {} to {} failed, held for them to claim",
+                    amount.0,
+                    account
+                );
+            }
+        }
+    }
+
+    /// Caller withdraws whatever is held for them in `token` after a payout
+    /// to them failed. Returns the amount sent.
+    pub fn claim_balance(&mut self, token: AccountId) -> U128 {
+        let account_id = env::predecessor_account_id();
+        let balances = self
+            .claimable_balances
+            .get_mut(&account_id)
+            .expect("Nothing to claim");
+        let index = balances
+            .iter()
+            .position(|balance| balance.token == token)
+            .expect("Nothing to claim in this token");
+        let amount = balances.remove(index).amount;
+        if balances.is_empty() {
+            self.claimable_balances.remove(&account_id);
+        }
+        debit_escrow(self, &token, amount, "claimable balance");
+
+        #[allow(unused_must_use)]
+        transfer(&token, account_id.clone(), amount).then(
+            ShedaContract::ext(env::current_account_id())
+                .with_static_gas(Gas::from_tgas(15))
+                .payout_callback(token, vec![(account_id, U128(amount))]),
+        );
+
+        U128(amount)
+    }
+
+    pub fn get_payout_split(&self, property_id: u64) -> Vec<PayoutShareView> {
+        self.payout_splits
+            .get(&property_id)
+            .map(|split| split.iter().map(|share| share.into()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn get_claimable_balances(&self, account_id: AccountId) -> Vec<ClaimableBalanceView> {
+        self.claimable_balances
+            .get(&account_id)
+            .map(|balances| balances.iter().map(|balance| balance.into()).collect())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,335 @@
+use near_sdk::{assert_one_yocto, env, log, near_bindgen, require, AccountId, Gas};
+
+use crate::{
+    events::{emit_event, AutoRenewalOptedOutEvent},
+    internal::{checked_add_u128, checked_add_u64, checked_mul_u64, lock_bid},
+    models::{Action, AutoRenewal, AutoRenewalStatus, Bid, BidStatus, Escalation, Lease},
+    views::AutoRenewalView,
+    ShedaContract, ShedaContractExt,
+};
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+fn lease_for(contract: &ShedaContract, lease_id: u64) -> Lease {
+    contract
+        .leases
+        .get(&lease_id)
+        .cloned()
+        .expect("Lease not found")
+}
+
+fn owner_of(contract: &ShedaContract, property_id: u64) -> AccountId {
+    contract
+        .properties
+        .get(&property_id)
+        .expect("Property not found")
+        .owner_id
+        .clone()
+}
+
+fn renewal_for(contract: &ShedaContract, lease_id: u64) -> AutoRenewal {
+    contract
+        .auto_renewals
+        .get(&lease_id)
+        .cloned()
+        .expect("This lease doesn't renew automatically")
+}
+
+/// The last moment either side can opt out of the next renewal.
+fn notice_deadline(lease: &Lease, renewal: &AutoRenewal) -> u64 {
+    lease.end_time.saturating_sub(renewal.notice_period_ns)
+}
+
+/// A renewal payment is on its way to the owner. One whose transfer failed
+/// is left a pending bid, and doesn't count.
+fn payment_in_flight(contract: &ShedaContract, property_id: u64, renewal: &AutoRenewal) -> bool {
+    renewal.pending_bid_id.is_some_and(|pending_bid_id| {
+        contract.bids.get(&property_id).is_some_and(|bids| {
+            bids.iter()
+                .any(|bid| bid.id == pending_bid_id && bid.status == BidStatus::Accepted)
+        })
+    })
+}
+
+fn next_term_price(renewal: &AutoRenewal) -> u128 {
+    let rise = match &renewal.escalation {
+        Escalation::Percentage { bps } => renewal.term_price * u128::from(*bps) / BPS_DENOMINATOR,
+        Escalation::FixedAmount { amount } => amount.0,
+    };
+    checked_add_u128(renewal.term_price, rise, "auto-renewal price")
+}
+
+/// Pay for the next term of an auto-renewing lease with an `ft_on_transfer`
+/// `PayAutoRenewal`. Open from the notice deadline until the lease ends.
+///
+/// The payment becomes an accepted renewal bid, paid straight on to the owner
+/// and settled by `accept_lease_renewal_callback` exactly as if the owner had
+/// accepted it — new end date, renewal document and all. Returns what was
+/// sent beyond the price.
+pub(crate) fn pay_auto_renewal(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    lease_id: u64,
+    sender_id: AccountId,
+    amount: u128,
+) -> u128 {
+    let lease = lease_for(contract, lease_id);
+    require!(
+        lease.property_id == property_id,
+        "Lease is not on this property"
+    );
+    require!(
+        lease.tenant_id == sender_id,
+        "Only the tenant can renew the lease"
+    );
+    require!(
+        lease.escrow_token == env::predecessor_account_id(),
+        "Renewal must be paid in the lease's stablecoin"
+    );
+    require!(lease.active, "Lease is not active");
+    require!(!lease.has_open_dispute(), "A dispute is open on this lease");
+    crate::termination::assert_no_notice(contract, lease_id);
+
+    let renewal = renewal_for(contract, lease_id);
+    require!(
+        renewal.status == AutoRenewalStatus::Active,
+        "Auto-renewal isn't active on this lease"
+    );
+    let now = env::block_timestamp();
+    require!(
+        now >= notice_deadline(&lease, &renewal),
+        "Renewal opens once the opt-out deadline has passed"
+    );
+    require!(now < lease.end_time, "The lease has already ended");
+    require!(
+        !payment_in_flight(contract, property_id, &renewal),
+        "This term's renewal is already being paid"
+    );
+
+    let price = next_term_price(&renewal);
+    require!(amount >= price, format!("The next term comes to {}", price));
+
+    let duration_months = contract
+        .properties
+        .get(&property_id)
+        .expect("Property not found")
+        .lease_duration_months
+        .expect("Property is not configured with a lease duration");
+    // Same term and same arithmetic as accept_lease_renewal.
+    let new_end_time = checked_add_u64(
+        lease.end_time,
+        checked_mul_u64(
+            duration_months,
+            30 * 24 * 60 * 60 * 1_000_000_000,
+            "renewal duration",
+        ),
+        "renewal end_time",
+    );
+
+    let bid_id = contract.bid_counter;
+    contract.bid_counter = checked_add_u64(contract.bid_counter, 1, "bid_counter");
+    let bid = Bid {
+        id: bid_id,
+        bidder: sender_id,
+        property_id,
+        amount: price,
+        created_at: now,
+        updated_at: now,
+        status: BidStatus::Accepted,
+        document_token_id: None,
+        document_image_uri: None,
+        document_description: None,
+        escrow_release_tx: None,
+        dispute_reason: None,
+        expires_at: None,
+        escrow_release_after: None,
+        action: Action::Lease,
+        stablecoin_token: lease.escrow_token.clone(),
+        lease_id: Some(lease_id),
+    };
+    // Fees come out of the owner's share once the payment goes through.
+    let net = crate::fees::charge_bid(contract, &bid, price);
+    contract.bids.entry(property_id).or_default().push(bid);
+    // The callback unlocks it.
+    lock_bid(contract, property_id, bid_id);
+
+    if let Some(renewal) = contract.auto_renewals.get_mut(&lease_id) {
+        renewal.pending_bid_id = Some(bid_id);
+    }
+
+    // The payment passes straight through, so the escrow ledger doesn't
+    // move; if the transfer fails the callback books it as held.
+    let owner_id = owner_of(contract, property_id);
+    #[allow(unused_must_use)]
+    crate::payout::pay_owner(contract, property_id, &lease.escrow_token, owner_id, net).then(
+        ShedaContract::ext(env::current_account_id())
+            .with_static_gas(Gas::from_tgas(50))
+            .accept_lease_renewal_callback(property_id, bid_id, new_end_time),
+    );
+
+    amount - price
+}
+
+/// A renewal went through; if it was the automatic one, the escalated price
+/// is now what the term costs.
+pub(crate) fn on_renewed(contract: &mut ShedaContract, lease_id: u64, bid_id: u64, price: u128) {
+    if let Some(renewal) = contract.auto_renewals.get_mut(&lease_id) {
+        if renewal.pending_bid_id == Some(bid_id) {
+            renewal.term_price = price;
+            renewal.renewals += 1;
+            renewal.pending_bid_id = None;
+        }
+    }
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Owner offers to renew a lease automatically, each term priced at the
+    /// last one plus `escalation`. Either side can opt out of a renewal up to
+    /// `notice_period_ns` before the lease ends; after that the tenant renews
+    /// by paying. Takes effect once the tenant agrees. Offering new terms
+    /// replaces the old ones, and needs agreeing to again.
+    #[payable]
+    pub fn offer_auto_renewal(
+        &mut self,
+        lease_id: u64,
+        escalation: Escalation,
+        notice_period_ns: u64,
+    ) {
+        assert_one_yocto();
+
+        let lease = lease_for(self, lease_id);
+        assert_eq!(
+            owner_of(self, lease.property_id),
+            env::predecessor_account_id(),
+            "Only the property owner can offer auto-renewal"
+        );
+        require!(lease.active, "Lease is not active");
+        require!(
+            crate::sublease::parent_of(self, lease_id).is_none(),
+            "A sublease can't renew automatically"
+        );
+        require!(
+            notice_period_ns > 0 && notice_period_ns < lease.end_time - lease.start_time,
+            "The notice period must fall within the lease term"
+        );
+        if let Escalation::Percentage { bps } = escalation {
+            require!(
+                u128::from(bps) <= BPS_DENOMINATOR,
+                "Escalation can't exceed 100% a term"
+            );
+        }
+        let previous = self.auto_renewals.get(&lease_id);
+        require!(
+            !previous.is_some_and(|renewal| payment_in_flight(self, lease.property_id, renewal)),
+            "A renewal is being paid for"
+        );
+
+        // The first term's price is what the lease was let for; a deposit
+        // held with it isn't part of that.
+        let term_price = previous
+            .map(|renewal| renewal.term_price)
+            .unwrap_or_else(|| {
+                let deposit = self
+                    .damage_deposits
+                    .get(&lease_id)
+                    .map(|deposit| deposit.amount)
+                    .unwrap_or(0);
+                lease.escrow_held.saturating_sub(deposit)
+            });
+        let renewals = previous.map(|renewal| renewal.renewals).unwrap_or(0);
+
+        self.auto_renewals.insert(
+            lease_id,
+            AutoRenewal {
+                escalation,
+                notice_period_ns,
+                term_price,
+                status: AutoRenewalStatus::Offered,
+                opted_out_by: None,
+                renewals,
+                pending_bid_id: None,
+            },
+        );
+    }
+
+    /// Tenant agrees to the owner's auto-renewal terms.
+    #[payable]
+    pub fn agree_to_auto_renewal(&mut self, lease_id: u64) {
+        assert_one_yocto();
+
+        let lease = lease_for(self, lease_id);
+        assert_eq!(
+            lease.tenant_id,
+            env::predecessor_account_id(),
+            "Only the tenant can agree to auto-renewal"
+        );
+        require!(lease.active, "Lease is not active");
+        let renewal = renewal_for(self, lease_id);
+        require!(
+            renewal.status == AutoRenewalStatus::Offered,
+            "No auto-renewal terms are waiting on you"
+        );
+
+        if let Some(renewal) = self.auto_renewals.get_mut(&lease_id) {
+            renewal.status = AutoRenewalStatus::Active;
+        }
+        log!("Lease {} now renews automatically", lease_id);
+    }
+
+    /// Tenant or owner stops the lease renewing, up to the notice deadline.
+    /// The lease then runs to its end as usual.
+    #[payable]
+    pub fn opt_out_of_auto_renewal(&mut self, lease_id: u64) {
+        assert_one_yocto();
+
+        let lease = lease_for(self, lease_id);
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == lease.tenant_id || caller == owner_of(self, lease.property_id),
+            "Only the tenant or the owner can opt out"
+        );
+        let renewal = renewal_for(self, lease_id);
+        require!(
+            renewal.status != AutoRenewalStatus::OptedOut,
+            "Auto-renewal is already off"
+        );
+        require!(
+            env::block_timestamp() < notice_deadline(&lease, &renewal),
+            "The deadline to opt out of this renewal has passed"
+        );
+
+        if let Some(renewal) = self.auto_renewals.get_mut(&lease_id) {
+            renewal.status = AutoRenewalStatus::OptedOut;
+            renewal.opted_out_by = Some(caller.clone());
+        }
+
+        emit_event(
+            "AutoRenewalOptedOut",
+            AutoRenewalOptedOutEvent {
+                token_id: lease.property_id,
+                lease_id,
+                opted_out_by: caller,
+                lease_end_time: lease.end_time,
+            },
+        );
+    }
+
+    /// A lease's auto-renewal terms, with what the next term will cost and
+    /// the deadline to opt out of it.
+    pub fn get_auto_renewal(&self, lease_id: u64) -> Option<AutoRenewalView> {
+        let renewal = self.auto_renewals.get(&lease_id)?;
+        let lease = self.leases.get(&lease_id)?;
+        Some(AutoRenewalView {
+            lease_id,
+            escalation: (&renewal.escalation).into(),
+            notice_period_ns: renewal.notice_period_ns,
+            term_price: renewal.term_price.to_string(),
+            next_term_price: next_term_price(renewal).to_string(),
+            notice_deadline: notice_deadline(lease, renewal),
+            status: renewal.status.clone(),
+            opted_out_by: renewal.opted_out_by.as_ref().map(|id| id.to_string()),
+            renewals: renewal.renewals,
+        })
+    }
+}
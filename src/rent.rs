@@ -0,0 +1,325 @@
+use near_sdk::{assert_one_yocto, env, json_types::U128, near_bindgen, require, AccountId};
+
+use crate::{
+    events::{emit_event, RentPaidEvent},
+    internal::checked_add_u64,
+    models::{Action, Lease, RentPayment, RentSchedule, RentTerms},
+    views::{
+        RentArrearsView, RentPaymentView, RentScheduleView, RentTermsView, MAX_PAGINATION_LIMIT,
+    },
+    ShedaContract, ShedaContractExt,
+};
+
+/// Give a new lease its rent schedule, if the property has rent terms.
+/// Called wherever a lease is created.
+pub(crate) fn start_schedule(contract: &mut ShedaContract, property_id: u64, lease_id: u64) {
+    let Some(terms) = contract.rent_terms.get(&property_id).cloned() else {
+        return;
+    };
+    let start_time = contract
+        .leases
+        .get(&lease_id)
+        .expect("Lease not found")
+        .start_time;
+
+    contract.rent_schedules.insert(
+        lease_id,
+        RentSchedule {
+            property_id,
+            first_due_at: checked_add_u64(start_time, terms.period_ns, "rent first due"),
+            terms,
+            payments: Vec::new(),
+        },
+    );
+}
+
+/// Periods that have fallen due by `now` and aren't paid, oldest first, with
+/// the late fee each would carry if paid now. Periods past the lease's end
+/// are never owed.
+fn overdue_periods(schedule: &RentSchedule, lease: &Lease, now: u64) -> Vec<(u32, u128)> {
+    (schedule.next_period()..)
+        .take_while(|&period| {
+            let due_at = schedule.due_at(period);
+            due_at <= now && due_at < lease.end_time
+        })
+        .map(|period| (period, schedule.late_fee(period, now)))
+        .collect()
+}
+
+/// What the tenant of `lease_id` owes in overdue rent and late fees now.
+pub(crate) fn arrears(contract: &ShedaContract, lease_id: u64) -> u128 {
+    let (Some(schedule), Some(lease)) = (
+        contract.rent_schedules.get(&lease_id),
+        contract.leases.get(&lease_id),
+    ) else {
+        return 0;
+    };
+    overdue_periods(schedule, lease, env::block_timestamp())
+        .iter()
+        .map(|(_, fee)| schedule.terms.amount + fee)
+        .sum()
+}
+
+fn arrears_view(
+    lease_id: u64,
+    schedule: &RentSchedule,
+    lease: &Lease,
+    now: u64,
+) -> RentArrearsView {
+    let overdue = overdue_periods(schedule, lease, now);
+    let late_fees: u128 = overdue.iter().map(|(_, fee)| fee).sum();
+    RentArrearsView {
+        lease_id,
+        property_id: schedule.property_id,
+        tenant_id: lease.tenant_id.to_string(),
+        periods_overdue: overdue.len() as u32,
+        amount_overdue: (schedule.terms.amount * overdue.len() as u128 + late_fees).to_string(),
+        late_fees: late_fees.to_string(),
+        oldest_due_at: overdue.first().map(|(period, _)| schedule.due_at(*period)),
+    }
+}
+
+/// Pay rent with an `ft_on_transfer` `PayRent`: as many whole periods as the
+/// amount covers, oldest first and each with its late fee, paid straight on
+/// to the owner (the primary tenant, on a sublease). Returns what was left over.
+///
+/// Periods not due yet can be paid in advance. Arrears can still be settled
+/// after the lease has ended.
+pub(crate) fn pay_rent(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    lease_id: u64,
+    sender_id: AccountId,
+    amount: u128,
+) -> u128 {
+    let lease = contract
+        .leases
+        .get(&lease_id)
+        .cloned()
+        .expect("Lease not found");
+    require!(
+        lease.property_id == property_id,
+        "Lease is not on this property"
+    );
+    require!(lease.tenant_id == sender_id, "Only the tenant can pay rent");
+    require!(
+        lease.escrow_token == env::predecessor_account_id(),
+        "Rent must be paid in the lease's stablecoin"
+    );
+    // A subtenant's rent goes to the primary tenant.
+    let owner_id = crate::sublease::landlord_of(contract, &lease);
+
+    let now = env::block_timestamp();
+    let schedule = contract
+        .rent_schedules
+        .get_mut(&lease_id)
+        .expect("This lease has no rent schedule");
+
+    let mut remaining = amount;
+    let mut paid: Vec<RentPayment> = Vec::new();
+    loop {
+        let period = schedule.next_period();
+        let due_at = schedule.due_at(period);
+        if due_at >= lease.end_time {
+            break;
+        }
+        let late_fee = schedule.late_fee(period, now);
+        let total = schedule.terms.amount + late_fee;
+        if remaining < total {
+            break;
+        }
+        remaining -= total;
+
+        let payment = RentPayment {
+            period,
+            due_at,
+            paid_at: now,
+            amount: schedule.terms.amount,
+            late_fee,
+        };
+        schedule.payments.push(payment.clone());
+        paid.push(payment);
+    }
+
+    if paid.is_empty() {
+        let period = schedule.next_period();
+        require!(
+            schedule.due_at(period) < lease.end_time,
+            "No rent is left to pay on this lease"
+        );
+        env::panic_str(&format!(
+            "Rent for period {} comes to {}",
+            period,
+            schedule.terms.amount + schedule.late_fee(period, now)
+        ));
+    }
+
+    for payment in &paid {
+        emit_event(
+            "RentPaid",
+            RentPaidEvent {
+                token_id: property_id,
+                lease_id,
+                tenant_id: lease.tenant_id.clone(),
+                period: payment.period,
+                due_at: payment.due_at,
+                amount: payment.amount,
+                late_fee: payment.late_fee,
+            },
+        );
+    }
+
+    let total_paid = amount - remaining;
+    let net = crate::fees::charge(
+        contract,
+        property_id,
+        &Action::Lease,
+        &lease.escrow_token,
+        &owner_id,
+        total_paid,
+    );
+    let payouts =
+        crate::payout::owner_payouts(contract, property_id, &lease.escrow_token, owner_id, net);
+    #[allow(unused_must_use)]
+    crate::payout::send_payouts(&lease.escrow_token, payouts);
+
+    remaining
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Owner sets the rent a property is let for. Applies to leases that
+    /// start from now on; a running lease keeps the terms it started with.
+    #[payable]
+    pub fn set_rent_terms(
+        &mut self,
+        property_id: u64,
+        amount: U128,
+        period_ns: u64,
+        late_fee_bps: u16,
+        grace_period_ns: u64,
+    ) {
+        assert_one_yocto();
+
+        let property = self
+            .properties
+            .get(&property_id)
+            .expect("Property not found");
+        assert_eq!(
+            property.owner_id,
+            env::predecessor_account_id(),
+            "Only the property owner can set rent terms"
+        );
+        require!(amount.0 > 0, "Rent must be greater than zero");
+        require!(period_ns > 0, "Rent period must be greater than zero");
+        require!(late_fee_bps <= 10_000, "Basis points can't exceed 10000");
+
+        self.rent_terms.insert(
+            property_id,
+            RentTerms {
+                amount: amount.0,
+                period_ns,
+                late_fee_bps,
+                grace_period_ns,
+            },
+        );
+    }
+
+    /// Owner stops letting the property on recurring rent. Running leases
+    /// keep their schedules.
+    #[payable]
+    pub fn clear_rent_terms(&mut self, property_id: u64) {
+        assert_one_yocto();
+
+        let property = self
+            .properties
+            .get(&property_id)
+            .expect("Property not found");
+        assert_eq!(
+            property.owner_id,
+            env::predecessor_account_id(),
+            "Only the property owner can clear rent terms"
+        );
+        self.rent_terms.remove(&property_id);
+    }
+
+    pub fn get_rent_terms(&self, property_id: u64) -> Option<RentTermsView> {
+        self.rent_terms.get(&property_id).map(|terms| terms.into())
+    }
+
+    pub fn get_rent_schedule(&self, lease_id: u64) -> Option<RentScheduleView> {
+        let schedule = self.rent_schedules.get(&lease_id)?;
+        let lease = self.leases.get(&lease_id)?;
+        let next_period = schedule.next_period();
+        let total_late_fees: u128 = schedule.payments.iter().map(|p| p.late_fee).sum();
+        Some(RentScheduleView {
+            lease_id,
+            property_id: schedule.property_id,
+            terms: (&schedule.terms).into(),
+            first_due_at: schedule.first_due_at,
+            periods_paid: next_period,
+            next_due_at: Some(schedule.due_at(next_period))
+                .filter(|due_at| *due_at < lease.end_time),
+            total_paid: schedule
+                .payments
+                .iter()
+                .map(|p| p.amount + p.late_fee)
+                .sum::<u128>()
+                .to_string(),
+            total_late_fees: total_late_fees.to_string(),
+        })
+    }
+
+    /// Payment history of a lease, oldest first.
+    pub fn get_rent_payments(
+        &self,
+        lease_id: u64,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<RentPaymentView> {
+        let limit = limit.min(MAX_PAGINATION_LIMIT);
+        self.rent_schedules
+            .get(&lease_id)
+            .map(|schedule| {
+                schedule
+                    .payments
+                    .iter()
+                    .skip(from_index as usize)
+                    .take(limit as usize)
+                    .map(|payment| payment.into())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// What a lease owes right now, late fees included.
+    pub fn get_rent_arrears(&self, lease_id: u64) -> Option<RentArrearsView> {
+        let schedule = self.rent_schedules.get(&lease_id)?;
+        let lease = self.leases.get(&lease_id)?;
+        Some(arrears_view(
+            lease_id,
+            schedule,
+            lease,
+            env::block_timestamp(),
+        ))
+    }
+
+    /// Every lease on the owner's properties that is behind on rent.
+    pub fn get_rent_arrears_by_owner(&self, owner_id: AccountId) -> Vec<RentArrearsView> {
+        let now = env::block_timestamp();
+        let owned = self
+            .property_per_owner
+            .get(&owner_id)
+            .cloned()
+            .unwrap_or_default();
+        self.rent_schedules
+            .iter()
+            .filter(|(_, schedule)| owned.contains(&schedule.property_id))
+            .filter_map(|(lease_id, schedule)| {
+                let lease = self.leases.get(lease_id)?;
+                Some(arrears_view(*lease_id, schedule, lease, now))
+            })
+            .filter(|arrears| arrears.periods_overdue > 0)
+            .collect()
+    }
+}
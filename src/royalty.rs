@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use near_contract_standards::non_fungible_token::core::NonFungibleTokenCore;
+use near_sdk::{assert_one_yocto, env, json_types::U128, near_bindgen, require, AccountId};
+
+use crate::{
+    events::{emit_event, RoyaltiesPaidEvent, RoyaltiesSetEvent},
+    internal::checked_add_u128,
+    models::{Action, Bid, PayoutShare},
+    views::{Payout, PayoutShareView},
+    ShedaContract, ShedaContractExt, TokenId,
+};
+
+/// Royalties can't take more than half of a sale.
+const MAX_ROYALTY_BPS: u128 = 5_000;
+/// Leaves room for the seller within the usual marketplace `max_len_payout`.
+const MAX_ROYALTY_RECIPIENTS: usize = 9;
+
+fn owner_of(contract: &ShedaContract, property_id: u64) -> AccountId {
+    contract
+        .properties
+        .get(&property_id)
+        .map(|property| property.owner_id.clone())
+        .expect("Property not found")
+}
+
+/// Each royalty holder's cut of a sale of `amount` by `seller`. A seller
+/// who is also a royalty holder keeps their cut as part of the proceeds.
+fn royalty_cuts(
+    contract: &ShedaContract,
+    property_id: u64,
+    seller: &AccountId,
+    amount: u128,
+) -> Vec<(AccountId, u128)> {
+    contract
+        .property_royalties
+        .get(&property_id)
+        .map(|royalties| {
+            royalties
+                .iter()
+                .filter(|royalty| royalty.account_id != *seller)
+                .map(|royalty| {
+                    let cut = amount
+                        .checked_mul(u128::from(royalty.bps))
+                        .unwrap_or_else(|| env::panic_str("Overflow in royalty"))
+                        / 10_000;
+                    (royalty.account_id.clone(), cut)
+                })
+                .filter(|(_, cut)| *cut > 0)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn total(cuts: &[(AccountId, u128)]) -> u128 {
+    cuts.iter().fold(0, |total, (_, cut)| {
+        checked_add_u128(total, *cut, "royalties")
+    })
+}
+
+fn send(property_id: u64, bid_id: Option<u64>, token: &AccountId, cuts: Vec<(AccountId, u128)>) {
+    if cuts.is_empty() {
+        return;
+    }
+    emit_event(
+        "RoyaltiesPaid",
+        RoyaltiesPaidEvent {
+            token_id: property_id,
+            bid_id,
+            royalties: cuts.clone(),
+        },
+    );
+    #[allow(unused_must_use)]
+    crate::payout::send_payouts(token, cuts);
+}
+
+/// Hold the royalties on a sale back from the seller's `net` proceeds of a
+/// `gross` payment, to be paid when the sale completes. Returns what's left
+/// for the seller. Leases pay no royalties.
+pub(crate) fn hold_back(contract: &mut ShedaContract, bid: &Bid, gross: u128, net: u128) -> u128 {
+    if bid.action != Action::Purchase {
+        return net;
+    }
+    let seller = owner_of(contract, bid.property_id);
+    let cuts = royalty_cuts(contract, bid.property_id, &seller, gross);
+    let royalties = total(&cuts);
+    require!(
+        royalties <= net,
+        "Royalties and fees come to more than the sale"
+    );
+    if !cuts.is_empty() {
+        contract.pending_royalties.insert(bid.id, cuts);
+    }
+    net - royalties
+}
+
+/// The sale went through: pay the royalties held back from it. Called from
+/// `finalize_accepted_bid` and `release_escrow_callback`.
+pub(crate) fn pay(contract: &mut ShedaContract, bid: &Bid) {
+    if let Some(cuts) = contract.pending_royalties.remove(&bid.id) {
+        send(bid.property_id, Some(bid.id), &bid.stablecoin_token, cuts);
+    }
+}
+
+/// The seller's payment failed and the sale is off; the royalties go back
+/// to escrow with the rest of it.
+pub(crate) fn drop_pending(contract: &mut ShedaContract, bid_id: u64) {
+    contract.pending_royalties.remove(&bid_id);
+}
+
+/// Pay the royalties on a `gross` sale payment that passes straight through
+/// the contract, such as an instalment, out of the seller's `net`. Returns
+/// what's left for the seller.
+pub(crate) fn charge(
+    contract: &ShedaContract,
+    property_id: u64,
+    token: &AccountId,
+    seller: &AccountId,
+    gross: u128,
+    net: u128,
+) -> u128 {
+    let cuts = royalty_cuts(contract, property_id, seller, gross);
+    let royalties = total(&cuts);
+    require!(
+        royalties <= net,
+        "Royalties and fees come to more than the sale"
+    );
+    send(property_id, None, token, cuts);
+    net - royalties
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Set the royalties paid on every sale of a property — to its developer,
+    /// say, or the platform — here and on NEP-199 marketplaces. The owner can
+    /// set them once; after that only an admin can change or clear them, so
+    /// a later owner can't write them off before reselling.
+    #[payable]
+    pub fn set_property_royalties(&mut self, property_id: u64, royalties: Vec<PayoutShare>) {
+        assert_one_yocto();
+
+        let caller = env::predecessor_account_id();
+        let is_admin = self.admins.contains(&caller);
+        require!(
+            is_admin
+                || (owner_of(self, property_id) == caller
+                    && !self.property_royalties.contains_key(&property_id)),
+            "Only the owner can set royalties, and only once; after that it takes an admin"
+        );
+        require!(
+            royalties.len() <= MAX_ROYALTY_RECIPIENTS,
+            format!(
+                "A property can have at most {} royalty recipients",
+                MAX_ROYALTY_RECIPIENTS
+            )
+        );
+        require!(
+            royalties.iter().all(|royalty| royalty.bps > 0),
+            "Every royalty needs a share above zero"
+        );
+        require!(
+            royalties
+                .iter()
+                .map(|royalty| u128::from(royalty.bps))
+                .sum::<u128>()
+                <= MAX_ROYALTY_BPS,
+            "Royalties can't exceed 5000 basis points"
+        );
+        for (index, royalty) in royalties.iter().enumerate() {
+            require!(
+                royalties[..index]
+                    .iter()
+                    .all(|earlier| earlier.account_id != royalty.account_id),
+                "A royalty recipient can only appear once"
+            );
+        }
+
+        emit_event(
+            "RoyaltiesSet",
+            RoyaltiesSetEvent {
+                token_id: property_id,
+                set_by: caller,
+                royalties: royalties
+                    .iter()
+                    .map(|royalty| (royalty.account_id.clone(), royalty.bps))
+                    .collect(),
+            },
+        );
+        if royalties.is_empty() {
+            self.property_royalties.remove(&property_id);
+        } else {
+            self.property_royalties.insert(property_id, royalties);
+        }
+    }
+
+    pub fn get_property_royalties(&self, property_id: u64) -> Vec<PayoutShareView> {
+        self.property_royalties
+            .get(&property_id)
+            .map(|royalties| royalties.iter().map(|royalty| royalty.into()).collect())
+            .unwrap_or_default()
+    }
+
+    /// NEP-199: how a sale of the token for `balance` would be shared out
+    /// between the royalty holders and its current holder.
+    pub fn nft_payout(
+        &self,
+        token_id: TokenId,
+        balance: U128,
+        max_len_payout: Option<u32>,
+    ) -> Payout {
+        let property_id = token_id.parse::<u64>().expect("Invalid token ID");
+        let holder = self
+            .tokens
+            .owner_by_id
+            .get(&token_id)
+            .expect("Token not found");
+        let cuts = royalty_cuts(self, property_id, &holder, balance.0);
+        if let Some(max_len) = max_len_payout {
+            require!(
+                cuts.len() < max_len as usize,
+                "Payout is longer than max_len_payout"
+            );
+        }
+
+        let royalties = total(&cuts);
+        let mut payout: HashMap<AccountId, U128> = cuts
+            .into_iter()
+            .map(|(account_id, cut)| (account_id, U128(cut)))
+            .collect();
+        payout.insert(holder, U128(balance.0 - royalties));
+        Payout { payout }
+    }
+
+    /// NEP-199: transfer the token as `nft_transfer` does and return how the
+    /// marketplace should share out the sale.
+    #[payable]
+    pub fn nft_transfer_payout(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        balance: U128,
+        max_len_payout: Option<u32>,
+    ) -> Payout {
+        let payout = self.nft_payout(token_id.clone(), balance, max_len_payout);
+        self.nft_transfer(receiver_id, token_id, approval_id, memo);
+        payout
+    }
+}
@@ -0,0 +1,421 @@
+use near_sdk::{assert_one_yocto, env, json_types::U128, log, near_bindgen, require, AccountId};
+
+use crate::{
+    events::{emit_event, LeaseAssignedEvent, SubleaseStartedEvent},
+    internal::checked_add_u64,
+    models::{DisputeStatus, Lease, LeaseAssignment, RentSchedule, RentTerms, SubleaseOffer},
+    views::{LeaseAssignmentView, LeaseView, SubleaseOfferView},
+    ShedaContract, ShedaContractExt,
+};
+
+fn lease_for(contract: &ShedaContract, lease_id: u64) -> Lease {
+    contract
+        .leases
+        .get(&lease_id)
+        .cloned()
+        .expect("Lease not found")
+}
+
+/// The primary lease a sublease was let from, if `lease_id` is a sublease.
+pub(crate) fn parent_of(contract: &ShedaContract, lease_id: u64) -> Option<Lease> {
+    let parent_id = contract.subleases.get(&lease_id)?;
+    contract.leases.get(parent_id).cloned()
+}
+
+/// Who a lease's rent and refunds are settled with: the primary tenant for a
+/// sublease, the property owner otherwise.
+pub(crate) fn landlord_of(contract: &ShedaContract, lease: &Lease) -> AccountId {
+    match parent_of(contract, lease.id) {
+        Some(parent) => parent.tenant_id,
+        None => contract
+            .properties
+            .get(&lease.property_id)
+            .expect("Property not found")
+            .owner_id
+            .clone(),
+    }
+}
+
+/// Every sublease let from `lease_id`, ended ones included.
+fn subleases_of(contract: &ShedaContract, lease_id: u64) -> Vec<u64> {
+    contract
+        .subleases_per_lease
+        .get(&lease_id)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// The running sublease under `lease_id`, if any.
+fn active_sublease(contract: &ShedaContract, lease_id: u64) -> Option<u64> {
+    subleases_of(contract, lease_id)
+        .into_iter()
+        .find(|child_id| contract.leases.get(child_id).is_some_and(|l| l.active))
+}
+
+fn assert_no_sublease(contract: &ShedaContract, lease_id: u64) {
+    require!(
+        active_sublease(contract, lease_id).is_none(),
+        "The lease is sublet"
+    );
+}
+
+/// Where the NFT goes when `lease_id` ends: back to the primary tenant for a
+/// sublease, to the owner (or the contract, holding it for shareholders)
+/// otherwise.
+pub(crate) fn return_nft_to(contract: &ShedaContract, lease: &Lease) -> AccountId {
+    match parent_of(contract, lease.id).filter(|parent| parent.active) {
+        Some(parent) => parent.tenant_id,
+        None => crate::fraction::nft_custodian(contract, lease.property_id),
+    }
+}
+
+/// A primary lease is ending: any sublease under it ends with it. The NFT is
+/// left for the caller to move.
+pub(crate) fn close_subleases(contract: &mut ShedaContract, lease_id: u64) {
+    if let Some(child_id) = active_sublease(contract, lease_id) {
+        if let Some(child) = contract.leases.get_mut(&child_id) {
+            child.active = false;
+        }
+        log!("Sublease {} ended with lease {}", child_id, lease_id);
+    }
+    contract.sublease_offers.remove(&lease_id);
+    contract.lease_assignments.remove(&lease_id);
+}
+
+fn assert_can_hand_on(contract: &ShedaContract, lease: &Lease) {
+    require!(lease.active, "Lease is not active");
+    require!(!lease.has_open_dispute(), "A dispute is open on this lease");
+    crate::termination::assert_no_notice(contract, lease.id);
+    require!(
+        !contract.lease_co_tenants.contains_key(&lease.id),
+        "A lease held jointly can't be handed on"
+    );
+}
+
+fn index_tenant(contract: &mut ShedaContract, tenant_id: &AccountId, lease_id: u64) {
+    let mut leases = contract
+        .lease_per_tenant
+        .get(tenant_id)
+        .cloned()
+        .unwrap_or_default();
+    leases.push(lease_id);
+    contract.lease_per_tenant.insert(tenant_id.clone(), leases);
+}
+
+fn unindex_tenant(contract: &mut ShedaContract, tenant_id: &AccountId, lease_id: u64) {
+    if let Some(leases) = contract.lease_per_tenant.get_mut(tenant_id) {
+        leases.retain(|id| *id != lease_id);
+    }
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Tenant offers the rest of their lease to `assignee`. The owner has to
+    /// consent before the assignee can accept.
+    #[payable]
+    pub fn propose_lease_assignment(&mut self, lease_id: u64, assignee: AccountId) {
+        assert_one_yocto();
+
+        let lease = lease_for(self, lease_id);
+        assert_eq!(
+            lease.tenant_id,
+            env::predecessor_account_id(),
+            "Only the tenant can assign the lease"
+        );
+        require!(assignee != lease.tenant_id, "That's already the tenant");
+        require!(
+            parent_of(self, lease_id).is_none(),
+            "A sublease can't be assigned"
+        );
+        assert_can_hand_on(self, &lease);
+        assert_no_sublease(self, lease_id);
+
+        self.lease_assignments.insert(
+            lease_id,
+            LeaseAssignment {
+                assignee,
+                proposed_at: env::block_timestamp(),
+                owner_consented: false,
+            },
+        );
+    }
+
+    /// Owner agrees to the proposed assignment.
+    #[payable]
+    pub fn consent_to_lease_assignment(&mut self, lease_id: u64) {
+        assert_one_yocto();
+
+        let lease = lease_for(self, lease_id);
+        assert_eq!(
+            landlord_of(self, &lease),
+            env::predecessor_account_id(),
+            "Only the property owner can consent to an assignment"
+        );
+        let assignment = self
+            .lease_assignments
+            .get_mut(&lease_id)
+            .expect("No assignment has been proposed");
+        assignment.owner_consented = true;
+    }
+
+    /// Tenant withdraws the proposal, or owner turns it down.
+    #[payable]
+    pub fn cancel_lease_assignment(&mut self, lease_id: u64) {
+        assert_one_yocto();
+
+        let lease = lease_for(self, lease_id);
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == lease.tenant_id || caller == landlord_of(self, &lease),
+            "Only the tenant or the owner can cancel an assignment"
+        );
+        require!(
+            self.lease_assignments.remove(&lease_id).is_some(),
+            "No assignment has been proposed"
+        );
+    }
+
+    /// The assignee takes the lease over: the NFT, the rest of the term, any
+    /// rent still to fall due and the damage deposit all pass to them.
+    #[payable]
+    pub fn accept_lease_assignment(&mut self, lease_id: u64) {
+        assert_one_yocto();
+
+        let assignment = self
+            .lease_assignments
+            .get(&lease_id)
+            .cloned()
+            .expect("No assignment has been proposed");
+        let assignee = env::predecessor_account_id();
+        assert_eq!(
+            assignment.assignee, assignee,
+            "Only the assignee can accept the lease"
+        );
+        require!(
+            assignment.owner_consented,
+            "The owner hasn't consented to the assignment"
+        );
+        let mut lease = lease_for(self, lease_id);
+        assert_can_hand_on(self, &lease);
+        assert_no_sublease(self, lease_id);
+
+        let previous_tenant = lease.tenant_id.clone();
+        self.tokens.internal_transfer(
+            &previous_tenant,
+            &assignee,
+            &lease.property_id.to_string(),
+            None,
+            None,
+        );
+
+        lease.tenant_id = assignee.clone();
+        self.leases.insert(lease_id, lease.clone());
+        if let Some(property) = self.properties.get_mut(&lease.property_id) {
+            property.active_lease = Some(lease.clone());
+        }
+        if let Some(deposit) = self.damage_deposits.get_mut(&lease_id) {
+            deposit.tenant_id = assignee.clone();
+        }
+        unindex_tenant(self, &previous_tenant, lease_id);
+        index_tenant(self, &assignee, lease_id);
+        self.lease_assignments.remove(&lease_id);
+
+        emit_event(
+            "LeaseAssigned",
+            LeaseAssignedEvent {
+                token_id: lease.property_id,
+                lease_id,
+                from_tenant_id: previous_tenant,
+                to_tenant_id: assignee,
+            },
+        );
+    }
+
+    /// Primary tenant offers to sublet to `subtenant_id` until `end_time`,
+    /// which must fall within their own lease. With `rent_amount` set the
+    /// subtenant pays rent every `rent_period_ns` from the day they move in,
+    /// to the primary tenant. A new offer replaces the last.
+    #[payable]
+    pub fn offer_sublease(
+        &mut self,
+        lease_id: u64,
+        subtenant_id: AccountId,
+        end_time: u64,
+        rent_amount: Option<U128>,
+        rent_period_ns: Option<u64>,
+    ) {
+        assert_one_yocto();
+
+        let lease = lease_for(self, lease_id);
+        assert_eq!(
+            lease.tenant_id,
+            env::predecessor_account_id(),
+            "Only the tenant can sublet"
+        );
+        require!(subtenant_id != lease.tenant_id, "That's already the tenant");
+        require!(
+            parent_of(self, lease_id).is_none(),
+            "A sublease can't be sublet again"
+        );
+        assert_can_hand_on(self, &lease);
+        assert_no_sublease(self, lease_id);
+        require!(
+            end_time > env::block_timestamp() && end_time <= lease.end_time,
+            "The sublease must end within the lease"
+        );
+
+        let rent = match (rent_amount, rent_period_ns) {
+            (None, None) => None,
+            (Some(amount), Some(period_ns)) => {
+                require!(amount.0 > 0, "Rent must be greater than zero");
+                require!(period_ns > 0, "Rent period must be greater than zero");
+                Some(RentTerms {
+                    amount: amount.0,
+                    period_ns,
+                    late_fee_bps: 0,
+                    grace_period_ns: 0,
+                })
+            }
+            _ => env::panic_str("Give both the rent and its period, or neither"),
+        };
+
+        self.sublease_offers.insert(
+            lease_id,
+            SubleaseOffer {
+                subtenant_id,
+                end_time,
+                rent,
+                offered_at: env::block_timestamp(),
+            },
+        );
+    }
+
+    #[payable]
+    pub fn withdraw_sublease_offer(&mut self, lease_id: u64) {
+        assert_one_yocto();
+
+        let lease = lease_for(self, lease_id);
+        assert_eq!(
+            lease.tenant_id,
+            env::predecessor_account_id(),
+            "Only the tenant can withdraw the offer"
+        );
+        require!(
+            self.sublease_offers.remove(&lease_id).is_some(),
+            "No sublease is on offer"
+        );
+    }
+
+    /// Subtenant takes up the offer. The sublease is a lease of its own: the
+    /// subtenant holds the NFT until it ends, then it goes back to the
+    /// primary tenant. Returns the sublease's lease id.
+    #[payable]
+    pub fn accept_sublease(&mut self, lease_id: u64) -> u64 {
+        assert_one_yocto();
+
+        let offer = self
+            .sublease_offers
+            .get(&lease_id)
+            .cloned()
+            .expect("No sublease is on offer");
+        let subtenant_id = env::predecessor_account_id();
+        assert_eq!(
+            offer.subtenant_id, subtenant_id,
+            "The sublease was offered to someone else"
+        );
+        let parent = lease_for(self, lease_id);
+        assert_can_hand_on(self, &parent);
+        assert_no_sublease(self, lease_id);
+        let now = env::block_timestamp();
+        require!(
+            offer.end_time > now && offer.end_time <= parent.end_time,
+            "The sublease must end within the lease"
+        );
+
+        let sublease_id = self.lease_counter;
+        self.lease_counter = checked_add_u64(self.lease_counter, 1, "lease_counter");
+        self.leases.insert(
+            sublease_id,
+            Lease {
+                id: sublease_id,
+                property_id: parent.property_id,
+                tenant_id: subtenant_id.clone(),
+                start_time: now,
+                end_time: offer.end_time,
+                active: true,
+                dispute_status: DisputeStatus::None,
+                dispute: None,
+                escrow_held: 0,
+                escrow_token: parent.escrow_token.clone(),
+            },
+        );
+        self.subleases.insert(sublease_id, lease_id);
+        self.subleases_per_lease
+            .entry(lease_id)
+            .or_default()
+            .push(sublease_id);
+        index_tenant(self, &subtenant_id, sublease_id);
+        self.sublease_offers.remove(&lease_id);
+
+        // Nothing was paid up front, so the first period is due on moving in.
+        if let Some(terms) = offer.rent {
+            self.rent_schedules.insert(
+                sublease_id,
+                RentSchedule {
+                    property_id: parent.property_id,
+                    terms,
+                    first_due_at: now,
+                    payments: Vec::new(),
+                },
+            );
+        }
+
+        self.tokens.internal_transfer(
+            &parent.tenant_id,
+            &subtenant_id,
+            &parent.property_id.to_string(),
+            None,
+            None,
+        );
+
+        emit_event(
+            "SubleaseStarted",
+            SubleaseStartedEvent {
+                token_id: parent.property_id,
+                lease_id: sublease_id,
+                parent_lease_id: lease_id,
+                tenant_id: parent.tenant_id,
+                subtenant_id,
+                end_time: offer.end_time,
+            },
+        );
+
+        sublease_id
+    }
+
+    pub fn get_lease_assignment(&self, lease_id: u64) -> Option<LeaseAssignmentView> {
+        self.lease_assignments
+            .get(&lease_id)
+            .map(|assignment| assignment.into())
+    }
+
+    pub fn get_sublease_offer(&self, lease_id: u64) -> Option<SubleaseOfferView> {
+        self.sublease_offers
+            .get(&lease_id)
+            .map(|offer| offer.into())
+    }
+
+    /// Every sublease let from `lease_id`, ended ones included.
+    pub fn get_subleases(&self, lease_id: u64) -> Vec<LeaseView> {
+        subleases_of(self, lease_id)
+            .iter()
+            .filter_map(|child_id| self.leases.get(child_id))
+            .map(|lease| self.lease_to_view(lease))
+            .collect()
+    }
+
+    /// The lease `lease_id` was sublet from, if it's a sublease.
+    pub fn get_parent_lease(&self, lease_id: u64) -> Option<u64> {
+        self.subleases.get(&lease_id).copied()
+    }
+}
@@ -0,0 +1,293 @@
+use near_sdk::{
+    assert_one_yocto, env, json_types::U128, log, near_bindgen, require, AccountId, Gas, NearToken,
+};
+
+use crate::{
+    events::{emit_event, LeaseTerminatedEarlyEvent, LeaseTerminationNoticedEvent},
+    ext::ft_contract,
+    internal::{checked_add_u128, checked_add_u64, close_lease},
+    models::{Lease, LeaseTermination, TerminationStatus},
+    sublease::landlord_of,
+    views::LeaseTerminationView,
+    ShedaContract, ShedaContractExt,
+};
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+fn lease_for(contract: &ShedaContract, lease_id: u64) -> Lease {
+    contract
+        .leases
+        .get(&lease_id)
+        .cloned()
+        .expect("Lease not found")
+}
+
+fn notice_for(contract: &ShedaContract, lease_id: u64) -> LeaseTermination {
+    contract
+        .lease_terminations
+        .get(&lease_id)
+        .cloned()
+        .expect("No notice has been given on this lease")
+}
+
+/// A renewal would move the end date the notice was priced against.
+pub(crate) fn assert_no_notice(contract: &ShedaContract, lease_id: u64) {
+    require!(
+        !contract
+            .lease_terminations
+            .get(&lease_id)
+            .is_some_and(|notice| notice.status == TerminationStatus::Noticed),
+        "Notice has been given to end this lease"
+    );
+}
+
+/// Pay the refund on an early termination with an `ft_on_transfer`
+/// `PayTerminationRefund`, passed straight on to the tenant. Returns what was
+/// sent beyond the refund.
+pub(crate) fn pay_termination_refund(
+    contract: &mut ShedaContract,
+    property_id: u64,
+    lease_id: u64,
+    sender_id: AccountId,
+    amount: u128,
+) -> u128 {
+    let lease = lease_for(contract, lease_id);
+    require!(
+        lease.property_id == property_id,
+        "Lease is not on this property"
+    );
+    require!(
+        landlord_of(contract, &lease) == sender_id,
+        "Only the owner pays the termination refund"
+    );
+    require!(
+        lease.escrow_token == env::predecessor_account_id(),
+        "The refund must be paid in the lease's stablecoin"
+    );
+    let notice = notice_for(contract, lease_id);
+    require!(
+        notice.status != TerminationStatus::Withdrawn,
+        "The notice was withdrawn"
+    );
+    require!(!notice.refund_paid, "The refund has already been paid");
+    require!(notice.refund_due > 0, "No refund is due");
+    require!(
+        amount >= notice.refund_due,
+        format!("The refund comes to {}", notice.refund_due)
+    );
+
+    if let Some(notice) = contract.lease_terminations.get_mut(&lease_id) {
+        notice.refund_paid = true;
+    }
+
+    #[allow(unused_must_use)]
+    ft_contract::ext(lease.escrow_token.clone())
+        .with_attached_deposit(NearToken::from_yoctonear(1))
+        .with_static_gas(Gas::from_tgas(30))
+        .ft_transfer(lease.tenant_id.clone(), U128(notice.refund_due))
+        .then(
+            ShedaContract::ext(env::current_account_id())
+                .with_static_gas(Gas::from_tgas(10))
+                .termination_refund_callback(
+                    lease.tenant_id,
+                    lease.escrow_token,
+                    U128(notice.refund_due),
+                ),
+        );
+
+    amount - notice.refund_due
+}
+
+#[near_bindgen]
+impl ShedaContract {
+    /// Tenant or owner gives notice to end a lease before its term is up.
+    ///
+    /// The lease ends `lease_early_termination_window_ns` from now. The
+    /// unused share of the lease payment is refunded to the tenant by the
+    /// owner, less `early_termination_penalty_bps` of the payment when the
+    /// tenant gives notice and plus it when the owner does.
+    #[payable]
+    pub fn give_lease_termination_notice(&mut self, lease_id: u64) {
+        assert_one_yocto();
+
+        let lease = lease_for(self, lease_id);
+        let owner_id = landlord_of(self, &lease);
+        let caller = env::predecessor_account_id();
+        let by_tenant = caller == lease.tenant_id;
+        require!(
+            by_tenant || caller == owner_id,
+            "Only the tenant or the owner can end a lease early"
+        );
+        require!(lease.active, "Lease is not active");
+        require!(!lease.has_open_dispute(), "A dispute is open on this lease");
+        assert_no_notice(self, lease_id);
+
+        let now = env::block_timestamp();
+        let effective_at = checked_add_u64(
+            now,
+            self.lease_early_termination_window_ns,
+            "termination notice",
+        );
+        require!(
+            effective_at < lease.end_time,
+            "The lease ends before the notice period would; let it expire"
+        );
+
+        // The lease payment is what the owner was paid for the whole term;
+        // a damage deposit held with it isn't part of it.
+        let deposit = self
+            .damage_deposits
+            .get(&lease_id)
+            .map(|deposit| deposit.amount)
+            .unwrap_or(0);
+        let payment = lease.escrow_held.saturating_sub(deposit);
+        let term = u128::from(lease.end_time - lease.start_time);
+        let unused = payment * u128::from(lease.end_time - effective_at) / term;
+        let penalty = payment * u128::from(self.early_termination_penalty_bps) / BPS_DENOMINATOR;
+        let refund_due = if by_tenant {
+            unused.saturating_sub(penalty)
+        } else {
+            checked_add_u128(unused, penalty, "termination refund")
+        };
+
+        self.lease_terminations.insert(
+            lease_id,
+            LeaseTermination {
+                initiated_by: caller.clone(),
+                noticed_at: now,
+                effective_at,
+                unused,
+                penalty,
+                refund_due,
+                refund_paid: false,
+                status: TerminationStatus::Noticed,
+            },
+        );
+
+        emit_event(
+            "LeaseTerminationNoticed",
+            LeaseTerminationNoticedEvent {
+                token_id: lease.property_id,
+                lease_id,
+                initiated_by: caller,
+                effective_at,
+                refund_due,
+            },
+        );
+    }
+
+    /// Whoever gave notice takes it back, before it takes effect. An owner
+    /// who has already paid the refund can't.
+    #[payable]
+    pub fn withdraw_lease_termination_notice(&mut self, lease_id: u64) {
+        assert_one_yocto();
+
+        let notice = notice_for(self, lease_id);
+        assert_eq!(
+            notice.initiated_by,
+            env::predecessor_account_id(),
+            "Only whoever gave notice can withdraw it"
+        );
+        require!(
+            notice.status == TerminationStatus::Noticed,
+            "The notice isn't pending"
+        );
+        require!(
+            env::block_timestamp() < notice.effective_at,
+            "The notice has already taken effect"
+        );
+        require!(!notice.refund_paid, "The refund has already been paid");
+
+        if let Some(notice) = self.lease_terminations.get_mut(&lease_id) {
+            notice.status = TerminationStatus::Withdrawn;
+        }
+        log!("Termination notice on lease {} withdrawn", lease_id);
+    }
+
+    /// End a lease whose notice period is over. Anyone may call it.
+    ///
+    /// The NFT goes back to the owner as on expiry and the lease's end date
+    /// moves up, so no rent falls due after it. When the owner gave notice
+    /// they must have paid the refund first. A tenant's notice doesn't wait on
+    /// the owner: an unpaid refund stays on the notice as a debt the owner can
+    /// still settle with `PayTerminationRefund`, and the tenant can dispute.
+    pub fn complete_lease_termination(&mut self, lease_id: u64) {
+        let notice = notice_for(self, lease_id);
+        require!(
+            notice.status == TerminationStatus::Noticed,
+            "The notice isn't pending"
+        );
+        let now = env::block_timestamp();
+        require!(
+            now >= notice.effective_at,
+            "The notice period hasn't run yet"
+        );
+
+        let mut lease = lease_for(self, lease_id);
+        require!(lease.active, "Lease is already inactive");
+        require!(!lease.has_open_dispute(), "A dispute is open on this lease");
+        let owner_id = landlord_of(self, &lease);
+        let by_tenant = notice.initiated_by == lease.tenant_id;
+        let refund_outstanding = if notice.refund_paid {
+            0
+        } else {
+            notice.refund_due
+        };
+        require!(
+            by_tenant || refund_outstanding == 0,
+            "The owner has to pay the termination refund first"
+        );
+
+        lease.end_time = notice.effective_at;
+        self.leases.insert(lease_id, lease.clone());
+        let escrow_returned = close_lease(self, lease_id);
+        if let Some(notice) = self.lease_terminations.get_mut(&lease_id) {
+            notice.status = TerminationStatus::Completed;
+        }
+
+        emit_event(
+            "LeaseTerminatedEarly",
+            LeaseTerminatedEarlyEvent {
+                token_id: lease.property_id,
+                lease_id,
+                tenant_id: lease.tenant_id,
+                owner_id,
+                initiated_by: notice.initiated_by,
+                refund: notice.refund_due,
+                refund_outstanding,
+                penalty: notice.penalty,
+                escrow_returned,
+            },
+        );
+    }
+
+    #[private]
+    pub fn termination_refund_callback(
+        &mut self,
+        tenant_id: AccountId,
+        stablecoin_token: AccountId,
+        amount: U128,
+    ) {
+        if crate::internal::promise_failed(0) {
+            // The refund counts as paid — the owner did pay it. The tenant
+            // can claim it.
+            crate::payout::hold_for_claim(self, &tenant_id, &stablecoin_token, amount.0);
+            log!(
+                "Termination refund of {} to {} failed, held for them to claim",
+                amount.0,
+                tenant_id
+            );
+        }
+    }
+
+    /// The early-termination notice on a lease, if one was ever given.
+    pub fn get_lease_termination(&self, lease_id: u64) -> Option<LeaseTerminationView> {
+        self.lease_terminations
+            .get(&lease_id)
+            .map(|notice| notice.into())
+    }
+
+    pub fn get_early_termination_penalty_bps(&self) -> u16 {
+        self.early_termination_penalty_bps
+    }
+}
@@ -1,13 +1,14 @@
 use crate::models::*;
 use crate::{ShedaContract, ShedaContractExt};
-use near_sdk::{env, near_bindgen, AccountId};
+use near_sdk::{env, json_types::U128, near_bindgen, AccountId};
 use schemars::JsonSchema;
+use std::collections::HashMap;
 
 /// View structs for JSON serialization - separate from internal models
 
 // Default pagination limit for view methods
 const DEFAULT_PAGINATION_LIMIT: u64 = 100;
-const MAX_PAGINATION_LIMIT: u64 = 200;
+pub(crate) const MAX_PAGINATION_LIMIT: u64 = 200;
 
 #[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
 pub struct DisputeStatusView {
@@ -83,6 +84,395 @@ pub struct UserStatsView {
     pub active_leases: u64,
 }
 
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct AuctionView {
+    pub property_id: u64,
+    pub seller_id: String,
+    pub mode: String,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub reserve_price: String, // u128 as string for JSON
+    pub min_increment: Option<String>,
+    /// The leading bid (English) or leading revealed commit (sealed).
+    pub highest_bid_id: Option<u64>,
+    pub reveal_end: Option<u64>,
+    /// Dutch only. `reserve_price` is the floor the price falls to.
+    pub start_price: Option<String>,
+    pub current_price: Option<String>,
+    pub use_escrow: Option<bool>,
+    pub settled: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct CounterOfferView {
+    pub round: u32,
+    pub proposer_id: String,
+    pub amount: String, // u128 as string for JSON
+    pub terms: Option<String>,
+    pub created_at: u64,
+    pub status: OfferStatus,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct SealedCommitView {
+    pub id: u64,
+    pub property_id: u64,
+    pub bidder_id: String,
+    pub commitment: String,
+    pub deposit: String, // u128 as string for JSON
+    pub stablecoin_token: String,
+    pub created_at: u64,
+    pub revealed_amount: Option<String>,
+    pub refunded: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct EarnestMoneyView {
+    pub offer_price: String, // u128 as string for JSON
+    pub deposit: String,
+    /// Still owed at closing; "0" once paid.
+    pub balance_due: String,
+    pub balance_due_by: Option<u64>,
+    pub balance_paid: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct InstalmentPlanView {
+    pub price: String, // u128 as string for JSON
+    pub down_payment: String,
+    pub instalment_count: u32,
+    pub interval_ns: u64,
+    pub paid_instalments: u32,
+    /// What the next instalment comes to; None once the plan has ended.
+    pub next_instalment: Option<String>,
+    pub next_due_at: Option<u64>,
+    pub amount_paid: String,
+    pub remaining: String,
+    pub status: InstalmentStatus,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct RentTermsView {
+    pub amount: String, // u128 as string for JSON
+    pub period_ns: u64,
+    pub late_fee_bps: u16,
+    pub grace_period_ns: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct RentScheduleView {
+    pub lease_id: u64,
+    pub property_id: u64,
+    pub terms: RentTermsView,
+    pub first_due_at: u64,
+    pub periods_paid: u32,
+    /// None once every period up to the lease's end is paid.
+    pub next_due_at: Option<u64>,
+    pub total_paid: String,
+    pub total_late_fees: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct RentPaymentView {
+    pub period: u32,
+    pub due_at: u64,
+    pub paid_at: u64,
+    pub amount: String, // u128 as string for JSON
+    pub late_fee: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct RentArrearsView {
+    pub lease_id: u64,
+    pub property_id: u64,
+    pub tenant_id: String,
+    pub periods_overdue: u32,
+    /// Rent plus the late fees it would carry if paid now.
+    pub amount_overdue: String,
+    pub late_fees: String,
+    pub oldest_due_at: Option<u64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct LeaseTerminationView {
+    pub initiated_by: String,
+    pub noticed_at: u64,
+    pub effective_at: u64,
+    pub unused: String, // u128 as string for JSON
+    pub penalty: String,
+    pub refund_due: String,
+    pub refund_paid: bool,
+    pub status: TerminationStatus,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct LeaseAssignmentView {
+    pub assignee: String,
+    pub proposed_at: u64,
+    pub owner_consented: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct SubleaseOfferView {
+    pub subtenant_id: String,
+    pub end_time: u64,
+    pub rent: Option<RentTermsView>,
+    pub offered_at: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct CoPartyView {
+    pub account_id: String,
+    pub contributed: String, // u128 as string for JSON
+    pub share_bps: u16,
+    pub refunded: bool,
+}
+
+/// One account's part in a jointly funded bid.
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct JointBidView {
+    pub bid_id: u64,
+    pub property_id: u64,
+    pub bidder_id: String,
+    pub contributed: String, // u128 as string for JSON
+    pub refunded: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub enum EscalationView {
+    Percentage { bps: u16 },
+    FixedAmount { amount: String }, // u128 as string for JSON
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct AutoRenewalView {
+    pub lease_id: u64,
+    pub escalation: EscalationView,
+    pub notice_period_ns: u64,
+    pub term_price: String, // u128 as string for JSON
+    pub next_term_price: String,
+    /// Opting out has to happen before this.
+    pub notice_deadline: u64,
+    pub status: AutoRenewalStatus,
+    pub opted_out_by: Option<String>,
+    pub renewals: u32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct BookingTermsView {
+    pub nightly_rate: String, // u128 as string for JSON
+    pub weekly_rate: Option<String>,
+    pub min_nights: u32,
+    pub max_nights: u32,
+    pub cancellation_policy: CancellationPolicy,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct BookingView {
+    pub booking_id: u64,
+    pub property_id: u64,
+    pub guest_id: String,
+    pub check_in: u64,
+    pub check_out: u64,
+    pub nights: u32,
+    pub amount: String, // u128 as string for JSON
+    pub stablecoin_token: String,
+    pub cancellation_policy: CancellationPolicy,
+    pub status: BookingStatus,
+    pub refunded: String,
+    pub created_at: u64,
+}
+
+/// A stretch of the calendar already taken.
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct BookedRangeView {
+    pub booking_id: u64,
+    pub check_in: u64,
+    pub check_out: u64,
+}
+
+/// How one unit of a building stands.
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct UnitOccupancyView {
+    pub unit_id: u64,
+    pub owner_id: String,
+    pub is_for_sale: bool,
+    pub is_for_lease: bool,
+    pub occupied: bool,
+    pub tenant_id: Option<String>,
+    pub income: String, // u128 as string for JSON
+}
+
+/// Occupancy and income across a building's units.
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct BuildingSummaryView {
+    pub building_id: u64,
+    pub owner_id: String,
+    pub unit_count: u32,
+    pub occupied_units: u32,
+    pub vacant_units: u32,
+    pub occupancy_bps: u16,
+    pub lease_income: String, // u128 as string for JSON
+    pub rent_income: String,
+    pub booking_income: String,
+    pub total_income: String,
+    pub units: Vec<UnitOccupancyView>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct FractionalView {
+    pub property_id: u64,
+    pub manager_id: String,
+    pub total_shares: String, // u128 as string for JSON
+    pub reserve_price: String,
+    pub income_token: String,
+    pub total_income: String,
+    pub status: FractionalStatus,
+    pub recombined_by: Option<String>,
+    pub created_at: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct ShareHoldingView {
+    pub property_id: u64,
+    pub account_id: String,
+    pub shares: String, // u128 as string for JSON
+    /// Income and buyout proceeds that can be claimed now.
+    pub claimable: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct PayoutShareView {
+    pub account_id: String,
+    pub bps: u16,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct PlatformFeeView {
+    pub stablecoin_token: String,
+    pub action: Action,
+    pub bps: u16,
+    pub flat: String, // u128 as string for JSON
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct BrokerCommissionView {
+    pub broker_id: String,
+    pub bps: u16,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct FeeQuoteView {
+    pub platform_fee: String, // u128 as string for JSON
+    pub broker_id: Option<String>,
+    pub commission: String,
+    pub net: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct AccruedFeeView {
+    pub stablecoin_token: String,
+    pub amount: String, // u128 as string for JSON
+}
+
+/// NEP-199 payout: what each account gets of a sale of the token.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Payout {
+    pub payout: HashMap<AccountId, U128>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct ClaimableBalanceView {
+    pub token: String,
+    pub amount: String, // u128 as string for JSON
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct DisputeBondView {
+    pub posted_by: String,
+    pub stablecoin_token: String,
+    pub amount: String, // u128 as string for JSON
+    pub forfeit_to_treasury: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct JurorView {
+    pub account_id: String,
+    pub stake: String, // u128 as string for JSON
+    pub panels: u32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct JuryBallotView {
+    pub juror_id: String,
+    pub committed: bool,
+    /// Shown once revealed.
+    pub for_raiser: Option<bool>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct ArbitrationView {
+    pub requested_by: String,
+    pub stablecoin_token: String,
+    pub fee: String, // u128 as string for JSON
+    pub panel: Vec<JuryBallotView>,
+    pub commit_deadline: u64,
+    pub reveal_deadline: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct DisputeVoteView {
+    pub admin_id: String,
+    pub for_tenant: bool,
+    pub payout: String, // u128 as string for JSON
+    pub voted_at: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct DisputeVotingView {
+    pub opened_at: u64,
+    pub closes_at: u64,
+    pub votes: Vec<DisputeVoteView>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct EvidenceEntryView {
+    pub submitted_by: String,
+    pub uri: String,
+    pub content_hash: String,
+    pub description: String,
+    pub submitted_at: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct CaseFileView {
+    pub raised_by: String,
+    pub respondent: String,
+    pub opened_at: u64,
+    pub response_deadline: u64,
+    pub entry_count: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct DepositDeductionView {
+    pub description: String,
+    pub amount: String, // u128 as string for JSON
+}
+
+#[derive(serde::Serialize, serde::Deserialize, JsonSchema)]
+pub struct DamageDepositView {
+    pub lease_id: u64,
+    pub property_id: u64,
+    pub tenant_id: String,
+    pub stablecoin_token: String,
+    pub amount: String, // u128 as string for JSON
+    pub deductions: Vec<DepositDeductionView>,
+    pub total_deductions: String,
+    pub deadline: Option<u64>,
+    pub status: DamageDepositStatus,
+    pub refunded: String,
+}
+
 /// Conversion functions from internal models to view structs
 
 impl From<&DisputeStatus> for DisputeStatusView {
@@ -170,10 +560,393 @@ impl From<&Bid> for BidView {
         }
     }
 }
+
+impl From<&CounterOffer> for CounterOfferView {
+    fn from(offer: &CounterOffer) -> Self {
+        CounterOfferView {
+            round: offer.round,
+            proposer_id: offer.proposer.to_string(),
+            amount: offer.amount.to_string(),
+            terms: offer.terms.clone(),
+            created_at: offer.created_at,
+            status: offer.status.clone(),
+        }
+    }
+}
+
+impl From<&Auction> for AuctionView {
+    fn from(auction: &Auction) -> Self {
+        let mut view = AuctionView {
+            property_id: auction.property_id,
+            seller_id: auction.seller_id.to_string(),
+            mode: String::new(),
+            start_time: auction.start_time,
+            end_time: auction.end_time,
+            reserve_price: String::new(),
+            min_increment: None,
+            highest_bid_id: None,
+            reveal_end: None,
+            start_price: None,
+            current_price: None,
+            use_escrow: None,
+            settled: auction.settled,
+        };
+        match &auction.mode {
+            AuctionMode::English {
+                reserve_price,
+                min_increment,
+                highest_bid_id,
+            } => {
+                view.mode = "English".to_string();
+                view.reserve_price = reserve_price.to_string();
+                view.min_increment = Some(min_increment.to_string());
+                view.highest_bid_id = *highest_bid_id;
+            }
+            AuctionMode::Sealed {
+                reserve_price,
+                reveal_end,
+                leading_commit_id,
+            } => {
+                view.mode = "Sealed".to_string();
+                view.reserve_price = reserve_price.to_string();
+                view.highest_bid_id = *leading_commit_id;
+                view.reveal_end = Some(*reveal_end);
+            }
+            AuctionMode::Dutch {
+                start_price,
+                floor_price,
+                use_escrow,
+            } => {
+                view.mode = "Dutch".to_string();
+                view.reserve_price = floor_price.to_string();
+                view.start_price = Some(start_price.to_string());
+                view.current_price = crate::auction::dutch_price(auction, env::block_timestamp())
+                    .map(|price| price.to_string());
+                view.use_escrow = Some(*use_escrow);
+            }
+        }
+        view
+    }
+}
+
+impl From<&EarnestMoney> for EarnestMoneyView {
+    fn from(terms: &EarnestMoney) -> Self {
+        let balance_due = if terms.balance_paid {
+            0
+        } else {
+            terms.offer_price - terms.deposit
+        };
+        EarnestMoneyView {
+            offer_price: terms.offer_price.to_string(),
+            deposit: terms.deposit.to_string(),
+            balance_due: balance_due.to_string(),
+            balance_due_by: terms.balance_due_by,
+            balance_paid: terms.balance_paid,
+        }
+    }
+}
+
+impl From<&InstalmentPlan> for InstalmentPlanView {
+    fn from(plan: &InstalmentPlan) -> Self {
+        let amount_paid = plan.amount_paid();
+        InstalmentPlanView {
+            price: plan.price.to_string(),
+            down_payment: plan.down_payment.to_string(),
+            instalment_count: plan.instalment_count,
+            interval_ns: plan.interval_ns,
+            paid_instalments: plan.paid_instalments,
+            next_instalment: (plan.status == InstalmentStatus::Active)
+                .then(|| plan.instalment_amount(plan.paid_instalments).to_string()),
+            next_due_at: plan.next_due_at,
+            amount_paid: amount_paid.to_string(),
+            remaining: (plan.price - amount_paid).to_string(),
+            status: plan.status.clone(),
+        }
+    }
+}
+
+impl From<&RentTerms> for RentTermsView {
+    fn from(terms: &RentTerms) -> Self {
+        RentTermsView {
+            amount: terms.amount.to_string(),
+            period_ns: terms.period_ns,
+            late_fee_bps: terms.late_fee_bps,
+            grace_period_ns: terms.grace_period_ns,
+        }
+    }
+}
+
+impl From<&RentPayment> for RentPaymentView {
+    fn from(payment: &RentPayment) -> Self {
+        RentPaymentView {
+            period: payment.period,
+            due_at: payment.due_at,
+            paid_at: payment.paid_at,
+            amount: payment.amount.to_string(),
+            late_fee: payment.late_fee.to_string(),
+        }
+    }
+}
+
+impl From<&LeaseTermination> for LeaseTerminationView {
+    fn from(termination: &LeaseTermination) -> Self {
+        LeaseTerminationView {
+            initiated_by: termination.initiated_by.to_string(),
+            noticed_at: termination.noticed_at,
+            effective_at: termination.effective_at,
+            unused: termination.unused.to_string(),
+            penalty: termination.penalty.to_string(),
+            refund_due: termination.refund_due.to_string(),
+            refund_paid: termination.refund_paid,
+            status: termination.status.clone(),
+        }
+    }
+}
+
+impl From<&LeaseAssignment> for LeaseAssignmentView {
+    fn from(assignment: &LeaseAssignment) -> Self {
+        LeaseAssignmentView {
+            assignee: assignment.assignee.to_string(),
+            proposed_at: assignment.proposed_at,
+            owner_consented: assignment.owner_consented,
+        }
+    }
+}
+
+impl From<&SubleaseOffer> for SubleaseOfferView {
+    fn from(offer: &SubleaseOffer) -> Self {
+        SubleaseOfferView {
+            subtenant_id: offer.subtenant_id.to_string(),
+            end_time: offer.end_time,
+            rent: offer.rent.as_ref().map(|terms| terms.into()),
+            offered_at: offer.offered_at,
+        }
+    }
+}
+
+impl From<&CoParty> for CoPartyView {
+    fn from(party: &CoParty) -> Self {
+        CoPartyView {
+            account_id: party.account_id.to_string(),
+            contributed: party.contributed.to_string(),
+            share_bps: party.share_bps,
+            refunded: party.refunded,
+        }
+    }
+}
+
+impl From<&Escalation> for EscalationView {
+    fn from(escalation: &Escalation) -> Self {
+        match escalation {
+            Escalation::Percentage { bps } => EscalationView::Percentage { bps: *bps },
+            Escalation::FixedAmount { amount } => EscalationView::FixedAmount {
+                amount: amount.0.to_string(),
+            },
+        }
+    }
+}
+
+impl From<&BookingTerms> for BookingTermsView {
+    fn from(terms: &BookingTerms) -> Self {
+        BookingTermsView {
+            nightly_rate: terms.nightly_rate.to_string(),
+            weekly_rate: terms.weekly_rate.map(|rate| rate.to_string()),
+            min_nights: terms.min_nights,
+            max_nights: terms.max_nights,
+            cancellation_policy: terms.cancellation_policy.clone(),
+        }
+    }
+}
+
+impl From<(u64, &Booking)> for BookingView {
+    fn from((booking_id, booking): (u64, &Booking)) -> Self {
+        BookingView {
+            booking_id,
+            property_id: booking.property_id,
+            guest_id: booking.guest_id.to_string(),
+            check_in: booking.check_in,
+            check_out: booking.check_out,
+            nights: booking.nights,
+            amount: booking.amount.to_string(),
+            stablecoin_token: booking.stablecoin_token.to_string(),
+            cancellation_policy: booking.cancellation_policy.clone(),
+            status: booking.status.clone(),
+            refunded: booking.refunded.to_string(),
+            created_at: booking.created_at,
+        }
+    }
+}
+
+impl From<(u64, &Fractional)> for FractionalView {
+    fn from((property_id, fractional): (u64, &Fractional)) -> Self {
+        FractionalView {
+            property_id,
+            manager_id: fractional.manager_id.to_string(),
+            total_shares: fractional.total_shares.to_string(),
+            reserve_price: fractional.reserve_price.to_string(),
+            income_token: fractional.income_token.to_string(),
+            total_income: fractional.total_income.to_string(),
+            status: fractional.status.clone(),
+            recombined_by: fractional.recombined_by.as_ref().map(|id| id.to_string()),
+            created_at: fractional.created_at,
+        }
+    }
+}
+
+impl From<&PayoutShare> for PayoutShareView {
+    fn from(share: &PayoutShare) -> Self {
+        PayoutShareView {
+            account_id: share.account_id.to_string(),
+            bps: share.bps,
+        }
+    }
+}
+
+impl From<&DisputeBond> for DisputeBondView {
+    fn from(bond: &DisputeBond) -> Self {
+        DisputeBondView {
+            posted_by: bond.posted_by.to_string(),
+            stablecoin_token: bond.stablecoin_token.to_string(),
+            amount: bond.amount.to_string(),
+            forfeit_to_treasury: bond.forfeit_to_treasury,
+        }
+    }
+}
+
+impl From<&Arbitration> for ArbitrationView {
+    fn from(arbitration: &Arbitration) -> Self {
+        ArbitrationView {
+            requested_by: arbitration.requested_by.to_string(),
+            stablecoin_token: arbitration.stablecoin_token.to_string(),
+            fee: arbitration.fee.to_string(),
+            panel: arbitration
+                .panel
+                .iter()
+                .map(|ballot| JuryBallotView {
+                    juror_id: ballot.juror_id.to_string(),
+                    committed: ballot.commitment.is_some(),
+                    for_raiser: ballot.for_raiser,
+                })
+                .collect(),
+            commit_deadline: arbitration.commit_deadline,
+            reveal_deadline: arbitration.reveal_deadline,
+        }
+    }
+}
+
+impl From<&DisputeVoting> for DisputeVotingView {
+    fn from(voting: &DisputeVoting) -> Self {
+        DisputeVotingView {
+            opened_at: voting.opened_at,
+            closes_at: voting.closes_at,
+            votes: voting
+                .votes
+                .iter()
+                .map(|vote| DisputeVoteView {
+                    admin_id: vote.admin_id.to_string(),
+                    for_tenant: vote.for_tenant,
+                    payout: vote.payout.to_string(),
+                    voted_at: vote.voted_at,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&EvidenceEntry> for EvidenceEntryView {
+    fn from(entry: &EvidenceEntry) -> Self {
+        EvidenceEntryView {
+            submitted_by: entry.submitted_by.to_string(),
+            uri: entry.uri.clone(),
+            content_hash: entry.content_hash.clone(),
+            description: entry.description.clone(),
+            submitted_at: entry.submitted_at,
+        }
+    }
+}
+
+impl From<&CaseFile> for CaseFileView {
+    fn from(file: &CaseFile) -> Self {
+        CaseFileView {
+            raised_by: file.raised_by.to_string(),
+            respondent: file.respondent.to_string(),
+            opened_at: file.opened_at,
+            response_deadline: file.response_deadline,
+            entry_count: file.entries.len() as u64,
+        }
+    }
+}
+
+impl From<&BrokerCommission> for BrokerCommissionView {
+    fn from(commission: &BrokerCommission) -> Self {
+        BrokerCommissionView {
+            broker_id: commission.broker_id.to_string(),
+            bps: commission.bps,
+        }
+    }
+}
+
+impl From<&ClaimableBalance> for ClaimableBalanceView {
+    fn from(balance: &ClaimableBalance) -> Self {
+        ClaimableBalanceView {
+            token: balance.token.to_string(),
+            amount: balance.amount.to_string(),
+        }
+    }
+}
+
+impl From<&DepositDeduction> for DepositDeductionView {
+    fn from(deduction: &DepositDeduction) -> Self {
+        DepositDeductionView {
+            description: deduction.description.clone(),
+            amount: deduction.amount.0.to_string(),
+        }
+    }
+}
+
+impl From<(u64, &DamageDeposit)> for DamageDepositView {
+    fn from((lease_id, deposit): (u64, &DamageDeposit)) -> Self {
+        DamageDepositView {
+            lease_id,
+            property_id: deposit.property_id,
+            tenant_id: deposit.tenant_id.to_string(),
+            stablecoin_token: deposit.stablecoin_token.to_string(),
+            amount: deposit.amount.to_string(),
+            deductions: deposit.deductions.iter().map(|d| d.into()).collect(),
+            total_deductions: deposit.total_deductions().to_string(),
+            deadline: deposit.deadline,
+            status: deposit.status.clone(),
+            refunded: deposit.refunded.to_string(),
+        }
+    }
+}
+
+impl From<&SealedCommit> for SealedCommitView {
+    fn from(commit: &SealedCommit) -> Self {
+        SealedCommitView {
+            id: commit.id,
+            property_id: commit.property_id,
+            bidder_id: commit.bidder.to_string(),
+            commitment: commit.commitment.clone(),
+            deposit: commit.deposit.to_string(),
+            stablecoin_token: commit.stablecoin_token.to_string(),
+            created_at: commit.created_at,
+            revealed_amount: commit.revealed_amount.map(|amount| amount.to_string()),
+            refunded: commit.refunded,
+        }
+    }
+}
 #[near_bindgen]
 impl ShedaContract {
-    fn property_to_view(&self, property: &Property) -> PropertyView {
+    pub(crate) fn property_to_view(&self, property: &Property) -> PropertyView {
         let mut view: PropertyView = property.into();
+        // Under a running Dutch auction the listing's price is whatever the
+        // auction is asking right now.
+        if let Some(price) = crate::auction::open_auction(self, property.id)
+            .and_then(|auction| crate::auction::dutch_price(&auction, env::block_timestamp()))
+        {
+            view.price = price.to_string();
+        }
         view.property_instance = self
             .property_instances
             .get(&property.id)
@@ -181,6 +954,17 @@ impl ShedaContract {
         view
     }
 
+    pub(crate) fn lease_to_view(&self, lease: &Lease) -> LeaseView {
+        let mut view: LeaseView = lease.into();
+        if let Some(dispute) = view.dispute.as_mut() {
+            dispute.bond = self
+                .dispute_bonds
+                .get(&DisputeCase::Lease { lease_id: lease.id })
+                .map(|bond| bond.amount.to_string());
+        }
+        view
+    }
+
     pub fn get_all_admins(&self) -> Vec<AccountId> {
         self.admins.iter().cloned().collect()
     }
@@ -212,7 +996,7 @@ impl ShedaContract {
     }
 
     pub fn get_lease_by_id(&self, lease_id: u64) -> Option<LeaseView> {
-        self.leases.get(&lease_id).map(|l| l.into())
+        self.leases.get(&lease_id).map(|l| self.lease_to_view(l))
     }
 
     pub fn get_bids_for_property(&self, property_id: u64) -> Vec<BidView> {
@@ -460,7 +1244,7 @@ impl ShedaContract {
         if let Some(ids) = lease_ids {
             for id in ids {
                 if let Some(lease) = self.leases.get(&id) {
-                    leases.push(lease.into());
+                    leases.push(self.lease_to_view(lease));
                 }
             }
         }
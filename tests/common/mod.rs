@@ -168,6 +168,120 @@ impl Fixture {
         Ok(before)
     }
 
+    /// `ft_transfer_call` into the marketplace with an arbitrary message, for
+    /// the flows that fund something other than a plain `place_bid`. Returns
+    /// the raw outcome so callers can assert on refusals too.
+    pub async fn transfer_call(
+        &self,
+        from: &Account,
+        amount: u128,
+        msg: serde_json::Value,
+    ) -> TestResult<near_workspaces::result::ExecutionFinalResult> {
+        Ok(from
+            .call(self.ft.id(), "ft_transfer_call")
+            .args_json(json!({
+                "receiver_id": self.contract.id(),
+                "amount": amount.to_string(),
+                "msg": msg.to_string(),
+            }))
+            .deposit(one_yocto())
+            .max_gas()
+            .transact()
+            .await?)
+    }
+
+    /// The seller accepts a lease bid; returns the id of the lease it starts.
+    pub async fn accept_lease_bid(&self, property_id: u64, bid_id: u64) -> TestResult<u64> {
+        // accept_bid allocates lease ids from lease_counter, so the lease it
+        // starts is the one the counter points at beforehand.
+        let lease_id: u64 = self.contract.view("get_lease_counter").await?.json()?;
+        self.seller
+            .call(self.contract.id(), "accept_bid")
+            .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+            .deposit(one_yocto())
+            .max_gas()
+            .transact()
+            .await?
+            .into_result()?;
+        Ok(lease_id)
+    }
+
+    /// Lets an already-minted property to the buyer on a plain lease bid and
+    /// returns the lease id.
+    pub async fn let_to_buyer(&self, property_id: u64) -> TestResult<u64> {
+        let bid_id = self.place_bid(property_id, false).await?;
+        self.accept_lease_bid(property_id, bid_id).await
+    }
+
+    /// Mints a property and lets it to the buyer. Returns
+    /// `(property_id, lease_id)`.
+    pub async fn let_property(&self) -> TestResult<(u64, u64)> {
+        let property_id = self.mint_property(false).await?;
+        let lease_id = self.let_to_buyer(property_id).await?;
+        Ok((property_id, lease_id))
+    }
+
+    /// As `let_property`, but the property asks for a damage deposit of
+    /// `deposit` and the buyer lodges it with a `LeaseWithDeposit` bid.
+    pub async fn let_property_with_deposit(&self, deposit: u128) -> TestResult<(u64, u64)> {
+        let property_id = self.mint_property(false).await?;
+        self.seller
+            .call(self.contract.id(), "set_damage_deposit")
+            .args_json(json!({ "property_id": property_id, "amount": deposit.to_string() }))
+            .deposit(one_yocto())
+            .transact()
+            .await?
+            .into_result()?;
+        let bid_id = self.bid_counter().await?;
+        self.transfer_call(
+            &self.buyer,
+            BID_AMOUNT,
+            json!({
+                "property_id": property_id,
+                "action": "Lease",
+                "stablecoin_token": self.ft.id(),
+                "kind": { "LeaseWithDeposit": { "damage_deposit": deposit.to_string() } },
+            }),
+        )
+        .await?
+        .into_result()?;
+        let lease_id = self.accept_lease_bid(property_id, bid_id).await?;
+        Ok((property_id, lease_id))
+    }
+
+    /// Drives a purchase bid on `property_id` to `Disputed` and returns its
+    /// id.
+    pub async fn disputed_bid(&self, property_id: u64) -> TestResult<u64> {
+        let bid_id = self.place_bid(property_id, true).await?;
+
+        self.seller
+            .call(self.contract.id(), "accept_bid_with_escrow")
+            .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+            .deposit(one_yocto())
+            .max_gas()
+            .transact()
+            .await?
+            .into_result()?;
+
+        self.buyer
+            .call(self.contract.id(), "raise_dispute")
+            .args_json(json!({
+                "bid_id": bid_id,
+                "property_id": property_id,
+                "reason": "Seller stopped responding after the viewing",
+            }))
+            .max_gas()
+            .transact()
+            .await?
+            .into_result()?;
+
+        assert_eq!(
+            self.bid_status(property_id, bid_id).await?.as_deref(),
+            Some("Disputed"),
+        );
+        Ok(bid_id)
+    }
+
     pub async fn bid_counter(&self) -> TestResult<u64> {
         Ok(self.contract.view("get_bid_counter").await?.json::<u64>()?)
     }
@@ -0,0 +1,398 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use serde_json::json;
+
+// English auctions
+// ---------------------------------------------------------------------------
+//
+// The seller opens a window; bids arrive through ft_transfer_call as usual but
+// each must beat the current highest by `min_increment`, and the bid it beats
+// is refunded immediately. When the window closes anyone may settle: the
+// highest bid at or above the reserve buys the property through the same path
+// as accept_bid, otherwise it is refunded and nothing changes hands.
+
+const INCREMENT: u128 = 10_000_000;
+
+/// Roughly one sandbox block per second; the auction gets a minute.
+const AUCTION_LENGTH_NS: u64 = 60 * 1_000_000_000;
+
+async fn start_auction(fx: &Fixture, property_id: u64, reserve: u128) -> TestResult<()> {
+    let property: serde_json::Value = fx
+        .contract
+        .view("get_property_by_id")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    let minted_at = property["timestamp"].as_u64().unwrap_or_default();
+
+    fx.seller
+        .call(fx.contract.id(), "start_auction")
+        .args_json(json!({
+            "property_id": property_id,
+            "start_time": null,
+            "end_time": minted_at + AUCTION_LENGTH_NS,
+            "reserve_price": reserve.to_string(),
+            "min_increment": INCREMENT.to_string(),
+        }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+async fn bid(fx: &Fixture, property_id: u64, amount: u128) -> TestResult<()> {
+    fx.transfer_call(
+        &fx.buyer,
+        amount,
+        json!({
+            "property_id": property_id,
+            "action": "Purchase",
+            "stablecoin_token": fx.ft.id(),
+        }),
+    )
+    .await?
+    .into_result()?;
+    Ok(())
+}
+
+async fn settle(fx: &Fixture, property_id: u64) -> TestResult<bool> {
+    let outcome = fx
+        .buyer
+        .call(fx.contract.id(), "settle_auction")
+        .args_json(json!({ "property_id": property_id }))
+        .max_gas()
+        .transact()
+        .await?;
+    Ok(outcome.is_success())
+}
+
+/// Outbidding refunds the previous leader, under-increment bids bounce, the
+/// seller can't short-circuit the auction, and settlement sells to the winner.
+#[tokio::test]
+async fn test_english_auction_sells_to_highest_bidder() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(true).await?;
+
+    start_auction(&fx, property_id, BID_AMOUNT).await?;
+
+    let starting_balance = fx.ft_balance(fx.buyer.id()).await?;
+
+    bid(&fx, property_id, BID_AMOUNT).await?;
+    let first = fx.bid_counter().await? - 1;
+
+    // Not enough to beat the leader: ft_on_transfer panics, the FT contract
+    // hands the tokens back and no bid is recorded.
+    bid(&fx, property_id, BID_AMOUNT + INCREMENT - 1).await?;
+    assert_eq!(fx.bid_counter().await?, first + 1);
+
+    bid(&fx, property_id, BID_AMOUNT + INCREMENT).await?;
+    let second = first + 1;
+
+    assert_eq!(
+        fx.bid_status(property_id, first).await?.as_deref(),
+        Some("Rejected")
+    );
+    assert_eq!(
+        fx.ft_balance(fx.buyer.id()).await?,
+        starting_balance - BID_AMOUNT - INCREMENT,
+        "only the leading bid should still be held"
+    );
+
+    // The leader can't walk away and the seller can't pick by hand.
+    let cancel = fx
+        .buyer
+        .call(fx.contract.id(), "cancel_bid")
+        .args_json(json!({ "bid_id": second, "property_id": property_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(cancel.is_failure(), "the leading bid must stay committed");
+
+    let accept = fx
+        .seller
+        .call(fx.contract.id(), "accept_bid")
+        .args_json(json!({ "bid_id": second, "property_id": property_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(
+        accept.is_failure(),
+        "accept_bid must refuse an auctioned property"
+    );
+
+    assert!(
+        !settle(&fx, property_id).await?,
+        "settled before the end time"
+    );
+
+    worker.fast_forward(120).await?;
+    assert!(settle(&fx, property_id).await?);
+
+    assert_eq!(
+        fx.property_owner(property_id).await?.as_deref(),
+        Some(fx.buyer.id().as_str())
+    );
+    assert_eq!(fx.ft_balance(fx.seller.id()).await?, BID_AMOUNT + INCREMENT);
+
+    let auction: serde_json::Value = fx
+        .contract
+        .view("get_auction")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    assert_eq!(auction["settled"], true);
+    assert_eq!(auction["highest_bid_id"], second);
+
+    Ok(())
+}
+
+/// Below the reserve nothing is sold and the highest bid comes back in full.
+#[tokio::test]
+async fn test_english_auction_refunds_below_reserve() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(true).await?;
+
+    start_auction(&fx, property_id, BID_AMOUNT * 2).await?;
+
+    let starting_balance = fx.ft_balance(fx.buyer.id()).await?;
+    bid(&fx, property_id, BID_AMOUNT).await?;
+    let bid_id = fx.bid_counter().await? - 1;
+
+    worker.fast_forward(120).await?;
+    assert!(settle(&fx, property_id).await?);
+
+    assert_eq!(
+        fx.property_owner(property_id).await?.as_deref(),
+        Some(fx.seller.id().as_str())
+    );
+    assert_eq!(fx.ft_balance(fx.buyer.id()).await?, starting_balance);
+    assert_eq!(
+        fx.bid_status(property_id, bid_id).await?.as_deref(),
+        Some("Rejected")
+    );
+
+    Ok(())
+}
+
+// Sealed-bid auctions
+// ---------------------------------------------------------------------------
+//
+// Commit a hash plus a deposit, reveal amount and salt once commits close,
+// settle after the reveal window. The winner's bid lands in the escrow flow
+// as Accepted; every other deposit (and the winner's excess) comes back via
+// claim_sealed_refund.
+
+const REVEAL_LENGTH_NS: u64 = 600 * 1_000_000_000;
+
+async fn commit(fx: &Fixture, property_id: u64, deposit: u128, hash: &str) -> TestResult<u64> {
+    let before = fx.bid_counter().await?;
+    fx.transfer_call(
+        &fx.buyer,
+        deposit,
+        json!({
+            "property_id": property_id,
+            "action": "Purchase",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "SealedCommit": { "commitment": hash } },
+        }),
+    )
+    .await?
+    .into_result()?;
+    assert_eq!(fx.bid_counter().await?, before + 1, "commit was refused");
+    Ok(before)
+}
+
+async fn sealed_hash(fx: &Fixture, amount: u128, salt: &str) -> TestResult<String> {
+    Ok(fx
+        .contract
+        .view("hash_sealed_bid")
+        .args_json(json!({
+            "bidder": fx.buyer.id(),
+            "amount": amount.to_string(),
+            "salt": salt,
+        }))
+        .await?
+        .json()?)
+}
+
+async fn reveal(
+    fx: &Fixture,
+    property_id: u64,
+    commit_id: u64,
+    amount: u128,
+    salt: &str,
+) -> TestResult<bool> {
+    let outcome = fx
+        .buyer
+        .call(fx.contract.id(), "reveal_sealed_bid")
+        .args_json(json!({
+            "property_id": property_id,
+            "commit_id": commit_id,
+            "amount": amount.to_string(),
+            "salt": salt,
+        }))
+        .transact()
+        .await?;
+    Ok(outcome.is_success())
+}
+
+/// The higher reveal wins and enters escrow; the lower deposit and the
+/// winner's over-funding are refunded; a bad salt is refused.
+#[tokio::test]
+async fn test_sealed_auction_winner_enters_escrow() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(true).await?;
+
+    let property: serde_json::Value = fx
+        .contract
+        .view("get_property_by_id")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    let minted_at = property["timestamp"].as_u64().unwrap_or_default();
+
+    fx.seller
+        .call(fx.contract.id(), "start_sealed_auction")
+        .args_json(json!({
+            "property_id": property_id,
+            "start_time": null,
+            "commit_end": minted_at + AUCTION_LENGTH_NS,
+            "reveal_end": minted_at + REVEAL_LENGTH_NS,
+            "reserve_price": BID_AMOUNT.to_string(),
+        }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+
+    let starting_balance = fx.ft_balance(fx.buyer.id()).await?;
+
+    let low = commit(
+        &fx,
+        property_id,
+        BID_AMOUNT,
+        &sealed_hash(&fx, BID_AMOUNT, "low").await?,
+    )
+    .await?;
+    // Over-funded on purpose, so the deposit doesn't give the bid away.
+    let high = commit(
+        &fx,
+        property_id,
+        BID_AMOUNT * 2,
+        &sealed_hash(&fx, BID_AMOUNT + INCREMENT, "high").await?,
+    )
+    .await?;
+
+    // Commitments are all anyone can see: no bids exist yet.
+    assert_eq!(fx.bid_status(property_id, high).await?, None);
+    assert!(
+        !reveal(&fx, property_id, high, BID_AMOUNT + INCREMENT, "high").await?,
+        "revealed during the commit window"
+    );
+
+    worker.fast_forward(120).await?;
+
+    assert!(!reveal(&fx, property_id, high, BID_AMOUNT + INCREMENT, "wrong").await?);
+    assert!(reveal(&fx, property_id, low, BID_AMOUNT, "low").await?);
+    assert!(reveal(&fx, property_id, high, BID_AMOUNT + INCREMENT, "high").await?);
+
+    worker.fast_forward(600).await?;
+    assert!(settle(&fx, property_id).await?);
+
+    assert_eq!(
+        fx.bid_status(property_id, high).await?.as_deref(),
+        Some("Accepted")
+    );
+
+    for commit_id in [low, high] {
+        fx.seller
+            .call(fx.contract.id(), "claim_sealed_refund")
+            .args_json(json!({ "property_id": property_id, "commit_id": commit_id }))
+            .max_gas()
+            .transact()
+            .await?
+            .into_result()?;
+    }
+
+    assert_eq!(
+        fx.ft_balance(fx.buyer.id()).await?,
+        starting_balance - BID_AMOUNT - INCREMENT,
+        "only the winning amount should remain in escrow"
+    );
+
+    Ok(())
+}
+
+// Dutch auctions
+// ---------------------------------------------------------------------------
+//
+// No settlement step: the first bid covering the falling price buys the
+// property there and then, and anything above the price rides back on the
+// ft_on_transfer refund.
+
+/// A bid below the asking price bounces; one above it buys at the price and
+/// gets the difference back.
+#[tokio::test]
+async fn test_dutch_auction_sells_to_first_qualifying_bid() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(true).await?;
+
+    let property: serde_json::Value = fx
+        .contract
+        .view("get_property_by_id")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    let minted_at = property["timestamp"].as_u64().unwrap_or_default();
+
+    fx.seller
+        .call(fx.contract.id(), "start_dutch_auction")
+        .args_json(json!({
+            "property_id": property_id,
+            "start_time": null,
+            // Long enough that the price barely moves during the test.
+            "end_time": minted_at + REVEAL_LENGTH_NS * 100,
+            "start_price": (BID_AMOUNT * 2).to_string(),
+            "floor_price": BID_AMOUNT.to_string(),
+            "use_escrow": false,
+        }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+
+    let starting_balance = fx.ft_balance(fx.buyer.id()).await?;
+    let before = fx.bid_counter().await?;
+
+    bid(&fx, property_id, BID_AMOUNT).await?;
+    assert_eq!(
+        fx.bid_counter().await?,
+        before,
+        "a bid under the current price must be refused"
+    );
+
+    bid(&fx, property_id, BID_AMOUNT * 3).await?;
+
+    assert_eq!(
+        fx.property_owner(property_id).await?.as_deref(),
+        Some(fx.buyer.id().as_str())
+    );
+
+    let paid = starting_balance - fx.ft_balance(fx.buyer.id()).await?;
+    assert!(
+        paid > BID_AMOUNT && paid <= BID_AMOUNT * 2,
+        "paid {} — should be the decayed price, with the overpayment returned",
+        paid
+    );
+    assert_eq!(fx.ft_balance(fx.seller.id()).await?, paid);
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use serde_json::json;
+
+// Automatic lease renewal
+// ---------------------------------------------------------------------------
+//
+// The owner offers auto-renewal with an escalation rule and the tenant agrees.
+// Up to the notice deadline either side can opt out; after it the tenant
+// renews simply by paying the escalated price, and the lease is extended just
+// as an accepted renewal bid would extend it.
+
+async fn lease(fx: &Fixture, lease_id: u64) -> TestResult<serde_json::Value> {
+    Ok(fx
+        .contract
+        .view("get_lease_by_id")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?)
+}
+
+/// Owner offers 5% a term and the tenant agrees.
+async fn agree_auto_renewal(fx: &Fixture, lease_id: u64, notice_period_ns: u64) -> TestResult {
+    fx.seller
+        .call(fx.contract.id(), "offer_auto_renewal")
+        .args_json(json!({
+            "lease_id": lease_id,
+            "escalation": { "Percentage": { "bps": 500 } },
+            "notice_period_ns": notice_period_ns,
+        }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+    fx.buyer
+        .call(fx.contract.id(), "agree_to_auto_renewal")
+        .args_json(json!({ "lease_id": lease_id }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+async fn pay_renewal(
+    fx: &Fixture,
+    property_id: u64,
+    lease_id: u64,
+    amount: u128,
+) -> TestResult<near_workspaces::result::ExecutionFinalResult> {
+    fx.transfer_call(
+        &fx.buyer,
+        amount,
+        json!({
+            "property_id": property_id,
+            "action": "Lease",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "PayAutoRenewal": { "lease_id": lease_id } },
+        }),
+    )
+    .await
+}
+
+/// Past the deadline, paying the escalated price renews the lease.
+#[tokio::test]
+async fn test_payment_renews_lease() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let (property_id, lease_id) = fx.let_property().await?;
+
+    // A notice period all but the whole term puts the deadline a second in.
+    let before = lease(&fx, lease_id).await?;
+    let start = before["start_time"].as_u64().unwrap_or_default();
+    let end = before["end_time"].as_u64().unwrap_or_default();
+    agree_auto_renewal(&fx, lease_id, end - start - 1_000_000_000).await?;
+
+    let renewal: serde_json::Value = fx
+        .contract
+        .view("get_auto_renewal")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    assert_eq!(renewal["status"], "Active");
+    let price = BID_AMOUNT * 105 / 100;
+    assert_eq!(renewal["next_term_price"], price.to_string());
+
+    worker.fast_forward(5).await?;
+    let owner_before = fx.ft_balance(fx.seller.id()).await?;
+    pay_renewal(&fx, property_id, lease_id, price)
+        .await?
+        .into_result()?;
+
+    assert_eq!(fx.ft_balance(fx.seller.id()).await?, owner_before + price);
+    let after = lease(&fx, lease_id).await?;
+    assert_eq!(
+        after["end_time"].as_u64().unwrap_or_default(),
+        end + (end - start),
+        "renewed for another term"
+    );
+    let renewal: serde_json::Value = fx
+        .contract
+        .view("get_auto_renewal")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    assert_eq!(renewal["renewals"], 1);
+    assert_eq!(renewal["term_price"], price.to_string());
+
+    Ok(())
+}
+
+/// Once either side opts out, paying doesn't renew.
+#[tokio::test]
+async fn test_opt_out_stops_renewal() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let (property_id, lease_id) = fx.let_property().await?;
+    agree_auto_renewal(&fx, lease_id, 1_000_000_000).await?;
+
+    fx.seller
+        .call(fx.contract.id(), "opt_out_of_auto_renewal")
+        .args_json(json!({ "lease_id": lease_id }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+
+    // The token contract hands a refused payment back.
+    let end = lease(&fx, lease_id).await?["end_time"].clone();
+    let tenant_before = fx.ft_balance(fx.buyer.id()).await?;
+    pay_renewal(&fx, property_id, lease_id, BID_AMOUNT * 2)
+        .await?
+        .into_result()?;
+    assert_eq!(fx.ft_balance(fx.buyer.id()).await?, tenant_before);
+    assert_eq!(lease(&fx, lease_id).await?["end_time"], end);
+    let renewal: serde_json::Value = fx
+        .contract
+        .view("get_auto_renewal")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    assert_eq!(renewal["status"], "OptedOut");
+    assert_eq!(renewal["opted_out_by"], fx.seller.id().as_str());
+
+    Ok(())
+}
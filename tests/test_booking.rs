@@ -0,0 +1,154 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult};
+use near_workspaces::{network::Sandbox, Worker};
+use serde_json::json;
+
+// Short-stay bookings
+// ---------------------------------------------------------------------------
+//
+// The owner opens a property for short stays with a nightly rate and a
+// cancellation policy. Guests book nights on its calendar by paying up front;
+// the payment is held until check-in and nights can't be booked twice.
+
+const NIGHT_NS: u64 = 24 * 60 * 60 * 1_000_000_000;
+const NIGHTLY_RATE: u128 = 10_000_000;
+
+/// Opens a fresh property for stays of 1 to 14 nights, moderate policy.
+async fn holiday_let(fx: &Fixture) -> TestResult<u64> {
+    let property_id = fx.mint_property(false).await?;
+    fx.seller
+        .call(fx.contract.id(), "set_booking_terms")
+        .args_json(json!({
+            "property_id": property_id,
+            "nightly_rate": NIGHTLY_RATE.to_string(),
+            "weekly_rate": null,
+            "min_nights": 1,
+            "max_nights": 14,
+            "cancellation_policy": "Moderate",
+        }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(property_id)
+}
+
+/// A check-in `days` days from now.
+async fn days_ahead(worker: &Worker<Sandbox>, days: u64) -> TestResult<u64> {
+    Ok(worker.view_block().await?.timestamp() + days * NIGHT_NS)
+}
+
+async fn book(fx: &Fixture, property_id: u64, check_in: u64, nights: u32) -> TestResult {
+    fx.transfer_call(
+        &fx.buyer,
+        NIGHTLY_RATE * u128::from(nights),
+        json!({
+            "property_id": property_id,
+            "action": "Lease",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "Book": { "check_in": check_in, "nights": nights } },
+        }),
+    )
+    .await?
+    .into_result()?;
+    Ok(())
+}
+
+async fn bookings(fx: &Fixture, property_id: u64) -> TestResult<Vec<serde_json::Value>> {
+    Ok(fx
+        .contract
+        .view("get_bookings_by_property")
+        .args_json(json!({ "property_id": property_id, "from_index": 0, "limit": 10 }))
+        .await?
+        .json()?)
+}
+
+/// Booked nights come off the calendar, and overlapping stays are refused.
+#[tokio::test]
+async fn test_overlapping_stay_is_refused() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = holiday_let(&fx).await?;
+    let check_in = days_ahead(&worker, 10).await?;
+
+    book(&fx, property_id, check_in, 3).await?;
+    let booked = bookings(&fx, property_id).await?;
+    assert_eq!(booked.len(), 1);
+    assert_eq!(booked[0]["status"], "Confirmed");
+    assert_eq!(booked[0]["check_out"], check_in + 3 * NIGHT_NS);
+
+    // Two nights starting on the second night of the first stay.
+    let available: bool = fx
+        .contract
+        .view("is_available")
+        .args_json(json!({
+            "property_id": property_id,
+            "check_in": check_in + NIGHT_NS,
+            "nights": 2,
+        }))
+        .await?
+        .json()?;
+    assert!(!available);
+
+    // The token contract hands a refused payment back.
+    let guest_before = fx.ft_balance(fx.buyer.id()).await?;
+    book(&fx, property_id, check_in + NIGHT_NS, 2).await?;
+    assert_eq!(fx.ft_balance(fx.buyer.id()).await?, guest_before);
+    assert_eq!(bookings(&fx, property_id).await?.len(), 1);
+
+    // Checking in on the day the first stay checks out is fine.
+    book(&fx, property_id, check_in + 3 * NIGHT_NS, 2).await?;
+    assert_eq!(bookings(&fx, property_id).await?.len(), 2);
+
+    Ok(())
+}
+
+/// Cancelling well ahead under a moderate policy refunds the guest in full
+/// and frees the nights.
+#[tokio::test]
+async fn test_cancellation_refunds_per_policy() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = holiday_let(&fx).await?;
+    let check_in = days_ahead(&worker, 10).await?;
+
+    let guest_before = fx.ft_balance(fx.buyer.id()).await?;
+    book(&fx, property_id, check_in, 4).await?;
+    assert_eq!(
+        fx.ft_balance(fx.buyer.id()).await?,
+        guest_before - 4 * NIGHTLY_RATE
+    );
+
+    let booking_id = bookings(&fx, property_id).await?[0]["booking_id"]
+        .as_u64()
+        .unwrap_or_default();
+    fx.buyer
+        .call(fx.contract.id(), "cancel_booking")
+        .args_json(json!({ "booking_id": booking_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert_eq!(fx.ft_balance(fx.buyer.id()).await?, guest_before);
+    let booking: serde_json::Value = fx
+        .contract
+        .view("get_booking")
+        .args_json(json!({ "booking_id": booking_id }))
+        .await?
+        .json()?;
+    assert_eq!(booking["status"], "Cancelled");
+    assert_eq!(booking["refunded"], (4 * NIGHTLY_RATE).to_string());
+
+    let available: bool = fx
+        .contract
+        .view("is_available")
+        .args_json(json!({ "property_id": property_id, "check_in": check_in, "nights": 4 }))
+        .await?
+        .json()?;
+    assert!(available);
+
+    Ok(())
+}
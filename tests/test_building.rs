@@ -0,0 +1,116 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use serde_json::json;
+
+// Multi-unit buildings
+// ---------------------------------------------------------------------------
+//
+// An owner groups the flats of a block under a building property. Each unit
+// stays its own NFT and is let or sold on its own; the building reports
+// occupancy and income across them and can't change hands while any unit is
+// occupied.
+
+/// Mints a building and two leasable units in it. Returns
+/// `(building_id, [unit_ids])`.
+async fn building_with_units(fx: &Fixture) -> TestResult<(u64, [u64; 2])> {
+    let building_id = fx.mint_property(true).await?;
+    let units = [
+        fx.mint_property(false).await?,
+        fx.mint_property(false).await?,
+    ];
+    for unit_id in units {
+        fx.seller
+            .call(fx.contract.id(), "add_building_unit")
+            .args_json(json!({ "building_id": building_id, "unit_id": unit_id }))
+            .deposit(one_yocto())
+            .transact()
+            .await?
+            .into_result()?;
+    }
+    Ok((building_id, units))
+}
+
+/// Letting one of two units shows up in the building's occupancy and income.
+#[tokio::test]
+async fn test_summary_aggregates_units() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let (building_id, units) = building_with_units(&fx).await?;
+
+    let listed: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_building_units")
+        .args_json(json!({ "building_id": building_id }))
+        .await?
+        .json()?;
+    assert_eq!(listed.len(), 2);
+    let parent: Option<u64> = fx
+        .contract
+        .view("get_unit_building")
+        .args_json(json!({ "unit_id": units[1] }))
+        .await?
+        .json()?;
+    assert_eq!(parent, Some(building_id));
+
+    fx.let_to_buyer(units[0]).await?;
+
+    let summary: serde_json::Value = fx
+        .contract
+        .view("get_building_summary")
+        .args_json(json!({ "building_id": building_id }))
+        .await?
+        .json()?;
+    assert_eq!(summary["unit_count"], 2);
+    assert_eq!(summary["occupied_units"], 1);
+    assert_eq!(summary["vacant_units"], 1);
+    assert_eq!(summary["occupancy_bps"], 5_000);
+    assert_eq!(summary["lease_income"], BID_AMOUNT.to_string());
+    assert_eq!(summary["units"][0]["tenant_id"], fx.buyer.id().as_str());
+    assert_eq!(summary["units"][1]["occupied"], false);
+
+    Ok(())
+}
+
+/// The building NFT stays put while a unit is let.
+#[tokio::test]
+async fn test_occupied_building_cannot_be_transferred() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let (building_id, units) = building_with_units(&fx).await?;
+    fx.let_to_buyer(units[0]).await?;
+
+    let transfer = fx
+        .seller
+        .call(fx.contract.id(), "nft_transfer")
+        .args_json(json!({ "receiver_id": fx.buyer.id(), "token_id": building_id.to_string() }))
+        .deposit(one_yocto())
+        .transact()
+        .await?;
+    assert!(transfer.is_failure());
+    let token: serde_json::Value = fx
+        .contract
+        .view("nft_token")
+        .args_json(json!({ "token_id": building_id.to_string() }))
+        .await?
+        .json()?;
+    assert_eq!(token["owner_id"], fx.seller.id().as_str());
+
+    // A unit that isn't let can still leave the building.
+    fx.seller
+        .call(fx.contract.id(), "remove_building_unit")
+        .args_json(json!({ "building_id": building_id, "unit_id": units[1] }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+    let listed: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_building_units")
+        .args_json(json!({ "building_id": building_id }))
+        .await?
+        .json()?;
+    assert_eq!(listed.len(), 1);
+
+    Ok(())
+}
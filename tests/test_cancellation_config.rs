@@ -35,7 +35,7 @@ async fn deploy() -> Result<near_workspaces::Contract, Box<dyn std::error::Error
     Ok(contract)
 }
 
-/// A fresh contract reports v4 and the documented default windows.
+/// A fresh contract reports v5 and the documented default windows.
 ///
 /// `get_version` exists because there was previously no way to ask a deployed
 /// contract which state layout it held — confirming whether a migration had
@@ -47,7 +47,7 @@ async fn test_version_and_default_windows() -> Result<(), Box<dyn std::error::Er
     let contract = deploy().await?;
 
     let version: u32 = contract.view("get_version").await?.json()?;
-    assert_eq!(version, 4, "a freshly initialised contract should be v4");
+    assert_eq!(version, 5, "a freshly initialised contract should be v5");
 
     let windows: [u64; 6] = contract.view("get_cancellation_windows").await?.json()?;
     assert_eq!(
@@ -0,0 +1,196 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use serde_json::json;
+
+// Damage deposits
+// ---------------------------------------------------------------------------
+//
+// The owner sets the deposit a property asks for and the tenant lodges it with
+// the lease bid (`LeaseWithDeposit`). Accepting the bid pays the owner only
+// the rent part; the deposit stays in the contract against the lease until
+// the lease ends and the owner's deductions, if any, are settled.
+
+const DEPOSIT: u128 = BID_AMOUNT / 5;
+
+async fn set_damage_deposit(fx: &Fixture, property_id: u64) -> TestResult {
+    fx.seller
+        .call(fx.contract.id(), "set_damage_deposit")
+        .args_json(json!({ "property_id": property_id, "amount": DEPOSIT.to_string() }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// The owner is paid the rent; the deposit is held against the new lease and
+/// shows on the property.
+#[tokio::test]
+async fn test_deposit_held_against_lease() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(false).await?;
+    set_damage_deposit(&fx, property_id).await?;
+
+    let bid_id = fx.bid_counter().await?;
+    fx.transfer_call(
+        &fx.buyer,
+        BID_AMOUNT,
+        json!({
+            "property_id": property_id,
+            "action": "Lease",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "LeaseWithDeposit": { "damage_deposit": DEPOSIT.to_string() } },
+        }),
+    )
+    .await?
+    .into_result()?;
+
+    let owner_before = fx.ft_balance(fx.seller.id()).await?;
+    let lease_id = fx.accept_lease_bid(property_id, bid_id).await?;
+    assert_eq!(
+        fx.ft_balance(fx.seller.id()).await?,
+        owner_before + BID_AMOUNT - DEPOSIT,
+        "the deposit isn't the owner's yet"
+    );
+
+    let deposit: serde_json::Value = fx
+        .contract
+        .view("get_damage_deposit")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    assert_eq!(deposit["status"], "Held");
+    assert_eq!(deposit["amount"], DEPOSIT.to_string());
+    assert_eq!(deposit["tenant_id"], fx.buyer.id().as_str());
+
+    let property: serde_json::Value = fx
+        .contract
+        .view("get_property_by_id")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    assert_eq!(property["damage_escrow"], DEPOSIT.to_string());
+
+    // Nothing to settle while the lease is running.
+    let early = fx
+        .buyer
+        .call(fx.contract.id(), "settle_damage_deposit")
+        .args_json(json!({ "lease_id": lease_id }))
+        .transact()
+        .await?;
+    assert!(early.is_failure());
+
+    Ok(())
+}
+
+/// A property that asks for a deposit won't be let on a bid without one.
+#[tokio::test]
+async fn test_lease_bid_without_deposit_is_refused() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(false).await?;
+    set_damage_deposit(&fx, property_id).await?;
+
+    let before = fx.bid_counter().await?;
+    let short = fx
+        .transfer_call(
+            &fx.buyer,
+            BID_AMOUNT,
+            json!({
+                "property_id": property_id,
+                "action": "Lease",
+                "stablecoin_token": fx.ft.id(),
+                "kind": { "LeaseWithDeposit": { "damage_deposit": (DEPOSIT / 2).to_string() } },
+            }),
+        )
+        .await?;
+    short.into_result()?;
+    assert_eq!(
+        fx.bid_counter().await?,
+        before,
+        "an undersized deposit was taken"
+    );
+
+    let bid_id = fx.place_bid(property_id, false).await?;
+    let accepted = fx
+        .seller
+        .call(fx.contract.id(), "accept_bid")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(accepted.is_failure());
+    assert_eq!(
+        fx.bid_status(property_id, bid_id).await?.as_deref(),
+        Some("Pending")
+    );
+
+    Ok(())
+}
+
+/// The rent is what the bid holds beyond the deposit, so neither the tenant
+/// nor a counter-offer can bring the bid down to the deposit.
+#[tokio::test]
+async fn test_lease_bid_stays_above_deposit() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(false).await?;
+    set_damage_deposit(&fx, property_id).await?;
+
+    let bid_id = fx.bid_counter().await?;
+    fx.transfer_call(
+        &fx.buyer,
+        BID_AMOUNT,
+        json!({
+            "property_id": property_id,
+            "action": "Lease",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "LeaseWithDeposit": { "damage_deposit": DEPOSIT.to_string() } },
+        }),
+    )
+    .await?
+    .into_result()?;
+
+    let reduce = fx
+        .buyer
+        .call(fx.contract.id(), "reduce_bid")
+        .args_json(json!({
+            "property_id": property_id,
+            "bid_id": bid_id,
+            "amount": (BID_AMOUNT - DEPOSIT).to_string(),
+        }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(reduce.is_failure(), "the bid was reduced to its deposit");
+
+    let counter = fx
+        .seller
+        .call(fx.contract.id(), "counter_offer")
+        .args_json(json!({
+            "property_id": property_id,
+            "bid_id": bid_id,
+            "amount": DEPOSIT.to_string(),
+            "terms": null,
+        }))
+        .deposit(one_yocto())
+        .transact()
+        .await?;
+    assert!(counter.is_failure(), "a counter-offer left no rent");
+
+    // The bid is untouched and still lets with the full deposit.
+    let lease_id = fx.accept_lease_bid(property_id, bid_id).await?;
+    let deposit: serde_json::Value = fx
+        .contract
+        .view("get_damage_deposit")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    assert_eq!(deposit["amount"], DEPOSIT.to_string());
+
+    Ok(())
+}
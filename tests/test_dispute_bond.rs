@@ -0,0 +1,255 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use serde_json::json;
+
+// Dispute bonds
+// ---------------------------------------------------------------------------
+//
+// Once the owner sets a bond, a dispute can only be raised by sending it in
+// the lease's or bid's stablecoin through `ft_transfer_call`. The winner of
+// the dispute decides where it goes: back to whoever posted it, or to the
+// other side — or the treasury — if they lose.
+
+const BOND: u128 = 1_000;
+
+async fn set_bond(fx: &Fixture, forfeit_to_treasury: bool) -> TestResult {
+    fx.contract
+        .call("set_dispute_bond_terms")
+        .args_json(json!({
+            "bond_amount": BOND.to_string(),
+            "forfeit_to_treasury": forfeit_to_treasury,
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// Places a purchase bid and has the seller accept it; returns the bid id.
+async fn accepted_bid(fx: &Fixture, property_id: u64) -> TestResult<u64> {
+    let bid_id = fx.place_bid(property_id, true).await?;
+    fx.seller
+        .call(fx.contract.id(), "accept_bid_with_escrow")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(bid_id)
+}
+
+async fn resolve_bid(fx: &Fixture, property_id: u64, bid_id: u64, resolution: &str) -> TestResult {
+    fx.contract
+        .call("admin_resolve_bid_dispute")
+        .args_json(json!({
+            "property_id": property_id,
+            "bid_id": bid_id,
+            "resolution": resolution,
+        }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// Without the bond the dispute is refused; with it, the overpayment comes
+/// back, and a buyer who wins has the bond returned with their refund.
+#[tokio::test]
+async fn test_bond_returned_to_winner() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    set_bond(&fx, false).await?;
+    let property_id = fx.mint_property(true).await?;
+    let bid_id = accepted_bid(&fx, property_id).await?;
+
+    let unbonded = fx
+        .buyer
+        .call(fx.contract.id(), "raise_dispute")
+        .args_json(json!({
+            "bid_id": bid_id,
+            "property_id": property_id,
+            "reason": "The survey doesn't match the listing",
+        }))
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(unbonded.is_failure(), "a dispute was raised without a bond");
+
+    let before = fx.ft_balance(fx.buyer.id()).await?;
+    fx.transfer_call(
+        &fx.buyer,
+        BOND + 500,
+        json!({ "RaiseBidDispute": {
+            "property_id": property_id,
+            "bid_id": bid_id,
+            "reason": "The survey doesn't match the listing",
+        }}),
+    )
+    .await?
+    .into_result()?;
+    assert_eq!(fx.ft_balance(fx.buyer.id()).await?, before - BOND);
+    assert_eq!(
+        fx.bid_status(property_id, bid_id).await?.as_deref(),
+        Some("Disputed"),
+    );
+    let bond: serde_json::Value = fx
+        .contract
+        .view("get_dispute_bond")
+        .args_json(json!({ "case": { "Bid": { "property_id": property_id, "bid_id": bid_id } } }))
+        .await?
+        .json()?;
+    assert_eq!(bond["amount"], BOND.to_string());
+    assert_eq!(bond["posted_by"], fx.buyer.id().to_string());
+
+    resolve_bid(&fx, property_id, bid_id, "BuyerWins").await?;
+    assert_eq!(
+        fx.ft_balance(fx.buyer.id()).await?,
+        before + BID_AMOUNT,
+        "the buyer should have their bid and their bond back"
+    );
+
+    Ok(())
+}
+
+/// A buyer who loses forfeits the bond to the seller.
+#[tokio::test]
+async fn test_bond_forfeited_to_counterparty() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    set_bond(&fx, false).await?;
+    let property_id = fx.mint_property(true).await?;
+    let bid_id = accepted_bid(&fx, property_id).await?;
+
+    fx.transfer_call(
+        &fx.buyer,
+        BOND,
+        json!({ "RaiseBidDispute": {
+            "property_id": property_id,
+            "bid_id": bid_id,
+            "reason": "Changed my mind",
+        }}),
+    )
+    .await?
+    .into_result()?;
+
+    let seller_before = fx.ft_balance(fx.seller.id()).await?;
+    resolve_bid(&fx, property_id, bid_id, "SellerWins").await?;
+    assert_eq!(fx.ft_balance(fx.seller.id()).await?, seller_before + BOND);
+    let bond: Option<serde_json::Value> = fx
+        .contract
+        .view("get_dispute_bond")
+        .args_json(json!({ "case": { "Bid": { "property_id": property_id, "bid_id": bid_id } } }))
+        .await?
+        .json()?;
+    assert!(bond.is_none(), "the bond was left behind after resolution");
+
+    Ok(())
+}
+
+/// A tenant's lease dispute shows its bond on the lease, and a lost bond
+/// goes to the treasury when the owner has set it to.
+#[tokio::test]
+async fn test_lease_bond_forfeited_to_treasury() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    set_bond(&fx, true).await?;
+    let (_, lease_id) = fx.let_property().await?;
+
+    fx.transfer_call(
+        &fx.buyer,
+        BOND,
+        json!({ "RaiseLeaseDispute": { "lease_id": lease_id, "reason": "Boiler broken" } }),
+    )
+    .await?
+    .into_result()?;
+    let lease: serde_json::Value = fx
+        .contract
+        .view("get_lease_by_id")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    assert_eq!(lease["dispute_status"]["status"], "raised");
+    assert_eq!(lease["dispute"]["bond"], BOND.to_string());
+
+    fx.contract
+        .call("resolve_dispute")
+        .args_json(json!({ "lease_id": lease_id, "winner": "Owner", "payout_amount": "0" }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    let accrued: Vec<serde_json::Value> = fx.contract.view("get_accrued_fees").await?.json()?;
+    let treasury = accrued
+        .iter()
+        .find(|fee| fee["stablecoin_token"] == fx.ft.id().to_string())
+        .expect("nothing accrued in the lease's stablecoin");
+    assert_eq!(treasury["amount"], BOND.to_string());
+
+    Ok(())
+}
+
+/// A landlord's claim on the damage deposit takes the bond too, and a
+/// landlord who loses it to the tenant forfeits the bond to them.
+#[tokio::test]
+async fn test_owner_lease_dispute_bonded() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    set_bond(&fx, false).await?;
+    let (_, lease_id) = fx.let_property_with_deposit(BID_AMOUNT / 5).await?;
+
+    let claim = (BID_AMOUNT / 10).to_string();
+    let unbonded = fx
+        .seller
+        .call(fx.contract.id(), "raise_owner_lease_dispute")
+        .args_json(json!({ "lease_id": lease_id, "reason": "Carpets ruined", "claim": claim }))
+        .transact()
+        .await?;
+    assert!(unbonded.is_failure(), "a claim was raised without a bond");
+
+    fx.transfer_call(
+        &fx.seller,
+        BOND,
+        json!({ "RaiseOwnerLeaseDispute": {
+            "lease_id": lease_id,
+            "reason": "Carpets ruined",
+            "claim": claim,
+        }}),
+    )
+    .await?
+    .into_result()?;
+    let bond: serde_json::Value = fx
+        .contract
+        .view("get_dispute_bond")
+        .args_json(json!({ "case": { "Lease": { "lease_id": lease_id } } }))
+        .await?
+        .json()?;
+    assert_eq!(bond["posted_by"], fx.seller.id().to_string());
+
+    fx.buyer
+        .call(fx.contract.id(), "contest_lease_dispute")
+        .args_json(json!({ "lease_id": lease_id }))
+        .transact()
+        .await?
+        .into_result()?;
+    let before = fx.ft_balance(fx.buyer.id()).await?;
+    fx.contract
+        .call("resolve_dispute")
+        .args_json(json!({ "lease_id": lease_id, "winner": "Tenant", "payout_amount": "0" }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(
+        fx.ft_balance(fx.buyer.id()).await?,
+        before + BOND,
+        "the tenant should have the landlord's bond"
+    );
+
+    Ok(())
+}
@@ -19,45 +19,13 @@ use serde_json::json;
 //   SellerWins  bid returns to DocsConfirmed so the ordinary release path
 //               finishes the deal — no second copy of the completion logic
 
-/// Drives a bid to `Disputed` and returns its id.
-async fn disputed_bid(fx: &common::Fixture, property_id: u64) -> common::TestResult<u64> {
-    let bid_id = fx.place_bid(property_id, true).await?;
-
-    fx.seller
-        .call(fx.contract.id(), "accept_bid_with_escrow")
-        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
-        .deposit(NearToken::from_yoctonear(1))
-        .max_gas()
-        .transact()
-        .await?
-        .into_result()?;
-
-    fx.buyer
-        .call(fx.contract.id(), "raise_dispute")
-        .args_json(json!({
-            "bid_id": bid_id,
-            "property_id": property_id,
-            "reason": "Seller stopped responding after the viewing",
-        }))
-        .max_gas()
-        .transact()
-        .await?
-        .into_result()?;
-
-    assert_eq!(
-        fx.bid_status(property_id, bid_id).await?.as_deref(),
-        Some("Disputed"),
-    );
-    Ok(bid_id)
-}
-
 #[tokio::test]
 async fn test_buyer_wins_refunds_in_full() -> common::TestResult {
     let worker = near_workspaces::sandbox().await?;
     let fx = common::setup(&worker).await?;
 
     let property_id = fx.mint_property(true).await?;
-    let bid_id = disputed_bid(&fx, property_id).await?;
+    let bid_id = fx.disputed_bid(property_id).await?;
     let buyer_before = fx.ft_balance(fx.buyer.id()).await?;
 
     let resolved = fx
@@ -103,7 +71,7 @@ async fn test_split_halves_the_escrow_and_rounds_to_the_buyer() -> common::TestR
     let fx = common::setup(&worker).await?;
 
     let property_id = fx.mint_property(true).await?;
-    let bid_id = disputed_bid(&fx, property_id).await?;
+    let bid_id = fx.disputed_bid(property_id).await?;
 
     let buyer_before = fx.ft_balance(fx.buyer.id()).await?;
     let seller_before = fx.ft_balance(fx.seller.id()).await?;
@@ -144,7 +112,7 @@ async fn test_seller_wins_returns_the_bid_to_the_normal_path() -> common::TestRe
     let fx = common::setup(&worker).await?;
 
     let property_id = fx.mint_property(true).await?;
-    let bid_id = disputed_bid(&fx, property_id).await?;
+    let bid_id = fx.disputed_bid(property_id).await?;
 
     let buyer_before = fx.ft_balance(fx.buyer.id()).await?;
     let seller_before = fx.ft_balance(fx.seller.id()).await?;
@@ -180,7 +148,7 @@ async fn test_only_admins_can_resolve_and_undisputed_bids_are_rejected() -> comm
     let fx = common::setup(&worker).await?;
 
     let property_id = fx.mint_property(true).await?;
-    let bid_id = disputed_bid(&fx, property_id).await?;
+    let bid_id = fx.disputed_bid(property_id).await?;
 
     // Settling a dispute moves someone else's money, so it cannot be open to
     // the parties themselves — the buyer would simply always win.
@@ -231,7 +199,7 @@ async fn test_disputed_bids_are_listed_for_admins() -> common::TestResult {
     let fx = common::setup(&worker).await?;
 
     let property_id = fx.mint_property(true).await?;
-    let bid_id = disputed_bid(&fx, property_id).await?;
+    let bid_id = fx.disputed_bid(property_id).await?;
 
     // Without this an admin would have to scan every property to find what
     // needs settling.
@@ -0,0 +1,160 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult};
+use near_workspaces::Account;
+use serde_json::json;
+
+// Admin votes on lease disputes
+// ---------------------------------------------------------------------------
+//
+// Each admin has one vote on a raised lease dispute, which they can change
+// until voting closes. Once the quorum has voted and a side has the
+// majority, the dispute resolves itself.
+
+/// Lets a property to the buyer, who raises a dispute; returns the lease id.
+async fn disputed_lease(fx: &Fixture) -> TestResult<u64> {
+    let (_, lease_id) = fx.let_property().await?;
+    fx.buyer
+        .call(fx.contract.id(), "raise_lease_dispute_with_reason")
+        .args_json(json!({ "lease_id": lease_id, "reason": "Heating never worked" }))
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(lease_id)
+}
+
+async fn vote(admin: &Account, fx: &Fixture, lease_id: u64, for_tenant: bool) -> TestResult {
+    admin
+        .call(fx.contract.id(), "vote_lease_dispute")
+        .args_json(json!({
+            "lease_id": lease_id,
+            "vote_for_tenant": for_tenant,
+            "payout_amount": "0",
+        }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+async fn dispute_status(fx: &Fixture, lease_id: u64) -> TestResult<String> {
+    let lease: serde_json::Value = fx
+        .contract
+        .view("get_lease_by_id")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    Ok(lease["dispute_status"]["status"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// An admin voting twice replaces their vote; a second admin reaching the
+/// quorum resolves the dispute.
+#[tokio::test]
+async fn test_quorum_resolves_dispute() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    fx.contract
+        .call("set_dispute_voting_rules")
+        .args_json(json!({ "quorum": 2, "majority_bps": null, "voting_period_ns": null }))
+        .transact()
+        .await?
+        .into_result()?;
+    let second_admin = worker.dev_create_account().await?;
+    fx.contract
+        .call("add_admin")
+        .args_json(json!({ "new_admin_id": second_admin.id() }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let lease_id = disputed_lease(&fx).await?;
+    let owner = fx.contract.as_account();
+    vote(owner, &fx, lease_id, false).await?;
+    vote(owner, &fx, lease_id, true).await?;
+
+    let voting: serde_json::Value = fx
+        .contract
+        .view("get_dispute_votes")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    assert_eq!(voting["votes"].as_array().map(Vec::len), Some(1));
+    assert_eq!(voting["votes"][0]["for_tenant"], true);
+    assert_eq!(dispute_status(&fx, lease_id).await?, "raised");
+
+    vote(&second_admin, &fx, lease_id, true).await?;
+    assert_eq!(dispute_status(&fx, lease_id).await?, "resolved");
+
+    Ok(())
+}
+
+/// Voting can't be closed early, and only admins vote.
+#[tokio::test]
+async fn test_vote_stays_open_until_period_ends() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let lease_id = disputed_lease(&fx).await?;
+
+    let outsider = vote(&fx.buyer, &fx, lease_id, true).await;
+    assert!(outsider.is_err());
+
+    // Callers from before votes carried a payout still work.
+    fx.contract
+        .call("vote_lease_dispute")
+        .args_json(json!({ "lease_id": lease_id, "vote_for_tenant": true }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    let early = fx
+        .buyer
+        .call(fx.contract.id(), "close_dispute_vote")
+        .args_json(json!({ "lease_id": lease_id }))
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(early.is_failure());
+    assert_eq!(dispute_status(&fx, lease_id).await?, "raised");
+
+    Ok(())
+}
+
+/// A vote that closes short of the quorum decides nothing; the dispute waits
+/// for an admin.
+#[tokio::test]
+async fn test_close_without_quorum_leaves_dispute_open() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    fx.contract
+        .call("set_dispute_voting_rules")
+        .args_json(
+            json!({ "quorum": 2, "majority_bps": null, "voting_period_ns": 1_000_000_000u64 }),
+        )
+        .transact()
+        .await?
+        .into_result()?;
+    let lease_id = disputed_lease(&fx).await?;
+
+    vote(fx.contract.as_account(), &fx, lease_id, true).await?;
+    worker.fast_forward(5).await?;
+    let closed = fx
+        .buyer
+        .call(fx.contract.id(), "close_dispute_vote")
+        .args_json(json!({ "lease_id": lease_id }))
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(
+        closed.is_failure(),
+        "one vote settled a dispute needing two"
+    );
+    assert_eq!(dispute_status(&fx, lease_id).await?, "raised");
+
+    Ok(())
+}
@@ -0,0 +1,208 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use serde_json::json;
+
+// Earnest money
+// ---------------------------------------------------------------------------
+//
+// A buyer can bid with a deposit instead of the full price. The deal runs the
+// ordinary escrow path; once the buyer confirms the documents the balance
+// falls due, and escrow can't be released to the seller until it is paid.
+// Missing the deadline forfeits the deposit (or the configured share of it)
+// to the seller and puts the property back on the market.
+
+const DEPOSIT: u128 = BID_AMOUNT / 10;
+
+/// Places an earnest-money bid and takes it through to `DocsConfirmed`.
+async fn confirmed_earnest_bid(fx: &Fixture) -> TestResult<(u64, u64)> {
+    let property_id = fx.mint_property(true).await?;
+    let bid_id = fx.bid_counter().await?;
+
+    fx.transfer_call(
+        &fx.buyer,
+        DEPOSIT,
+        json!({
+            "property_id": property_id,
+            "action": "Purchase",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "EarnestMoney": { "offer_price": BID_AMOUNT.to_string() } },
+        }),
+    )
+    .await?
+    .into_result()?;
+    assert_eq!(
+        fx.bid_counter().await?,
+        bid_id + 1,
+        "earnest bid not placed"
+    );
+
+    // The fast path would pay the seller the deposit and hand over the NFT.
+    let fast = fx
+        .seller
+        .call(fx.contract.id(), "accept_bid")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(fast.is_failure());
+
+    fx.seller
+        .call(fx.contract.id(), "accept_bid_with_escrow")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    fx.seller
+        .call(fx.contract.id(), "confirm_document_release")
+        .args_json(json!({
+            "bid_id": bid_id,
+            "property_id": property_id,
+            "document_image_uri": "https://example.com/agreement.png",
+            "document_description": "Sale agreement",
+        }))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    fx.buyer
+        .call(fx.contract.id(), "confirm_document_receipt")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok((property_id, bid_id))
+}
+
+async fn earnest_money(fx: &Fixture, bid_id: u64) -> TestResult<serde_json::Value> {
+    Ok(fx
+        .contract
+        .view("get_earnest_money")
+        .args_json(json!({ "bid_id": bid_id }))
+        .await?
+        .json()?)
+}
+
+/// Escrow stays shut until the balance is in; paying it refunds any excess
+/// and brings the bid up to the full price.
+#[tokio::test]
+async fn test_balance_paid_before_release() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let (property_id, bid_id) = confirmed_earnest_bid(&fx).await?;
+
+    let terms = earnest_money(&fx, bid_id).await?;
+    assert_eq!(terms["balance_due"], (BID_AMOUNT - DEPOSIT).to_string());
+    assert!(
+        terms["balance_due_by"].is_u64(),
+        "deadline should be running"
+    );
+
+    let early = fx
+        .buyer
+        .call(fx.contract.id(), "release_escrow")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(format!("{:?}", early.into_result().unwrap_err()).contains("hasn't been paid"));
+
+    let before = fx.ft_balance(fx.buyer.id()).await?;
+    fx.transfer_call(
+        &fx.buyer,
+        BID_AMOUNT,
+        json!({
+            "property_id": property_id,
+            "action": "Purchase",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "PayBalance": { "bid_id": bid_id } },
+        }),
+    )
+    .await?
+    .into_result()?;
+
+    assert_eq!(
+        fx.ft_balance(fx.buyer.id()).await?,
+        before - (BID_AMOUNT - DEPOSIT),
+        "only the balance should have been kept"
+    );
+    let terms = earnest_money(&fx, bid_id).await?;
+    assert_eq!(terms["balance_paid"], true);
+    assert_eq!(terms["balance_due"], "0");
+
+    Ok(())
+}
+
+/// Past the deadline the deposit goes to the seller, the agreement is burned
+/// and the property is listed again.
+#[tokio::test]
+async fn test_unpaid_balance_forfeits_deposit() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+
+    fx.contract
+        .call("set_earnest_money_terms")
+        .args_json(json!({
+            "min_deposit_bps": null,
+            "forfeit_bps": null,
+            "balance_due_window_ns": 60_000_000_000u64,
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let (property_id, bid_id) = confirmed_earnest_bid(&fx).await?;
+    let seller_before = fx.ft_balance(fx.seller.id()).await?;
+
+    let too_soon = fx
+        .seller
+        .call(fx.contract.id(), "forfeit_earnest_money")
+        .args_json(json!({ "property_id": property_id, "bid_id": bid_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(too_soon.is_failure());
+
+    worker.fast_forward(120).await?;
+
+    fx.seller
+        .call(fx.contract.id(), "forfeit_earnest_money")
+        .args_json(json!({ "property_id": property_id, "bid_id": bid_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert_eq!(
+        fx.bid_status(property_id, bid_id).await?.as_deref(),
+        Some("Cancelled")
+    );
+    assert_eq!(
+        fx.ft_balance(fx.seller.id()).await?,
+        seller_before + DEPOSIT
+    );
+
+    let document: Option<serde_json::Value> = fx
+        .contract
+        .view("nft_token")
+        .args_json(json!({ "token_id": format!("doc:{}:{}", property_id, bid_id) }))
+        .await?
+        .json()?;
+    assert!(document.is_none(), "the agreement should be burned");
+
+    let property: serde_json::Value = fx
+        .contract
+        .view("get_property_by_id")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    assert_eq!(property["is_for_sale"], true);
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult};
+use near_workspaces::Account;
+use serde_json::json;
+
+// Dispute evidence
+// ---------------------------------------------------------------------------
+//
+// Raising a dispute opens a case file. Both sides can attach evidence — a
+// URI, a content hash and a description — until the response period ends,
+// and admins can add notes until the dispute is resolved.
+
+async fn submit(
+    account: &Account,
+    fx: &Fixture,
+    case: &serde_json::Value,
+    uri: &str,
+) -> TestResult<near_workspaces::result::ExecutionFinalResult> {
+    Ok(account
+        .call(fx.contract.id(), "submit_evidence")
+        .args_json(json!({
+            "case": case,
+            "uri": uri,
+            "content_hash": "sha256:9f86d081884c7d659a2feaa0c55ad015",
+            "description": "Signed survey report",
+        }))
+        .deposit(one_yocto())
+        .transact()
+        .await?)
+}
+
+/// Both parties add evidence, and the log pages oldest first.
+#[tokio::test]
+async fn test_parties_submit_evidence() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(true).await?;
+    let bid_id = fx.disputed_bid(property_id).await?;
+    let case = json!({ "Bid": { "property_id": property_id, "bid_id": bid_id } });
+
+    let file: serde_json::Value = fx
+        .contract
+        .view("get_dispute_case_file")
+        .args_json(json!({ "case": case }))
+        .await?
+        .json()?;
+    assert_eq!(file["raised_by"], fx.buyer.id().as_str());
+    assert_eq!(file["respondent"], fx.seller.id().as_str());
+
+    submit(&fx.buyer, &fx, &case, "ipfs://survey")
+        .await?
+        .into_result()?;
+    submit(&fx.seller, &fx, &case, "ipfs://reply")
+        .await?
+        .into_result()?;
+
+    let entries: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_dispute_evidence")
+        .args_json(json!({ "case": case, "from_index": 1, "limit": 10 }))
+        .await?
+        .json()?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["submitted_by"], fx.seller.id().as_str());
+    assert_eq!(entries[0]["uri"], "ipfs://reply");
+
+    Ok(())
+}
+
+/// Outsiders can't add to the file, and nothing goes in once the response
+/// period is over — except an admin's notes.
+#[tokio::test]
+async fn test_evidence_closes_with_response_period() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    fx.contract
+        .call("set_dispute_response_period")
+        .args_json(json!({ "period_ns": 0 }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let property_id = fx.mint_property(true).await?;
+    let bid_id = fx.disputed_bid(property_id).await?;
+    let case = json!({ "Bid": { "property_id": property_id, "bid_id": bid_id } });
+
+    let outsider = worker.dev_create_account().await?;
+    assert!(submit(&outsider, &fx, &case, "ipfs://x")
+        .await?
+        .is_failure());
+    assert!(submit(&fx.seller, &fx, &case, "ipfs://late")
+        .await?
+        .is_failure());
+    submit(fx.contract.as_account(), &fx, &case, "ipfs://admin-note")
+        .await?
+        .into_result()?;
+
+    Ok(())
+}
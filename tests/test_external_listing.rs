@@ -0,0 +1,133 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use near_workspaces::types::NearToken;
+use serde_json::json;
+
+// Listing from the NFT
+// ---------------------------------------------------------------------------
+//
+// An owner can list a property by sending its NFT to the contract with
+// `nft_transfer_call`, or by approving the contract with `nft_approve`, with
+// the listing terms as the message.
+
+fn listing_msg() -> String {
+    json!({
+        "price": BID_AMOUNT.to_string(),
+        "is_for_sale": true,
+        "lease_duration_months": null,
+    })
+    .to_string()
+}
+
+async fn nft_holder(fx: &Fixture, property_id: u64) -> TestResult<String> {
+    let token: serde_json::Value = fx
+        .contract
+        .view("nft_token")
+        .args_json(json!({ "token_id": property_id.to_string() }))
+        .await?
+        .json()?;
+    Ok(token["owner_id"].as_str().unwrap_or_default().to_string())
+}
+
+async fn is_for_sale(fx: &Fixture, property_id: u64) -> TestResult<bool> {
+    let property: serde_json::Value = fx
+        .contract
+        .view("get_property_by_id")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    Ok(property["is_for_sale"].as_bool().unwrap_or_default())
+}
+
+/// Transferring the NFT in lists the property; the contract holds it until
+/// a purchase hands it to the buyer.
+#[tokio::test]
+async fn test_transfer_call_lists_in_custody() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(false).await?;
+
+    fx.seller
+        .call(fx.contract.id(), "nft_transfer_call")
+        .args_json(json!({
+            "receiver_id": fx.contract.id(),
+            "token_id": property_id.to_string(),
+            "msg": listing_msg(),
+        }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert_eq!(
+        nft_holder(&fx, property_id).await?,
+        fx.contract.id().as_str()
+    );
+    assert!(is_for_sale(&fx, property_id).await?);
+    let listing: serde_json::Value = fx
+        .contract
+        .view("get_external_listing")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    assert_eq!(listing, json!("Custody"));
+
+    let bid_id = fx.place_bid(property_id, true).await?;
+    fx.seller
+        .call(fx.contract.id(), "accept_bid")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert_eq!(nft_holder(&fx, property_id).await?, fx.buyer.id().as_str());
+    assert_eq!(
+        fx.property_owner(property_id).await?.as_deref(),
+        Some(fx.buyer.id().as_str())
+    );
+
+    Ok(())
+}
+
+/// Approving the contract lists the property without giving up the NFT, and
+/// revoking the approval takes the listing down.
+#[tokio::test]
+async fn test_approval_lists_without_custody() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(false).await?;
+
+    fx.seller
+        .call(fx.contract.id(), "nft_approve")
+        .args_json(json!({
+            "token_id": property_id.to_string(),
+            "account_id": fx.contract.id(),
+            "msg": listing_msg(),
+        }))
+        .deposit(NearToken::from_millinear(10))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert_eq!(nft_holder(&fx, property_id).await?, fx.seller.id().as_str());
+    assert!(is_for_sale(&fx, property_id).await?);
+
+    fx.seller
+        .call(fx.contract.id(), "nft_revoke")
+        .args_json(json!({
+            "token_id": property_id.to_string(),
+            "account_id": fx.contract.id(),
+        }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+    assert!(!is_for_sale(&fx, property_id).await?);
+
+    Ok(())
+}
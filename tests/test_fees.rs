@@ -0,0 +1,122 @@
+mod common;
+
+use common::{one_yocto, setup, TestResult, BID_AMOUNT};
+use near_workspaces::types::NearToken;
+use serde_json::json;
+
+// Platform fees and broker commissions
+// ---------------------------------------------------------------------------
+//
+// The contract owner sets a fee per kind of deal and stablecoin, and an owner
+// can attach a broker to a listing. Both come out of what the property owner
+// is paid; the platform's cut accrues in a ledger of its own until the
+// contract owner withdraws it.
+
+/// A lease payment pays a 2% platform fee and a 1% broker commission.
+#[tokio::test]
+async fn test_lease_payment_pays_fee_and_commission() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(false).await?;
+
+    fx.contract
+        .call("set_platform_fee")
+        .args_json(json!({
+            "action": "Lease",
+            "stablecoin_token": fx.ft.id(),
+            "bps": 200,
+            "flat": "0",
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let broker = worker.dev_create_account().await?;
+    fx.ft
+        .call("storage_deposit")
+        .args_json(json!({ "account_id": broker.id() }))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await?
+        .into_result()?;
+    fx.seller
+        .call(fx.contract.id(), "set_listing_broker")
+        .args_json(json!({ "property_id": property_id, "broker_id": broker.id(), "bps": 100 }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+
+    let quote: serde_json::Value = fx
+        .contract
+        .view("quote_fees")
+        .args_json(json!({
+            "property_id": property_id,
+            "action": "Lease",
+            "stablecoin_token": fx.ft.id(),
+            "amount": BID_AMOUNT.to_string(),
+        }))
+        .await?
+        .json()?;
+    assert_eq!(quote["net"], (BID_AMOUNT * 97 / 100).to_string());
+
+    let bid_id = fx.place_bid(property_id, false).await?;
+    let owner_before = fx.ft_balance(fx.seller.id()).await?;
+    fx.seller
+        .call(fx.contract.id(), "accept_bid")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert_eq!(
+        fx.ft_balance(fx.seller.id()).await?,
+        owner_before + BID_AMOUNT * 97 / 100
+    );
+    assert_eq!(fx.ft_balance(broker.id()).await?, BID_AMOUNT / 100);
+    let accrued: Vec<serde_json::Value> = fx.contract.view("get_accrued_fees").await?.json()?;
+    assert_eq!(accrued.len(), 1);
+    assert_eq!(accrued[0]["amount"], (BID_AMOUNT * 2 / 100).to_string());
+
+    // Only the contract owner takes the fees out.
+    let withdrawal = fx
+        .buyer
+        .call(fx.contract.id(), "withdraw_fees")
+        .args_json(json!({ "stablecoin_token": fx.ft.id(), "amount": null }))
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(withdrawal.is_failure());
+
+    Ok(())
+}
+
+/// Without any fees set the owner is paid the whole bid.
+#[tokio::test]
+async fn test_no_fees_by_default() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(false).await?;
+
+    let bid_id = fx.place_bid(property_id, false).await?;
+    let owner_before = fx.ft_balance(fx.seller.id()).await?;
+    fx.seller
+        .call(fx.contract.id(), "accept_bid")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert_eq!(
+        fx.ft_balance(fx.seller.id()).await?,
+        owner_before + BID_AMOUNT
+    );
+    let accrued: Vec<serde_json::Value> = fx.contract.view("get_accrued_fees").await?.json()?;
+    assert!(accrued.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use serde_json::json;
+
+// Fractional ownership
+// ---------------------------------------------------------------------------
+//
+// The owner splits a property into shares and the NFT is locked in the
+// contract. Lease income is shared out pro rata among the shareholders, who
+// claim it when they like; a buyout at the reserve price recombines the
+// shares and hands the NFT to the buyer.
+
+const TOTAL_SHARES: u128 = 100;
+
+/// Mints a leasable property and fractionalises it with the given reserve.
+async fn fractionalised_property(fx: &Fixture, reserve_price: u128) -> TestResult<u64> {
+    let property_id = fx.mint_property(false).await?;
+    fx.seller
+        .call(fx.contract.id(), "fractionalise_property")
+        .args_json(json!({
+            "property_id": property_id,
+            "total_shares": TOTAL_SHARES.to_string(),
+            "reserve_price": reserve_price.to_string(),
+            "income_token": fx.ft.id(),
+        }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(property_id)
+}
+
+async fn token_owner(fx: &Fixture, property_id: u64) -> TestResult<String> {
+    let token: serde_json::Value = fx
+        .contract
+        .view("nft_token")
+        .args_json(json!({ "token_id": property_id.to_string() }))
+        .await?
+        .json()?;
+    Ok(token["owner_id"].as_str().unwrap_or_default().to_string())
+}
+
+async fn claim(fx: &Fixture, account: &near_workspaces::Account, property_id: u64) -> TestResult {
+    account
+        .call(fx.contract.id(), "claim_share_income")
+        .args_json(json!({ "property_id": property_id }))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// A lease payment is shared out by shareholding rather than paid to the
+/// manager.
+#[tokio::test]
+async fn test_lease_income_shared_pro_rata() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fractionalised_property(&fx, BID_AMOUNT).await?;
+    assert_eq!(
+        token_owner(&fx, property_id).await?,
+        fx.contract.id().as_str()
+    );
+
+    fx.seller
+        .call(fx.contract.id(), "transfer_shares")
+        .args_json(json!({
+            "property_id": property_id,
+            "receiver_id": fx.buyer.id(),
+            "shares": "25",
+        }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+
+    // The manager lets the property as usual; the tenant gets the NFT.
+    let bid_id = fx.place_bid(property_id, false).await?;
+    let manager_before = fx.ft_balance(fx.seller.id()).await?;
+    fx.seller
+        .call(fx.contract.id(), "accept_bid")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(fx.ft_balance(fx.seller.id()).await?, manager_before);
+    assert_eq!(token_owner(&fx, property_id).await?, fx.buyer.id().as_str());
+
+    let holders: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_shareholders")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    assert_eq!(holders.len(), 2);
+    assert_eq!(holders[0]["claimable"], (BID_AMOUNT * 3 / 4).to_string());
+    assert_eq!(holders[1]["claimable"], (BID_AMOUNT / 4).to_string());
+
+    let investor_before = fx.ft_balance(fx.buyer.id()).await?;
+    claim(&fx, &fx.buyer, property_id).await?;
+    assert_eq!(
+        fx.ft_balance(fx.buyer.id()).await?,
+        investor_before + BID_AMOUNT / 4
+    );
+
+    Ok(())
+}
+
+/// Paying the reserve price buys the other shareholders out and recombines
+/// the property in the buyer's hands.
+#[tokio::test]
+async fn test_buyout_recombines_property() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fractionalised_property(&fx, BID_AMOUNT).await?;
+
+    let price: Option<String> = fx
+        .contract
+        .view("get_buyout_price")
+        .args_json(json!({ "property_id": property_id, "account_id": fx.buyer.id() }))
+        .await?
+        .json()?;
+    assert_eq!(price, Some(BID_AMOUNT.to_string()));
+
+    fx.transfer_call(
+        &fx.buyer,
+        BID_AMOUNT,
+        json!({
+            "property_id": property_id,
+            "action": "Purchase",
+            "stablecoin_token": fx.ft.id(),
+            "kind": "BuyOutShares",
+        }),
+    )
+    .await?
+    .into_result()?;
+
+    assert_eq!(token_owner(&fx, property_id).await?, fx.buyer.id().as_str());
+    assert_eq!(
+        fx.property_owner(property_id).await?.as_deref(),
+        Some(fx.buyer.id().as_str())
+    );
+    let fractional: serde_json::Value = fx
+        .contract
+        .view("get_fractional_property")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    assert_eq!(fractional["status"], "Recombined");
+
+    // The bought-out owner claims their proceeds.
+    let seller_before = fx.ft_balance(fx.seller.id()).await?;
+    claim(&fx, &fx.seller, property_id).await?;
+    assert_eq!(
+        fx.ft_balance(fx.seller.id()).await?,
+        seller_before + BID_AMOUNT
+    );
+
+    Ok(())
+}
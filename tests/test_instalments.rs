@@ -0,0 +1,252 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use serde_json::json;
+
+// Instalment purchases
+// ---------------------------------------------------------------------------
+//
+// The down payment runs the ordinary escrow path. Releasing it hands the buyer
+// the NFT under a lien: the seller keeps the title and the token can't be
+// transferred on until the last instalment is paid. An instalment left unpaid
+// past the grace period lets the seller foreclose and take the NFT back.
+
+const DOWN_PAYMENT: u128 = BID_AMOUNT / 5;
+const INSTALMENT: u128 = (BID_AMOUNT - DOWN_PAYMENT) / 2;
+const INTERVAL_NS: u64 = 60_000_000_000;
+
+/// Places a two-instalment bid and takes it through to the buyer holding the
+/// NFT with the down payment released.
+async fn instalment_bid_in_possession(fx: &Fixture) -> TestResult<(u64, u64)> {
+    // No 24h escrow timelock, so the down payment can be released at once.
+    fx.contract
+        .call("set_time_lock_config")
+        .args_json(json!({
+            "bid_expiry_ns": 0,
+            "escrow_release_delay_ns": 0,
+            "lost_bid_claim_delay_ns": 0,
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let property_id = fx.mint_property(true).await?;
+    let bid_id = fx.bid_counter().await?;
+    fx.transfer_call(
+        &fx.buyer,
+        DOWN_PAYMENT,
+        json!({
+            "property_id": property_id,
+            "action": "Purchase",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "Instalments": {
+                "price": BID_AMOUNT.to_string(),
+                "instalment_count": 2,
+                "interval_ns": INTERVAL_NS,
+            } },
+        }),
+    )
+    .await?
+    .into_result()?;
+
+    fx.seller
+        .call(fx.contract.id(), "accept_bid_with_escrow")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    fx.seller
+        .call(fx.contract.id(), "confirm_document_release")
+        .args_json(json!({
+            "bid_id": bid_id,
+            "property_id": property_id,
+            "document_image_uri": "https://example.com/agreement.png",
+            "document_description": "Instalment sale agreement",
+        }))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    fx.buyer
+        .call(fx.contract.id(), "confirm_document_receipt")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .transact()
+        .await?
+        .into_result()?;
+    fx.buyer
+        .call(fx.contract.id(), "release_escrow")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok((property_id, bid_id))
+}
+
+async fn nft_owner(fx: &Fixture, property_id: u64) -> TestResult<Option<String>> {
+    let token: Option<serde_json::Value> = fx
+        .contract
+        .view("nft_token")
+        .args_json(json!({ "token_id": property_id.to_string() }))
+        .await?
+        .json()?;
+    Ok(token.and_then(|t| t["owner_id"].as_str().map(str::to_string)))
+}
+
+async fn pay_instalment(fx: &Fixture, property_id: u64, bid_id: u64) -> TestResult {
+    fx.transfer_call(
+        &fx.buyer,
+        INSTALMENT,
+        json!({
+            "property_id": property_id,
+            "action": "Purchase",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "PayInstalment": { "bid_id": bid_id } },
+        }),
+    )
+    .await?
+    .into_result()?;
+    Ok(())
+}
+
+/// The buyer holds the NFT but can't move it while instalments are owed; the
+/// last one passes the title and lifts the lien.
+#[tokio::test]
+async fn test_instalments_paid_off_pass_title() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let (property_id, bid_id) = instalment_bid_in_possession(&fx).await?;
+
+    assert_eq!(
+        nft_owner(&fx, property_id).await?.as_deref(),
+        Some(fx.buyer.id().as_str())
+    );
+    assert_eq!(
+        fx.property_owner(property_id).await?.as_deref(),
+        Some(fx.seller.id().as_str()),
+        "title stays with the seller under the lien"
+    );
+
+    let resale = fx
+        .buyer
+        .call(fx.contract.id(), "nft_transfer")
+        .args_json(json!({ "receiver_id": fx.seller.id(), "token_id": property_id.to_string() }))
+        .deposit(one_yocto())
+        .transact()
+        .await?;
+    assert!(resale.is_failure(), "the NFT moved while under a lien");
+
+    // The seller still holds the title, but the buyer is living there.
+    let stays = fx
+        .seller
+        .call(fx.contract.id(), "set_booking_terms")
+        .args_json(json!({
+            "property_id": property_id,
+            "nightly_rate": "10000000",
+            "weekly_rate": null,
+            "min_nights": 1,
+            "max_nights": 14,
+            "cancellation_policy": "Moderate",
+        }))
+        .deposit(one_yocto())
+        .transact()
+        .await?;
+    assert!(stays.is_failure(), "short stays opened under a lien");
+
+    let seller_before = fx.ft_balance(fx.seller.id()).await?;
+    let buyer_before = fx.ft_balance(fx.buyer.id()).await?;
+    pay_instalment(&fx, property_id, bid_id).await?;
+    // ft_transfer_call succeeds even when the payment is refused, so check
+    // the instalment was actually taken and credited to the plan.
+    let plan: serde_json::Value = fx
+        .contract
+        .view("get_instalment_plan")
+        .args_json(json!({ "bid_id": bid_id }))
+        .await?
+        .json()?;
+    assert_eq!(plan["paid_instalments"], 1);
+    assert_eq!(
+        fx.ft_balance(fx.buyer.id()).await?,
+        buyer_before - INSTALMENT
+    );
+    pay_instalment(&fx, property_id, bid_id).await?;
+    assert_eq!(
+        fx.ft_balance(fx.seller.id()).await?,
+        seller_before + INSTALMENT * 2,
+        "instalments go straight to the seller"
+    );
+
+    let plan: serde_json::Value = fx
+        .contract
+        .view("get_instalment_plan")
+        .args_json(json!({ "bid_id": bid_id }))
+        .await?
+        .json()?;
+    assert_eq!(plan["status"], "Completed");
+    assert_eq!(plan["remaining"], "0");
+    assert_eq!(
+        fx.property_owner(property_id).await?.as_deref(),
+        Some(fx.buyer.id().as_str())
+    );
+    let lien: Option<u64> = fx
+        .contract
+        .view("get_property_lien")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    assert_eq!(lien, None);
+
+    Ok(())
+}
+
+/// A missed instalment past the grace period lets the seller take the NFT
+/// back and relist.
+#[tokio::test]
+async fn test_missed_instalment_forecloses() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    fx.contract
+        .call("set_instalment_grace_period")
+        .args_json(json!({ "grace_period_ns": INTERVAL_NS }))
+        .transact()
+        .await?
+        .into_result()?;
+    let (property_id, bid_id) = instalment_bid_in_possession(&fx).await?;
+
+    let foreclose = || async {
+        fx.seller
+            .call(fx.contract.id(), "foreclose_instalment_plan")
+            .args_json(json!({ "property_id": property_id, "bid_id": bid_id }))
+            .deposit(one_yocto())
+            .max_gas()
+            .transact()
+            .await
+    };
+
+    assert!(foreclose().await?.is_failure(), "nothing is overdue yet");
+
+    worker.fast_forward(240).await?;
+    foreclose().await?.into_result()?;
+
+    assert_eq!(
+        nft_owner(&fx, property_id).await?.as_deref(),
+        Some(fx.seller.id().as_str())
+    );
+    assert_eq!(
+        fx.bid_status(property_id, bid_id).await?.as_deref(),
+        Some("Cancelled")
+    );
+
+    let property: serde_json::Value = fx
+        .contract
+        .view("get_property_by_id")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    assert_eq!(property["is_for_sale"], true);
+
+    Ok(())
+}
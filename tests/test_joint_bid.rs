@@ -0,0 +1,154 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use near_workspaces::types::NearToken;
+use near_workspaces::{network::Sandbox, Account, Worker};
+use serde_json::json;
+
+// Joint bids
+// ---------------------------------------------------------------------------
+//
+// The bidder invites co-bidders, who each pay into the bid with a `CoFund`
+// transfer. Everyone's share is what they put in. A refunded bid pays each of
+// them back their own part; an accepted one lists them all on the lease.
+
+/// A fresh account holding `amount` of the stablecoin.
+async fn funded_account(
+    worker: &Worker<Sandbox>,
+    fx: &Fixture,
+    amount: u128,
+) -> TestResult<Account> {
+    let account = worker.dev_create_account().await?;
+    fx.ft
+        .call("storage_deposit")
+        .args_json(json!({ "account_id": account.id() }))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await?
+        .into_result()?;
+    fx.buyer
+        .call(fx.ft.id(), "ft_transfer")
+        .args_json(json!({ "receiver_id": account.id(), "amount": amount.to_string() }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(account)
+}
+
+/// Places a lease bid from the buyer with `partner` funding the same again.
+/// Returns `(property_id, bid_id)`.
+async fn joint_lease_bid(fx: &Fixture, partner: &Account) -> TestResult<(u64, u64)> {
+    let property_id = fx.mint_property(false).await?;
+    let bid_id = fx.place_bid(property_id, false).await?;
+
+    fx.buyer
+        .call(fx.contract.id(), "invite_co_bidders")
+        .args_json(json!({
+            "property_id": property_id,
+            "bid_id": bid_id,
+            "co_bidders": [partner.id()],
+        }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+    fx.transfer_call(
+        partner,
+        BID_AMOUNT,
+        json!({
+            "property_id": property_id,
+            "action": "Lease",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "CoFund": { "bid_id": bid_id } },
+        }),
+    )
+    .await?
+    .into_result()?;
+
+    Ok((property_id, bid_id))
+}
+
+/// Cancelling a joint bid pays each party back what they put in.
+#[tokio::test]
+async fn test_cancelled_joint_bid_refunds_each_party() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let partner = funded_account(&worker, &fx, BID_AMOUNT).await?;
+    let (property_id, bid_id) = joint_lease_bid(&fx, &partner).await?;
+
+    let parties: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_co_bidders")
+        .args_json(json!({ "property_id": property_id, "bid_id": bid_id }))
+        .await?
+        .json()?;
+    assert_eq!(parties.len(), 2);
+    assert_eq!(parties[0]["account_id"], fx.buyer.id().as_str());
+    assert_eq!(parties[0]["share_bps"], 5_000);
+    assert_eq!(parties[1]["contributed"], BID_AMOUNT.to_string());
+    assert_eq!(parties[1]["share_bps"], 5_000);
+
+    let buyer_before = fx.ft_balance(fx.buyer.id()).await?;
+    fx.buyer
+        .call(fx.contract.id(), "cancel_bid")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert_eq!(fx.ft_balance(partner.id()).await?, BID_AMOUNT);
+    assert_eq!(
+        fx.ft_balance(fx.buyer.id()).await?,
+        buyer_before + BID_AMOUNT
+    );
+
+    Ok(())
+}
+
+/// An accepted joint lease bid lists every party on the lease.
+#[tokio::test]
+async fn test_joint_lease_lists_co_tenants() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let partner = funded_account(&worker, &fx, BID_AMOUNT).await?;
+    let (property_id, bid_id) = joint_lease_bid(&fx, &partner).await?;
+
+    let owner_before = fx.ft_balance(fx.seller.id()).await?;
+    let lease_id = fx.accept_lease_bid(property_id, bid_id).await?;
+    assert_eq!(
+        fx.ft_balance(fx.seller.id()).await?,
+        owner_before + 2 * BID_AMOUNT
+    );
+
+    let co_tenants: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_lease_co_tenants")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    assert_eq!(co_tenants.len(), 2);
+    assert_eq!(co_tenants[1]["account_id"], partner.id().as_str());
+
+    let partner_leases: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_leases_by_tenant")
+        .args_json(json!({ "tenant_id": partner.id() }))
+        .await?
+        .json()?;
+    assert_eq!(partner_leases.len(), 1);
+
+    // The partner sees the bid among their own.
+    let joint: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_joint_bids_by_account")
+        .args_json(json!({ "account_id": partner.id() }))
+        .await?
+        .json()?;
+    assert_eq!(joint.len(), 1);
+    assert_eq!(joint[0]["bid_id"], bid_id);
+
+    Ok(())
+}
@@ -0,0 +1,270 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult};
+use near_workspaces::{types::NearToken, Account};
+use serde_json::json;
+
+// Juror panels
+// ---------------------------------------------------------------------------
+//
+// Jurors stake stablecoin to be drawn onto panels. A party to a raised
+// dispute pays the arbitration fee to have a panel decide it; panellists
+// commit to a vote, then reveal it. The majority shares the fee and what is
+// slashed from the minority, and the verdict resolves the dispute.
+
+const STAKE: u128 = 1_000;
+const FEE: u128 = 100;
+
+async fn set_terms(fx: &Fixture, panel_size: u32) -> TestResult {
+    fx.contract
+        .call("set_jury_terms")
+        .args_json(json!({
+            "terms": {
+                "stablecoin_token": fx.ft.id(),
+                "min_stake": STAKE.to_string(),
+                "arbitration_fee": FEE.to_string(),
+                "panel_size": panel_size,
+                "slash_bps": 1_000,
+                "commit_period_ns": 3_600_000_000_000u64,
+                "reveal_period_ns": 3_600_000_000_000u64,
+            }
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// A fresh account funded by the buyer and staked as a juror.
+async fn staked_juror(
+    worker: &near_workspaces::Worker<near_workspaces::network::Sandbox>,
+    fx: &Fixture,
+) -> TestResult<Account> {
+    let juror = worker.dev_create_account().await?;
+    fx.ft
+        .call("storage_deposit")
+        .args_json(json!({ "account_id": juror.id() }))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await?
+        .into_result()?;
+    fx.buyer
+        .call(fx.ft.id(), "ft_transfer")
+        .args_json(json!({ "receiver_id": juror.id(), "amount": STAKE.to_string() }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+    fx.transfer_call(&juror, STAKE, json!("StakeJuror"))
+        .await?
+        .into_result()?;
+    Ok(juror)
+}
+
+async fn vote(
+    juror: &Account,
+    fx: &Fixture,
+    case: &serde_json::Value,
+    for_raiser: bool,
+) -> TestResult {
+    let commitment: String = fx
+        .contract
+        .view("hash_jury_vote")
+        .args_json(json!({ "juror": juror.id(), "for_raiser": for_raiser, "salt": "pepper" }))
+        .await?
+        .json()?;
+    juror
+        .call(fx.contract.id(), "commit_jury_vote")
+        .args_json(json!({ "case": case, "commitment": commitment }))
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+async fn reveal(
+    juror: &Account,
+    fx: &Fixture,
+    case: &serde_json::Value,
+    for_raiser: bool,
+) -> TestResult {
+    juror
+        .call(fx.contract.id(), "reveal_jury_vote")
+        .args_json(json!({ "case": case, "for_raiser": for_raiser, "salt": "pepper" }))
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+async fn juror_stake(fx: &Fixture, juror: &Account) -> TestResult<u128> {
+    let view: serde_json::Value = fx
+        .contract
+        .view("get_juror")
+        .args_json(json!({ "account_id": juror.id() }))
+        .await?
+        .json()?;
+    Ok(view["stake"].as_str().unwrap_or_default().parse()?)
+}
+
+/// A panel of three finds for the buyer two to one: the bid is refunded, the
+/// dissenting juror is slashed, and the majority shares fee and slash.
+#[tokio::test]
+async fn test_panel_decides_bid_dispute() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    set_terms(&fx, 3).await?;
+    let mut jurors = Vec::new();
+    for _ in 0..3 {
+        jurors.push(staked_juror(&worker, &fx).await?);
+    }
+
+    let property_id = fx.mint_property(true).await?;
+    let bid_id = fx.disputed_bid(property_id).await?;
+    let case = json!({ "Bid": { "property_id": property_id, "bid_id": bid_id } });
+
+    // The excess over the fee comes back.
+    let buyer_before = fx.ft_balance(fx.buyer.id()).await?;
+    fx.transfer_call(
+        &fx.buyer,
+        FEE + 50,
+        json!({ "RequestArbitration": { "case": case } }),
+    )
+    .await?
+    .into_result()?;
+    assert_eq!(fx.ft_balance(fx.buyer.id()).await?, buyer_before - FEE);
+
+    let arbitration: serde_json::Value = fx
+        .contract
+        .view("get_arbitration")
+        .args_json(json!({ "case": case }))
+        .await?
+        .json()?;
+    assert_eq!(arbitration["panel"].as_array().map(Vec::len), Some(3));
+
+    // Admins stand aside while the panel sits.
+    let by_admin = fx
+        .contract
+        .call("admin_resolve_bid_dispute")
+        .args_json(json!({
+            "property_id": property_id,
+            "bid_id": bid_id,
+            "resolution": "SellerWins",
+        }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(by_admin.is_failure(), "an admin overrode the panel");
+
+    let votes = [true, true, false];
+    for (juror, for_raiser) in jurors.iter().zip(votes) {
+        vote(juror, &fx, &case, for_raiser).await?;
+    }
+    for (juror, for_raiser) in jurors.iter().zip(votes) {
+        reveal(juror, &fx, &case, for_raiser).await?;
+    }
+    let buyer_before = fx.ft_balance(fx.buyer.id()).await?;
+    fx.seller
+        .call(fx.contract.id(), "finalize_arbitration")
+        .args_json(json!({ "case": case }))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert_eq!(
+        fx.bid_status(property_id, bid_id).await?.as_deref(),
+        Some("Cancelled"),
+    );
+    assert_eq!(
+        fx.ft_balance(fx.buyer.id()).await?,
+        buyer_before + common::BID_AMOUNT,
+    );
+    // 10% of the dissenter's stake plus the fee, split between two.
+    let slashed = STAKE / 10;
+    let reward = (FEE + slashed) / 2;
+    assert_eq!(juror_stake(&fx, &jurors[0]).await?, STAKE + reward);
+    assert_eq!(juror_stake(&fx, &jurors[1]).await?, STAKE + reward);
+    assert_eq!(juror_stake(&fx, &jurors[2]).await?, STAKE - slashed);
+
+    // Off the panel, a juror can take their stake back.
+    let before = fx.ft_balance(jurors[2].id()).await?;
+    jurors[2]
+        .call(fx.contract.id(), "withdraw_juror_stake")
+        .args_json(json!({ "amount": null }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(
+        fx.ft_balance(jurors[2].id()).await?,
+        before + STAKE - slashed
+    );
+
+    Ok(())
+}
+
+/// A reveal that doesn't match the commitment is refused, and a panel can't
+/// be drawn from fewer jurors than it seats.
+#[tokio::test]
+async fn test_bad_reveal_and_short_pool_refused() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    set_terms(&fx, 3).await?;
+    let juror = staked_juror(&worker, &fx).await?;
+
+    let property_id = fx.mint_property(true).await?;
+    let bid_id = fx.disputed_bid(property_id).await?;
+    let case = json!({ "Bid": { "property_id": property_id, "bid_id": bid_id } });
+
+    // ft_on_transfer panics, so the token refunds the whole transfer.
+    let buyer_before = fx.ft_balance(fx.buyer.id()).await?;
+    let _refused = fx
+        .transfer_call(
+            &fx.buyer,
+            FEE,
+            json!({ "RequestArbitration": { "case": case } }),
+        )
+        .await?;
+    assert_eq!(fx.ft_balance(fx.buyer.id()).await?, buyer_before);
+    let arbitration: Option<serde_json::Value> = fx
+        .contract
+        .view("get_arbitration")
+        .args_json(json!({ "case": case }))
+        .await?
+        .json()?;
+    assert!(arbitration.is_none(), "a panel was drawn from one juror");
+
+    set_terms(&fx, 1).await?;
+    fx.transfer_call(
+        &fx.buyer,
+        FEE,
+        json!({ "RequestArbitration": { "case": case } }),
+    )
+    .await?
+    .into_result()?;
+    vote(&juror, &fx, &case, true).await?;
+    let mismatched = juror
+        .call(fx.contract.id(), "reveal_jury_vote")
+        .args_json(json!({ "case": case, "for_raiser": false, "salt": "pepper" }))
+        .transact()
+        .await?;
+    assert!(
+        mismatched.is_failure(),
+        "a reveal changed the committed vote"
+    );
+
+    // Sitting on a panel locks the stake.
+    let withdrawal = juror
+        .call(fx.contract.id(), "withdraw_juror_stake")
+        .args_json(json!({ "amount": null }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(withdrawal.is_failure(), "a panellist withdrew their stake");
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use serde_json::json;
+
+// Early lease termination
+// ---------------------------------------------------------------------------
+//
+// Either side gives notice; once `lease_early_termination_window_ns` has run
+// the lease ends as it would on expiry, with the NFT back with the owner. The
+// owner refunds the unused share of the lease payment, less the penalty when
+// the tenant gave notice and plus it when the owner did.
+
+const NOTICE_NS: u64 = 1_000_000_000;
+
+/// Lets a property with a short notice period and returns
+/// `(property_id, lease_id)`.
+async fn let_property(fx: &Fixture) -> TestResult<(u64, u64)> {
+    fx.contract
+        .call("set_cancellation_windows")
+        .args_json(json!({ "lease_early_termination_ns": NOTICE_NS }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    fx.let_property().await
+}
+
+async fn give_notice(fx: &Fixture, from: &near_workspaces::Account, lease_id: u64) -> TestResult {
+    from.call(fx.contract.id(), "give_lease_termination_notice")
+        .args_json(json!({ "lease_id": lease_id }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+async fn complete(
+    fx: &Fixture,
+    lease_id: u64,
+) -> TestResult<near_workspaces::result::ExecutionFinalResult> {
+    Ok(fx
+        .buyer
+        .call(fx.contract.id(), "complete_lease_termination")
+        .args_json(json!({ "lease_id": lease_id }))
+        .max_gas()
+        .transact()
+        .await?)
+}
+
+async fn termination(fx: &Fixture, lease_id: u64) -> TestResult<serde_json::Value> {
+    Ok(fx
+        .contract
+        .view("get_lease_termination")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?)
+}
+
+/// A tenant's notice ends the lease once it has run, and hands the NFT back.
+/// The owner can't hold the tenant to the lease by not paying the refund: it
+/// stays owed after the lease has ended, and can still be paid.
+#[tokio::test]
+async fn test_tenant_notice_ends_lease() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let (property_id, lease_id) = let_property(&fx).await?;
+
+    give_notice(&fx, &fx.buyer, lease_id).await?;
+    let notice = termination(&fx, lease_id).await?;
+    assert_eq!(notice["status"], "Noticed");
+    let unused: u128 = notice["unused"].as_str().unwrap_or_default().parse()?;
+    let penalty: u128 = notice["penalty"].as_str().unwrap_or_default().parse()?;
+    assert_eq!(penalty, BID_AMOUNT / 10, "default penalty is 10%");
+    assert_eq!(notice["refund_due"], (unused - penalty).to_string());
+
+    worker.fast_forward(5).await?;
+    complete(&fx, lease_id).await?.into_result()?;
+
+    let lease: serde_json::Value = fx
+        .contract
+        .view("get_lease_by_id")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    assert_eq!(lease["active"], false);
+    let notice = termination(&fx, lease_id).await?;
+    assert_eq!(notice["status"], "Completed");
+    assert_eq!(notice["refund_paid"], false, "the refund is still owed");
+
+    let token: serde_json::Value = fx
+        .contract
+        .view("nft_token")
+        .args_json(json!({ "token_id": property_id.to_string() }))
+        .await?
+        .json()?;
+    assert_eq!(token["owner_id"], fx.seller.id().as_str());
+
+    let tenant_before = fx.ft_balance(fx.buyer.id()).await?;
+    fx.transfer_call(
+        &fx.seller,
+        unused - penalty,
+        json!({
+            "property_id": property_id,
+            "action": "Lease",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "PayTerminationRefund": { "lease_id": lease_id } },
+        }),
+    )
+    .await?
+    .into_result()?;
+    assert_eq!(
+        fx.ft_balance(fx.buyer.id()).await?,
+        tenant_before + unused - penalty
+    );
+    assert_eq!(termination(&fx, lease_id).await?["refund_paid"], true);
+
+    Ok(())
+}
+
+/// The owner can't end the lease on their own notice without refunding the
+/// tenant first.
+#[tokio::test]
+async fn test_owner_notice_needs_refund() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let (property_id, lease_id) = let_property(&fx).await?;
+
+    give_notice(&fx, &fx.seller, lease_id).await?;
+    let refund_due: u128 = termination(&fx, lease_id).await?["refund_due"]
+        .as_str()
+        .unwrap_or_default()
+        .parse()?;
+
+    worker.fast_forward(5).await?;
+    assert!(complete(&fx, lease_id).await?.is_failure());
+
+    // The refund tops the lease payment up by the penalty, more than the
+    // owner was paid.
+    fx.buyer
+        .call(fx.ft.id(), "ft_transfer")
+        .args_json(json!({ "receiver_id": fx.seller.id(), "amount": BID_AMOUNT.to_string() }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+
+    let tenant_before = fx.ft_balance(fx.buyer.id()).await?;
+    fx.transfer_call(
+        &fx.seller,
+        refund_due,
+        json!({
+            "property_id": property_id,
+            "action": "Lease",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "PayTerminationRefund": { "lease_id": lease_id } },
+        }),
+    )
+    .await?
+    .into_result()?;
+    assert_eq!(
+        fx.ft_balance(fx.buyer.id()).await?,
+        tenant_before + refund_due
+    );
+
+    complete(&fx, lease_id).await?.into_result()?;
+    assert_eq!(termination(&fx, lease_id).await?["status"], "Completed");
+
+    Ok(())
+}
@@ -0,0 +1,240 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use serde_json::json;
+
+// Counter-offers
+// ---------------------------------------------------------------------------
+//
+// The seller answers a pending bid with a number of their own; the bidder can
+// counter, decline, or accept. Accepting moves the bid into escrow at the
+// offered amount — topping up through ft_transfer_call when the offer is
+// higher, refunding the difference when it is lower.
+
+const DIFFERENCE: u128 = 20_000_000;
+
+async fn counter_offer(fx: &Fixture, property_id: u64, bid_id: u64, amount: u128) -> TestResult {
+    fx.seller
+        .call(fx.contract.id(), "counter_offer")
+        .args_json(json!({
+            "property_id": property_id,
+            "bid_id": bid_id,
+            "amount": amount.to_string(),
+            "terms": "Completion within 30 days",
+        }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+async fn negotiation(fx: &Fixture, bid_id: u64) -> TestResult<Vec<serde_json::Value>> {
+    Ok(fx
+        .contract
+        .view("get_negotiation")
+        .args_json(json!({ "bid_id": bid_id }))
+        .await?
+        .json()?)
+}
+
+/// Back-and-forth ending in a higher price the bidder tops up to; anything
+/// sent beyond the difference comes back.
+#[tokio::test]
+async fn test_counter_offer_accepted_with_top_up() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(true).await?;
+    let bid_id = fx.place_bid(property_id, true).await?;
+    let after_bid = fx.ft_balance(fx.buyer.id()).await?;
+
+    counter_offer(&fx, property_id, bid_id, BID_AMOUNT + DIFFERENCE * 2).await?;
+
+    fx.buyer
+        .call(fx.contract.id(), "counter_seller_offer")
+        .args_json(json!({
+            "property_id": property_id,
+            "bid_id": bid_id,
+            "amount": (BID_AMOUNT + DIFFERENCE / 2).to_string(),
+            "terms": null,
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+
+    // Only the bidder's move now; the seller meets them partway.
+    counter_offer(&fx, property_id, bid_id, BID_AMOUNT + DIFFERENCE).await?;
+
+    fx.transfer_call(
+        &fx.buyer,
+        DIFFERENCE * 2,
+        json!({
+            "property_id": property_id,
+            "action": "Purchase",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "AcceptCounterOffer": { "bid_id": bid_id } },
+        }),
+    )
+    .await?
+    .into_result()?;
+
+    assert_eq!(
+        fx.bid_status(property_id, bid_id).await?.as_deref(),
+        Some("Accepted")
+    );
+    assert_eq!(
+        fx.ft_balance(fx.buyer.id()).await?,
+        after_bid - DIFFERENCE,
+        "only the difference should have been kept"
+    );
+
+    let rounds = negotiation(&fx, bid_id).await?;
+    let statuses: Vec<&str> = rounds
+        .iter()
+        .map(|round| round["status"].as_str().unwrap_or_default())
+        .collect();
+    assert_eq!(statuses, ["Countered", "Countered", "Accepted"]);
+
+    Ok(())
+}
+
+/// A lower counter-offer refunds the difference; a declined one leaves the
+/// bid untouched.
+#[tokio::test]
+async fn test_counter_offer_below_bid_and_decline() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(true).await?;
+    let bid_id = fx.place_bid(property_id, true).await?;
+    let after_bid = fx.ft_balance(fx.buyer.id()).await?;
+
+    counter_offer(&fx, property_id, bid_id, BID_AMOUNT - DIFFERENCE).await?;
+    fx.buyer
+        .call(fx.contract.id(), "decline_counter_offer")
+        .args_json(json!({ "property_id": property_id, "bid_id": bid_id }))
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(
+        fx.bid_status(property_id, bid_id).await?.as_deref(),
+        Some("Pending")
+    );
+
+    // Nothing left to accept once declined.
+    let stale = fx
+        .buyer
+        .call(fx.contract.id(), "accept_counter_offer")
+        .args_json(json!({ "property_id": property_id, "bid_id": bid_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(stale.is_failure());
+
+    counter_offer(&fx, property_id, bid_id, BID_AMOUNT - DIFFERENCE).await?;
+    fx.buyer
+        .call(fx.contract.id(), "accept_counter_offer")
+        .args_json(json!({ "property_id": property_id, "bid_id": bid_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert_eq!(
+        fx.bid_status(property_id, bid_id).await?.as_deref(),
+        Some("Accepted")
+    );
+    assert_eq!(fx.ft_balance(fx.buyer.id()).await?, after_bid + DIFFERENCE);
+
+    Ok(())
+}
+
+// Amending a bid
+// ---------------------------------------------------------------------------
+//
+// A `TopUp` transfer raises a pending bid and `reduce_bid` hands part of it
+// back, both without giving up the bid's id (and with it, its place).
+
+/// Raising a bid in place and withdrawing part of it keep the same bid id
+/// and move exactly the difference.
+#[tokio::test]
+async fn test_top_up_and_reduce_bid() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(true).await?;
+    let bid_id = fx.place_bid(property_id, true).await?;
+    let after_bid = fx.ft_balance(fx.buyer.id()).await?;
+
+    fx.transfer_call(
+        &fx.buyer,
+        DIFFERENCE,
+        json!({
+            "property_id": property_id,
+            "action": "Purchase",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "TopUp": { "bid_id": bid_id } },
+        }),
+    )
+    .await?
+    .into_result()?;
+
+    let bid_amount = |bids: Vec<serde_json::Value>| {
+        bids.into_iter()
+            .find(|b| b["id"].as_u64() == Some(bid_id))
+            .and_then(|b| b["bid_amount"].as_str().map(str::to_string))
+    };
+    let bids: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_bids_for_property")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    assert_eq!(
+        bid_amount(bids),
+        Some((BID_AMOUNT + DIFFERENCE).to_string())
+    );
+    assert_eq!(fx.bid_counter().await?, bid_id + 1, "no new bid created");
+
+    fx.buyer
+        .call(fx.contract.id(), "reduce_bid")
+        .args_json(json!({
+            "property_id": property_id,
+            "bid_id": bid_id,
+            "amount": (DIFFERENCE * 2).to_string(),
+        }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    let bids: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_bids_for_property")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    assert_eq!(
+        bid_amount(bids),
+        Some((BID_AMOUNT - DIFFERENCE).to_string())
+    );
+    assert_eq!(fx.ft_balance(fx.buyer.id()).await?, after_bid + DIFFERENCE);
+
+    // Reducing to zero is a cancellation, and has its own entrypoint.
+    let to_zero = fx
+        .buyer
+        .call(fx.contract.id(), "reduce_bid")
+        .args_json(json!({
+            "property_id": property_id,
+            "bid_id": bid_id,
+            "amount": (BID_AMOUNT - DIFFERENCE).to_string(),
+        }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(to_zero.is_failure());
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+mod common;
+
+use common::{setup, Fixture, TestResult, BID_AMOUNT};
+use serde_json::json;
+
+// Owner-raised lease disputes
+// ---------------------------------------------------------------------------
+//
+// A landlord can raise a dispute on a lease with a claim against its damage
+// deposit. It waits on the tenant: contesting it within the response period
+// takes it to the admins, and staying silent lets anyone settle it for the
+// owner.
+
+const DEPOSIT: u128 = BID_AMOUNT / 5;
+const CLAIM: u128 = BID_AMOUNT / 10;
+
+async fn raise(
+    fx: &Fixture,
+    lease_id: u64,
+    claim: u128,
+) -> TestResult<near_workspaces::result::ExecutionFinalResult> {
+    Ok(fx
+        .seller
+        .call(fx.contract.id(), "raise_owner_lease_dispute")
+        .args_json(json!({
+            "lease_id": lease_id,
+            "reason": "Kitchen left water-damaged",
+            "claim": claim.to_string(),
+        }))
+        .transact()
+        .await?)
+}
+
+/// What the contract's escrow ledger says it holds, against what it actually
+/// holds in the token.
+async fn escrow_and_holdings(fx: &Fixture) -> TestResult<(u128, u128)> {
+    let ledger: String = fx
+        .contract
+        .view("get_stablecoin_balance")
+        .args_json(json!({ "token_account": fx.ft.id() }))
+        .await?
+        .json()?;
+    Ok((ledger.parse()?, fx.ft_balance(fx.contract.id()).await?))
+}
+
+async fn dispute_status(fx: &Fixture, lease_id: u64) -> TestResult<String> {
+    let lease: serde_json::Value = fx
+        .contract
+        .view("get_lease_by_id")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    Ok(lease["dispute_status"]["status"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// The tenant contests in time and the dispute goes to the admins.
+#[tokio::test]
+async fn test_tenant_contests_owner_dispute() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let (_, lease_id) = fx.let_property_with_deposit(DEPOSIT).await?;
+
+    raise(&fx, lease_id, CLAIM).await?.into_result()?;
+    assert_eq!(
+        dispute_status(&fx, lease_id).await?,
+        "pending_tenant_response"
+    );
+
+    let early = fx
+        .seller
+        .call(fx.contract.id(), "settle_uncontested_lease_dispute")
+        .args_json(json!({ "lease_id": lease_id }))
+        .max_gas()
+        .transact()
+        .await?;
+    assert!(early.is_failure());
+
+    fx.buyer
+        .call(fx.contract.id(), "contest_lease_dispute")
+        .args_json(json!({ "lease_id": lease_id }))
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(dispute_status(&fx, lease_id).await?, "raised");
+
+    Ok(())
+}
+
+/// A tenant who lets the response period pass loses the claim to the owner,
+/// paid out of the damage deposit: the contract never pays out more than it
+/// holds for the lease.
+#[tokio::test]
+async fn test_silent_tenant_settles_for_owner() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    fx.contract
+        .call("set_dispute_response_period")
+        .args_json(json!({ "period_ns": 0 }))
+        .transact()
+        .await?
+        .into_result()?;
+    let (_, lease_id) = fx.let_property_with_deposit(DEPOSIT).await?;
+    // Another buyer's pending bid sits in the same pool; the claim mustn't
+    // reach it.
+    let other = fx.mint_property(true).await?;
+    fx.place_bid(other, true).await?;
+
+    // The lease payment is already the owner's: only the deposit is claimable.
+    let too_much = raise(&fx, lease_id, DEPOSIT + 1).await?;
+    assert!(too_much.is_failure(), "claimed beyond the damage deposit");
+    raise(&fx, lease_id, CLAIM).await?.into_result()?;
+    let late = fx
+        .buyer
+        .call(fx.contract.id(), "contest_lease_dispute")
+        .args_json(json!({ "lease_id": lease_id }))
+        .transact()
+        .await?;
+    assert!(late.is_failure());
+
+    let owner_before = fx.ft_balance(fx.seller.id()).await?;
+    fx.buyer
+        .call(fx.contract.id(), "settle_uncontested_lease_dispute")
+        .args_json(json!({ "lease_id": lease_id }))
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+    assert_eq!(dispute_status(&fx, lease_id).await?, "resolved");
+    assert_eq!(fx.ft_balance(fx.seller.id()).await?, owner_before + CLAIM);
+
+    let deposit: serde_json::Value = fx
+        .contract
+        .view("get_damage_deposit")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    assert_eq!(deposit["amount"], (DEPOSIT - CLAIM).to_string());
+    let (ledger, holdings) = escrow_and_holdings(&fx).await?;
+    assert_eq!(
+        ledger,
+        BID_AMOUNT + DEPOSIT - CLAIM,
+        "the pending bid and the rest of the deposit are still owed"
+    );
+    assert!(
+        holdings >= ledger,
+        "the contract paid out more than it held"
+    );
+
+    Ok(())
+}
+
+/// A lease with no damage deposit can still be disputed by its owner, just
+/// not with a claim nothing backs.
+#[tokio::test]
+async fn test_owner_dispute_without_deposit() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let (_, lease_id) = fx.let_property().await?;
+
+    let unbacked = raise(&fx, lease_id, CLAIM).await?;
+    assert!(unbacked.is_failure(), "claimed with no deposit or arrears");
+    raise(&fx, lease_id, 0).await?.into_result()?;
+    assert_eq!(
+        dispute_status(&fx, lease_id).await?,
+        "pending_tenant_response"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use near_workspaces::types::NearToken;
+use serde_json::json;
+
+// Payout splits
+// ---------------------------------------------------------------------------
+//
+// An owner can have what a property earns split between several accounts by
+// basis points. Every owner-bound payment follows the split; a transfer that
+// fails is held for its recipient to claim rather than holding up the rest.
+
+async fn set_split(
+    fx: &Fixture,
+    property_id: u64,
+    recipients: serde_json::Value,
+) -> TestResult<near_workspaces::result::ExecutionFinalResult> {
+    Ok(fx
+        .seller
+        .call(fx.contract.id(), "set_payout_split")
+        .args_json(json!({ "property_id": property_id, "recipients": recipients }))
+        .deposit(one_yocto())
+        .transact()
+        .await?)
+}
+
+/// A 60/40 split of a lease payment between the owner and a partner.
+#[tokio::test]
+async fn test_lease_payment_follows_split() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(false).await?;
+
+    let partner = worker.dev_create_account().await?;
+    fx.ft
+        .call("storage_deposit")
+        .args_json(json!({ "account_id": partner.id() }))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await?
+        .into_result()?;
+
+    set_split(
+        &fx,
+        property_id,
+        json!([
+            { "account_id": fx.seller.id(), "bps": 6_000 },
+            { "account_id": partner.id(), "bps": 4_000 },
+        ]),
+    )
+    .await?
+    .into_result()?;
+    let split: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_payout_split")
+        .args_json(json!({ "property_id": property_id }))
+        .await?
+        .json()?;
+    assert_eq!(split.len(), 2);
+
+    let bid_id = fx.place_bid(property_id, false).await?;
+    let owner_before = fx.ft_balance(fx.seller.id()).await?;
+    fx.seller
+        .call(fx.contract.id(), "accept_bid")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert_eq!(
+        fx.ft_balance(fx.seller.id()).await?,
+        owner_before + BID_AMOUNT * 6 / 10
+    );
+    assert_eq!(fx.ft_balance(partner.id()).await?, BID_AMOUNT * 4 / 10);
+
+    Ok(())
+}
+
+/// Shares have to cover the whole payment, once per recipient.
+#[tokio::test]
+async fn test_split_must_add_up() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(true).await?;
+
+    let short = set_split(
+        &fx,
+        property_id,
+        json!([
+            { "account_id": fx.seller.id(), "bps": 5_000 },
+            { "account_id": fx.buyer.id(), "bps": 4_000 },
+        ]),
+    )
+    .await?;
+    assert!(short.is_failure());
+
+    let repeated = set_split(
+        &fx,
+        property_id,
+        json!([
+            { "account_id": fx.seller.id(), "bps": 5_000 },
+            { "account_id": fx.seller.id(), "bps": 5_000 },
+        ]),
+    )
+    .await?;
+    assert!(repeated.is_failure());
+
+    let balances: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_claimable_balances")
+        .args_json(json!({ "account_id": fx.seller.id() }))
+        .await?
+        .json()?;
+    assert!(balances.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use serde_json::json;
+
+// Recurring rent
+// ---------------------------------------------------------------------------
+//
+// The owner sets rent terms on the property; every lease that starts while
+// they're set gets a schedule. The tenant pays through `ft_transfer_call`
+// with a `PayRent` message, each payment goes straight on to the owner, and a
+// period paid after its grace period carries the late fee.
+
+const RENT: u128 = BID_AMOUNT / 10;
+const PERIOD_NS: u64 = 60_000_000_000;
+const LATE_FEE_BPS: u16 = 1_000;
+
+/// Lets a property on rent terms and returns `(property_id, lease_id)`.
+async fn let_on_rent(fx: &Fixture) -> TestResult<(u64, u64)> {
+    let property_id = fx.mint_property(false).await?;
+    fx.seller
+        .call(fx.contract.id(), "set_rent_terms")
+        .args_json(json!({
+            "property_id": property_id,
+            "amount": RENT.to_string(),
+            "period_ns": PERIOD_NS,
+            "late_fee_bps": LATE_FEE_BPS,
+            "grace_period_ns": 0,
+        }))
+        .deposit(one_yocto())
+        .transact()
+        .await?
+        .into_result()?;
+
+    let lease_id = fx.let_to_buyer(property_id).await?;
+    Ok((property_id, lease_id))
+}
+
+async fn pay_rent(fx: &Fixture, property_id: u64, lease_id: u64, amount: u128) -> TestResult {
+    fx.transfer_call(
+        &fx.buyer,
+        amount,
+        json!({
+            "property_id": property_id,
+            "action": "Lease",
+            "stablecoin_token": fx.ft.id(),
+            "kind": { "PayRent": { "lease_id": lease_id } },
+        }),
+    )
+    .await?
+    .into_result()?;
+    Ok(())
+}
+
+/// Rent paid ahead of time carries no fee and goes to the owner; a period
+/// left past its due date shows up in arrears and is charged the late fee.
+#[tokio::test]
+async fn test_rent_paid_in_advance_then_late() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let (property_id, lease_id) = let_on_rent(&fx).await?;
+
+    let owner_before = fx.ft_balance(fx.seller.id()).await?;
+    pay_rent(&fx, property_id, lease_id, RENT).await?;
+    assert_eq!(fx.ft_balance(fx.seller.id()).await?, owner_before + RENT);
+
+    let schedule: serde_json::Value = fx
+        .contract
+        .view("get_rent_schedule")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    assert_eq!(schedule["periods_paid"], 1);
+    assert_eq!(schedule["total_late_fees"], "0");
+
+    worker.fast_forward(200).await?;
+
+    let arrears: serde_json::Value = fx
+        .contract
+        .view("get_rent_arrears")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    assert!(arrears["periods_overdue"].as_u64().unwrap_or_default() >= 1);
+    let by_owner: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_rent_arrears_by_owner")
+        .args_json(json!({ "owner_id": fx.seller.id() }))
+        .await?
+        .json()?;
+    assert_eq!(by_owner.len(), 1);
+
+    let late_fee = RENT * u128::from(LATE_FEE_BPS) / 10_000;
+    pay_rent(&fx, property_id, lease_id, RENT + late_fee).await?;
+
+    let payments: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_rent_payments")
+        .args_json(json!({ "lease_id": lease_id, "from_index": 0, "limit": 10 }))
+        .await?
+        .json()?;
+    assert_eq!(payments.len(), 2);
+    assert_eq!(payments[0]["late_fee"], "0");
+    assert_eq!(payments[1]["late_fee"], late_fee.to_string());
+
+    Ok(())
+}
+
+/// Less than a period's rent is refused outright rather than held as credit.
+#[tokio::test]
+async fn test_partial_rent_is_refunded() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let (property_id, lease_id) = let_on_rent(&fx).await?;
+
+    let before = fx.ft_balance(fx.buyer.id()).await?;
+    pay_rent(&fx, property_id, lease_id, RENT / 2).await?;
+    assert_eq!(fx.ft_balance(fx.buyer.id()).await?, before);
+
+    let payments: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_rent_payments")
+        .args_json(json!({ "lease_id": lease_id, "from_index": 0, "limit": 10 }))
+        .await?
+        .json()?;
+    assert!(payments.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult, BID_AMOUNT};
+use near_workspaces::{network::Sandbox, types::NearToken, Account, Worker};
+use serde_json::json;
+
+// Royalties
+// ---------------------------------------------------------------------------
+//
+// A property can carry royalties, paid on every sale: here when a purchase
+// completes, and on other marketplaces through NEP-199 `nft_payout`.
+
+/// A developer account registered with the stablecoin.
+async fn developer(worker: &Worker<Sandbox>, fx: &Fixture) -> TestResult<Account> {
+    let account = worker.dev_create_account().await?;
+    fx.ft
+        .call("storage_deposit")
+        .args_json(json!({ "account_id": account.id() }))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(account)
+}
+
+async fn set_royalties(
+    fx: &Fixture,
+    property_id: u64,
+    royalties: serde_json::Value,
+) -> TestResult<near_workspaces::result::ExecutionFinalResult> {
+    Ok(fx
+        .seller
+        .call(fx.contract.id(), "set_property_royalties")
+        .args_json(json!({ "property_id": property_id, "royalties": royalties }))
+        .deposit(one_yocto())
+        .transact()
+        .await?)
+}
+
+/// `nft_payout` shares a sale between the royalty holder and the seller,
+/// and the owner can't rewrite the royalties once set.
+#[tokio::test]
+async fn test_nft_payout_includes_royalties() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(true).await?;
+    let developer = developer(&worker, &fx).await?;
+
+    set_royalties(
+        &fx,
+        property_id,
+        json!([{ "account_id": developer.id(), "bps": 1_000 }]),
+    )
+    .await?
+    .into_result()?;
+
+    let payout: serde_json::Value = fx
+        .contract
+        .view("nft_payout")
+        .args_json(json!({
+            "token_id": property_id.to_string(),
+            "balance": "1000",
+            "max_len_payout": 10,
+        }))
+        .await?
+        .json()?;
+    assert_eq!(payout["payout"][developer.id().as_str()], "100");
+    assert_eq!(payout["payout"][fx.seller.id().as_str()], "900");
+
+    let rewrite = set_royalties(&fx, property_id, json!([])).await?;
+    assert!(rewrite.is_failure());
+
+    Ok(())
+}
+
+/// Completing a purchase pays the royalty out of the seller's proceeds.
+#[tokio::test]
+async fn test_purchase_pays_royalty() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let property_id = fx.mint_property(true).await?;
+    let developer = developer(&worker, &fx).await?;
+
+    set_royalties(
+        &fx,
+        property_id,
+        json!([{ "account_id": developer.id(), "bps": 500 }]),
+    )
+    .await?
+    .into_result()?;
+
+    let bid_id = fx.place_bid(property_id, true).await?;
+    let seller_before = fx.ft_balance(fx.seller.id()).await?;
+    fx.seller
+        .call(fx.contract.id(), "accept_bid")
+        .args_json(json!({ "bid_id": bid_id, "property_id": property_id }))
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?
+        .into_result()?;
+
+    assert_eq!(
+        fx.ft_balance(fx.seller.id()).await?,
+        seller_before + BID_AMOUNT * 95 / 100
+    );
+    assert_eq!(fx.ft_balance(developer.id()).await?, BID_AMOUNT * 5 / 100);
+    assert_eq!(
+        fx.property_owner(property_id).await?.as_deref(),
+        Some(fx.buyer.id().as_str())
+    );
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use common::{one_yocto, setup, Fixture, TestResult};
+use near_workspaces::Account;
+use serde_json::json;
+
+// Lease assignment and subleasing
+// ---------------------------------------------------------------------------
+//
+// A tenant can hand the rest of their lease to someone else once the owner
+// has consented, or sublet part of it while staying on the hook themselves.
+// Either way the NFT goes to whoever is living there.
+
+async fn call(
+    from: &Account,
+    fx: &Fixture,
+    method: &str,
+    args: serde_json::Value,
+) -> TestResult<near_workspaces::result::ExecutionFinalResult> {
+    Ok(from
+        .call(fx.contract.id(), method)
+        .args_json(args)
+        .deposit(one_yocto())
+        .max_gas()
+        .transact()
+        .await?)
+}
+
+async fn token_owner(fx: &Fixture, property_id: u64) -> TestResult<String> {
+    let token: serde_json::Value = fx
+        .contract
+        .view("nft_token")
+        .args_json(json!({ "token_id": property_id.to_string() }))
+        .await?
+        .json()?;
+    Ok(token["owner_id"].as_str().unwrap_or_default().to_string())
+}
+
+/// The assignee can't take the lease over until the owner consents; once
+/// they do, the lease and the NFT are theirs.
+#[tokio::test]
+async fn test_assignment_needs_owner_consent() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let (property_id, lease_id) = fx.let_property().await?;
+    let assignee = worker.dev_create_account().await?;
+
+    call(
+        &fx.buyer,
+        &fx,
+        "propose_lease_assignment",
+        json!({ "lease_id": lease_id, "assignee": assignee.id() }),
+    )
+    .await?
+    .into_result()?;
+
+    let early = call(
+        &assignee,
+        &fx,
+        "accept_lease_assignment",
+        json!({ "lease_id": lease_id }),
+    )
+    .await?;
+    assert!(early.is_failure(), "accepted without the owner's consent");
+
+    call(
+        &fx.seller,
+        &fx,
+        "consent_to_lease_assignment",
+        json!({ "lease_id": lease_id }),
+    )
+    .await?
+    .into_result()?;
+    call(
+        &assignee,
+        &fx,
+        "accept_lease_assignment",
+        json!({ "lease_id": lease_id }),
+    )
+    .await?
+    .into_result()?;
+
+    let lease: serde_json::Value = fx
+        .contract
+        .view("get_lease_by_id")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    assert_eq!(lease["tenant_id"], assignee.id().as_str());
+    assert_eq!(token_owner(&fx, property_id).await?, assignee.id().as_str());
+
+    let assignee_leases: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_leases_by_tenant")
+        .args_json(json!({ "tenant_id": assignee.id() }))
+        .await?
+        .json()?;
+    assert_eq!(assignee_leases.len(), 1);
+    let previous_leases: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_leases_by_tenant")
+        .args_json(json!({ "tenant_id": fx.buyer.id() }))
+        .await?
+        .json()?;
+    assert!(previous_leases.is_empty());
+
+    Ok(())
+}
+
+/// A sublease is a lease of its own under the primary one, with the
+/// subtenant holding the NFT.
+#[tokio::test]
+async fn test_sublease_moves_nft_to_subtenant() -> TestResult {
+    let worker = near_workspaces::sandbox().await?;
+    let fx = setup(&worker).await?;
+    let (property_id, lease_id) = fx.let_property().await?;
+    let subtenant = worker.dev_create_account().await?;
+
+    let parent: serde_json::Value = fx
+        .contract
+        .view("get_lease_by_id")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    let end_time = parent["end_time"].as_u64().unwrap_or_default();
+
+    // Not past the end of the primary lease.
+    let too_long = call(
+        &fx.buyer,
+        &fx,
+        "offer_sublease",
+        json!({ "lease_id": lease_id, "subtenant_id": subtenant.id(), "end_time": end_time + 1 }),
+    )
+    .await?;
+    assert!(too_long.is_failure());
+
+    call(
+        &fx.buyer,
+        &fx,
+        "offer_sublease",
+        json!({ "lease_id": lease_id, "subtenant_id": subtenant.id(), "end_time": end_time }),
+    )
+    .await?
+    .into_result()?;
+    let sublease_id: u64 = call(
+        &subtenant,
+        &fx,
+        "accept_sublease",
+        json!({ "lease_id": lease_id }),
+    )
+    .await?
+    .json()?;
+
+    assert_eq!(
+        token_owner(&fx, property_id).await?,
+        subtenant.id().as_str()
+    );
+    let subleases: Vec<serde_json::Value> = fx
+        .contract
+        .view("get_subleases")
+        .args_json(json!({ "lease_id": lease_id }))
+        .await?
+        .json()?;
+    assert_eq!(subleases.len(), 1);
+    assert_eq!(subleases[0]["tenant_id"], subtenant.id().as_str());
+    let parent_id: Option<u64> = fx
+        .contract
+        .view("get_parent_lease")
+        .args_json(json!({ "lease_id": sublease_id }))
+        .await?
+        .json()?;
+    assert_eq!(parent_id, Some(lease_id));
+
+    // A sublet lease can't be handed on again.
+    let assign = call(
+        &fx.buyer,
+        &fx,
+        "propose_lease_assignment",
+        json!({ "lease_id": lease_id, "assignee": fx.seller.id() }),
+    )
+    .await?;
+    assert!(assign.is_failure());
+
+    Ok(())
+}